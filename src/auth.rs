@@ -0,0 +1,29 @@
+use keyring::Entry;
+
+const KEYRING_SERVICE: &str = "gemini-commit-message";
+const KEYRING_USER: &str = "default";
+
+fn entry(profile: Option<&str>) -> Result<Entry, Box<dyn std::error::Error>> {
+    Ok(Entry::new(
+        KEYRING_SERVICE,
+        profile.unwrap_or(KEYRING_USER),
+    )?)
+}
+
+pub fn save_api_key(
+    profile: Option<&str>,
+    api_key: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    entry(profile)?.set_password(api_key)?;
+    Ok(())
+}
+
+/// Returns `None` when the keyring is unavailable or holds no key.
+pub fn load_api_key(profile: Option<&str>) -> Option<String> {
+    entry(profile).ok()?.get_password().ok()
+}
+
+pub fn delete_api_key(profile: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    entry(profile)?.delete_credential()?;
+    Ok(())
+}
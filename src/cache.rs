@@ -0,0 +1,86 @@
+//! On-disk cache of generated messages and file summaries, keyed by a hash
+//! of the request.
+
+use sha2::{Digest, Sha256};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+/// `$XDG_CACHE_HOME`, or the platform's usual per-user cache directory.
+pub(crate) fn cache_home() -> Option<PathBuf> {
+    let var = |name| {
+        env::var_os(name)
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+    };
+    if let Some(dir) = var("XDG_CACHE_HOME") {
+        return Some(dir);
+    }
+    if cfg!(windows) {
+        return var("LOCALAPPDATA");
+    }
+    let home = var("HOME")?;
+    Some(if cfg!(target_os = "macos") {
+        home.join("Library").join("Caches")
+    } else {
+        home.join(".cache")
+    })
+}
+
+/// Normalizes line endings and trailing whitespace so that a diff that
+/// only differs in those still hits the cache.
+pub fn normalize_diff(diff: &str) -> String {
+    diff.lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// Hex SHA-256 of `parts`, each terminated by a NUL so that boundaries count.
+pub fn key(parts: &[&str]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+pub struct Cache {
+    dir: PathBuf,
+}
+
+impl Cache {
+    /// The cache under the user's cache directory, if one can be determined.
+    pub fn open() -> Option<Self> {
+        Some(Self {
+            dir: cache_home()?.join(env!("CARGO_PKG_NAME")),
+        })
+    }
+
+    /// A cache in `dir`, e.g. for tests.
+    pub fn at(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    pub fn get(&self, key: &str) -> Option<Vec<String>> {
+        let text = fs::read_to_string(self.path(key)).ok()?;
+        serde_json::from_str(&text).ok()
+    }
+
+    pub fn put(&self, key: &str, messages: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.path(key), serde_json::to_string(messages)?)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+//! Keep a Changelog sections built from Conventional Commits.
+
+use crate::commits::Commit;
+
+pub const HEADER: &str = "# Changelog\n\nAll notable changes to this project will be documented in this file.\n\nThe format is based on [Keep a Changelog](https://keepachangelog.com/en/1.1.0/),\nand this project adheres to [Semantic Versioning](https://semver.org/spec/v2.0.0.html).\n";
+
+/// Keep a Changelog categories in the order they are written.
+const CATEGORIES: [&str; 4] = ["Added", "Changed", "Removed", "Fixed"];
+
+/// Maps a commit to its category; chores, tests, CI and docs are left out.
+fn category(commit: &Commit) -> Option<&'static str> {
+    let header = commit.message.conventional()?;
+    if commit.message.is_breaking() {
+        return Some("Changed");
+    }
+    match header.kind.to_lowercase().as_str() {
+        "feat" => Some("Added"),
+        "fix" => Some("Fixed"),
+        "perf" | "refactor" => Some("Changed"),
+        "revert" => Some("Removed"),
+        _ => None,
+    }
+}
+
+fn entry(commit: &Commit) -> Option<String> {
+    let header = commit.message.conventional()?;
+    let breaking = if commit.message.is_breaking() {
+        "**BREAKING:** "
+    } else {
+        ""
+    };
+    let scope = header
+        .scope
+        .map_or(String::new(), |scope| format!("**{}:** ", scope));
+    Some(format!(
+        "- {}{}{} ({})",
+        breaking,
+        scope,
+        header.description,
+        commit.short_id()
+    ))
+}
+
+/// A `## [version] - date` section; commits that do not follow Conventional
+/// Commits or only touch internals are skipped.
+pub fn section(version: &str, date: Option<&str>, commits: &[Commit]) -> String {
+    let mut text = match date {
+        Some(date) => format!("## [{}] - {}\n", version, date),
+        None => format!("## [{}]\n", version),
+    };
+    for name in CATEGORIES {
+        let entries: Vec<String> = commits
+            .iter()
+            .filter(|commit| category(commit) == Some(name))
+            .filter_map(entry)
+            .collect();
+        if entries.is_empty() {
+            continue;
+        }
+        text.push_str(&format!("\n### {}\n\n{}\n", name, entries.join("\n")));
+    }
+    text
+}
+
+/// Inserts `section` above the newest release of an existing changelog, or
+/// starts a new one.
+pub fn insert(changelog: Option<&str>, section: &str) -> String {
+    let changelog = match changelog {
+        Some(text) if !text.trim().is_empty() => text,
+        _ => return format!("{}\n{}", HEADER, section),
+    };
+    if changelog.starts_with("## ") {
+        return format!("{}\n{}", section, changelog);
+    }
+    match changelog.find("\n## ") {
+        Some(at) => format!("{}{}\n{}", &changelog[..=at], section, &changelog[at + 1..]),
+        None => format!("{}\n\n{}", changelog.trim_end(), section),
+    }
+}
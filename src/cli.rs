@@ -0,0 +1,1143 @@
+pub mod auth;
+pub mod batch;
+pub mod branch_name;
+pub mod changelog;
+pub mod ci;
+pub mod clipboard;
+pub mod commit;
+pub mod completions;
+pub mod config;
+pub mod doctor;
+pub mod explain;
+pub mod history;
+pub mod hook;
+pub mod learn;
+pub mod lint;
+pub mod mcp;
+pub mod models;
+pub mod next_version;
+pub mod pr;
+pub mod prompt;
+pub mod release;
+pub mod revert;
+pub mod review;
+pub mod rpc;
+pub mod select;
+pub mod self_update;
+pub mod serve;
+pub mod spinner;
+pub mod split;
+pub mod squash;
+pub mod stash;
+pub mod unstaged;
+pub mod watch;
+
+use gemini_commit_message::cache::{self, Cache};
+use gemini_commit_message::config::GitConfig;
+use gemini_commit_message::config::{
+    Config, DEFAULT_MODEL, DEFAULT_PROVIDER, Overrides, Setting, parse_bool,
+};
+use gemini_commit_message::generator::{DEFAULT_CONCURRENCY, DEFAULT_RECENT_COMMITS};
+use gemini_commit_message::i18n::{Message, tr};
+use gemini_commit_message::learn::DEFAULT_CORRECTIONS;
+use gemini_commit_message::message::Footer;
+use gemini_commit_message::preset::{self, Preset};
+use gemini_commit_message::rate::RateLimit;
+use gemini_commit_message::style::Style;
+use gemini_commit_message::subject::Mood;
+use gemini_commit_message::{
+    CommitMessage, DiffSource, Error, Exec, Gemini, Generator, Mock, git, log,
+};
+use serde_json::Value;
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+/// Returns the value of `--name=value` or `--name value` at `args[*i]`,
+/// advancing `i` past a separate value argument. A flag without its value
+/// at the end of `args` is an error.
+pub fn flag_value(args: &[String], i: &mut usize, name: &str) -> Result<Option<String>, Error> {
+    let a = &args[*i];
+    if let Some(rest) = a.strip_prefix(name).and_then(|r| r.strip_prefix('=')) {
+        return Ok(Some(rest.to_string()));
+    }
+    if a == name {
+        let Some(value) = args.get(*i + 1) else {
+            return Err(Error::Config(tr(Message::MissingFlagValue, &[&name])));
+        };
+        *i += 1;
+        return Ok(Some(value.clone()));
+    }
+    Ok(None)
+}
+
+/// Prompts on stderr and reads one trimmed line from stdin.
+pub fn read_line(prompt: &str) -> Result<String, Box<dyn std::error::Error>> {
+    eprint!("{}", prompt);
+    io::stderr().flush()?;
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line)?;
+    Ok(line.trim().to_string())
+}
+
+pub fn print_usage() {
+    println!("Usage:");
+    println!("  <program> [OPTIONS] [API_KEY]   Generate a message for the staged changes");
+    println!("  <program> config show [OPTIONS] Print the effective configuration and its sources");
+    println!("  <program> auth ...              Manage keys stored in the OS keyring");
+    println!("  <program> doctor [OPTIONS]      Check the repository, key, network and clipboard");
+    println!("  <program> history [show|copy N] Browse and re-copy previously generated messages");
+    println!("  <program> mcp [OPTIONS]         Serve generate_commit_message over MCP (stdio)");
+    println!(
+        "  <program> serve [OPTIONS]       Run a daemon answering generate requests on a socket"
+    );
+    println!("  <program> models [OPTIONS]      List the models the key can use and their limits");
+    println!("  <program> lint <FILE|-> [--fix] Check a commit message, e.g. in a commit-msg hook");
+    println!(
+        "  <program> learn                 Learn from edits of generated messages (post-commit)"
+    );
+    println!("  <program> prompt export|import  Edit the guideline sent to the model");
+    println!(
+        "  <program> hook install          Fill in the editor of git commit (prepare-commit-msg)"
+    );
+    println!("  <program> branch-name           Suggest a branch name for the local changes");
+    println!("  <program> next-version          Suggest the next semantic version");
+    println!("  <program> squash [RANGE]        Write one message for a series of commits");
+    println!("  <program> explain [RANGE]       Walk a reviewer through the staged changes");
+    println!("  <program> revert <COMMIT>       Write (and --commit) the message reverting COMMIT");
+    println!("  <program> pr [BASE] [OPTIONS]   Write a pull request title and description");
+    println!(
+        "  <program> batch [PATH...]       Write (and --commit) messages in several repositories"
+    );
+    println!("  <program> self-update [--check] Replace this binary with the latest release");
+    println!(
+        "  <program> completions <SHELL>   Print completions for bash, zsh, fish or powershell"
+    );
+    println!();
+    println!("  Provide Gemini API key via one of (highest precedence first):");
+    println!("    - As first positional argument: <program> <API_KEY>");
+    println!("    - Using --api-key=<KEY>");
+    println!("    - Using -k <KEY>");
+    println!("    - GEMINI_API_KEY or GOOGLE_API_KEY in environment (or in an optional .env file)");
+    println!("    - Repo config: git config gemini.apikey <KEY>");
+    println!("    - User config: git config --global gemini.apikey <KEY>");
+    println!("    - OS keyring: <program> auth set");
+    println!();
+    println!("Options:");
+    println!(
+        "  --profile <NAME>  Use a named profile (env GEMINI_PROFILE, git config gemini.profile)"
+    );
+    println!(
+        "  -C, --repo <PATH> Run as if started in PATH (any directory inside a repository or worktree)"
+    );
+    println!("  --watch           Regenerate whenever the staged changes change");
+    println!("  -n, --candidates <N>");
+    println!("                    Generate N alternative messages (default: 1)");
+    println!("  --type <TYPE>     Use TYPE (feat, fix, ...) instead of the type the model picks");
+    println!("  --scope <SCOPE>   Use SCOPE whatever the model prefers; --no-scope leaves it out");
+    println!("  --instruct <TEXT> Add TEXT to the instructions for this one message, e.g.");
+    println!("                    --instruct \"mention that this is a hotfix for incident 4512\"");
+    println!("  --breaking        Add ! and a BREAKING CHANGE footer describing what breaks");
+    println!("  --subject <TEXT>  Keep TEXT as the header and only write the body and footers");
+    println!("  --commit          Commit with the message, choosing among candidates if several");
+    println!("  --fixup           When the staged changes rework an unpushed commit, write");
+    println!("                    fixup! <subject> (with --commit, git commit --fixup) instead");
+    println!(
+        "  --notes           With --commit, keep the candidates in git notes ({})",
+        commit::NOTES_REF
+    );
+    println!(
+        "  --review          Show the files and size of the request and ask before sending it"
+    );
+    println!("  --interactive-select");
+    println!("                    Pick the files and hunks sent to the model; the staged");
+    println!("                    changes stay as they are");
+    println!("  --no-cache        Regenerate even if the staged changes were seen before");
+    println!("  --images          Send added and changed images so the message can say what they");
+    println!("                    show (git config gemini.images true)");
+    println!("  --minify          Leave out context lines, long runs of added lines and repeated");
+    println!("                    hunks to save tokens (git config gemini.minify true)");
+    println!("  --chunked         Summarize each file separately, then write the message from the");
+    println!(
+        "                    summaries; for very large diffs. Requests run concurrently, up to"
+    );
+    println!(
+        "                    git config gemini.concurrency (env GEMINI_CONCURRENCY, default {})",
+        DEFAULT_CONCURRENCY
+    );
+    println!("  --ci              In GitHub Actions: describe the pull request or pushed commits");
+    println!("                    and report as annotation, job summary and step output");
+    println!("  --rpc             Read one JSON request from stdin and write one JSON response");
+    println!("  --suggest-split   Propose how to split mixed staged changes into separate commits");
+    println!("  --split           Commit the proposed groups one by one, asking before each");
+    println!(
+        "  --timings         Report the time spent in git, prompt building, network and model"
+    );
+    println!("  -o, --output-file <PATH>");
+    println!("                    Write the message to PATH, ending in one newline, for");
+    println!("                    git commit -F PATH; - writes it to stdout. Skips the clipboard");
+    println!("  -q, --quiet       Print only the message; see exit codes below");
+    println!("  -v, -vv           Log progress (-v) and git commands (-vv) to stderr");
+    println!("  --debug[=<FILE>]  Append full requests and responses (API key masked) to FILE,");
+    println!(
+        "                    by default {}",
+        default_debug_file().display()
+    );
+    println!("  --stdin           Read the diff from standard input instead of the index,");
+    println!("                    e.g. git diff --cached | <program> --stdin");
+    println!(
+        "  --model <MODEL>   Gemini model to use (env GEMINI_MODEL, git config gemini.model, default: {})",
+        DEFAULT_MODEL
+    );
+    println!("  --style <STYLE>   conventional (default), plain (subject and body) or kernel");
+    println!("                    (subsystem: summary); env GEMINI_STYLE, git config gemini.style");
+    println!(
+        "  --preset <NAME>   Convention preset: conventional, angular, gitmoji, plain, kernel or"
+    );
+    println!(
+        "                    one defined in {} (env GEMINI_PRESET, git config gemini.preset)",
+        preset::REPO_FILE
+    );
+    println!(
+        "  --provider <NAME> gemini, mock for canned offline messages, or exec:<COMMAND> to pipe"
+    );
+    println!("                    the prompt to a command (env GEMINI_PROVIDER, GCM_MOCK=1,");
+    println!("                    git config gemini.provider)");
+    println!();
+    println!("Exit codes: 0 success, 1 other error, 2 nothing to commit, 3 API error,");
+    println!("            4 configuration error, 5 message failed validation");
+    println!();
+    println!("Profiles keep their own key, provider and model:");
+    println!("  <program> auth login --profile work");
+    println!("  git config --global gemini.work.model gemini-2.5-flash");
+    println!("  git config gemini.profile work   # use it in this repository");
+}
+
+pub struct Flag {
+    pub long: &'static str,
+    pub short: Option<char>,
+    /// Name of the value the flag takes, if any.
+    pub value: Option<&'static str>,
+    pub help: &'static str,
+}
+
+const fn flag(
+    long: &'static str,
+    short: Option<char>,
+    value: Option<&'static str>,
+    help: &'static str,
+) -> Flag {
+    Flag {
+        long,
+        short,
+        value,
+        help,
+    }
+}
+
+/// Every flag `parse_options` understands; shell completions are generated from it.
+pub const FLAGS: &[Flag] = &[
+    flag("help", Some('h'), None, "Print usage"),
+    flag("api-key", Some('k'), Some("KEY"), "Gemini API key"),
+    flag("profile", None, Some("NAME"), "Use a named profile"),
+    flag("model", None, Some("MODEL"), "Gemini model to use"),
+    flag(
+        "style",
+        None,
+        Some("STYLE"),
+        "Message style: conventional, plain or kernel",
+    ),
+    flag(
+        "preset",
+        None,
+        Some("NAME"),
+        "Convention preset: conventional, angular, gitmoji, plain, kernel or a custom one",
+    ),
+    flag(
+        "provider",
+        None,
+        Some("NAME"),
+        "Provider to use (gemini, mock or exec:COMMAND)",
+    ),
+    flag("watch", None, None, "Regenerate on every staging change"),
+    flag(
+        "ci",
+        None,
+        None,
+        "Describe the pushed range or pull request in GitHub Actions",
+    ),
+    flag(
+        "rpc",
+        None,
+        None,
+        "Answer one JSON request from stdin with one JSON line",
+    ),
+    flag("stdin", None, None, "Read the diff from standard input"),
+    flag(
+        "candidates",
+        Some('n'),
+        Some("N"),
+        "Generate N alternative messages",
+    ),
+    flag(
+        "type",
+        None,
+        Some("TYPE"),
+        "Use TYPE as the Conventional Commits type",
+    ),
+    flag(
+        "scope",
+        None,
+        Some("SCOPE"),
+        "Use SCOPE as the Conventional Commits scope",
+    ),
+    flag("no-scope", None, None, "Leave the scope out"),
+    flag(
+        "instruct",
+        None,
+        Some("TEXT"),
+        "Add TEXT to the instructions for this message",
+    ),
+    flag(
+        "breaking",
+        None,
+        None,
+        "Mark the message as a breaking change",
+    ),
+    flag(
+        "subject",
+        None,
+        Some("TEXT"),
+        "Use TEXT as the header and only write the body",
+    ),
+    flag("commit", None, None, "Commit with the generated message"),
+    flag(
+        "fixup",
+        None,
+        None,
+        "Make changes to an unpushed commit a fixup! commit",
+    ),
+    flag("notes", None, None, "Record the candidates in git notes"),
+    flag(
+        "review",
+        None,
+        None,
+        "Confirm what will be sent before calling the model",
+    ),
+    flag(
+        "interactive-select",
+        None,
+        None,
+        "Choose the hunks sent to the model",
+    ),
+    flag(
+        "no-cache",
+        None,
+        None,
+        "Ignore cached messages and regenerate",
+    ),
+    flag(
+        "chunked",
+        None,
+        None,
+        "Summarize file by file, for very large diffs",
+    ),
+    flag(
+        "suggest-split",
+        None,
+        None,
+        "Propose separate commits for mixed changes",
+    ),
+    flag("split", None, None, "Commit the proposed groups one by one"),
+    flag(
+        "output-file",
+        Some('o'),
+        Some("PATH"),
+        "Write the message to PATH (- for stdout)",
+    ),
+    flag("repo", Some('C'), Some("PATH"), "Run as if started in PATH"),
+    flag("quiet", Some('q'), None, "Print only the message"),
+    flag("timings", None, None, "Report the time spent in each phase"),
+    flag("verbose", Some('v'), None, "Log progress to stderr"),
+    flag(
+        "debug",
+        None,
+        None,
+        "Write requests and responses to a log file",
+    ),
+    flag(
+        "tag",
+        None,
+        Some("NAME"),
+        "Create an annotated tag with the notes",
+    ),
+    flag(
+        "write",
+        None,
+        None,
+        "Write CHANGELOG.md or SQUASH_MSG instead of stdout",
+    ),
+    flag("socket", None, Some("PATH"), "Unix socket for serve"),
+    flag("port", None, Some("PORT"), "Localhost port for serve"),
+    flag(
+        "prefix",
+        None,
+        Some("PREFIX"),
+        "Prefix for the branch name, e.g. feat/",
+    ),
+    flag(
+        "check",
+        None,
+        None,
+        "Ask the model about unmarked breaking changes",
+    ),
+    flag(
+        "body-only",
+        None,
+        None,
+        "Print only the pull request description",
+    ),
+    flag(
+        "create",
+        None,
+        None,
+        "Open the pull request, or create the branch",
+    ),
+    flag(
+        "images",
+        None,
+        None,
+        "Send added and changed images to the model",
+    ),
+    flag("minify", None, None, "Shorten the diff to save tokens"),
+    flag(
+        "recurse",
+        None,
+        None,
+        "With batch, include every repository below the paths",
+    ),
+    flag(
+        "update",
+        None,
+        None,
+        "Rewrite the open pull request's description",
+    ),
+    flag(
+        "fix",
+        None,
+        None,
+        "With lint, let the model rewrite a failing message",
+    ),
+];
+
+pub struct Subcommand {
+    pub name: &'static str,
+    pub help: &'static str,
+    pub actions: &'static [&'static str],
+}
+
+const fn subcommand(
+    name: &'static str,
+    help: &'static str,
+    actions: &'static [&'static str],
+) -> Subcommand {
+    Subcommand {
+        name,
+        help,
+        actions,
+    }
+}
+
+pub const SUBCOMMANDS: &[Subcommand] = &[
+    subcommand(
+        "auth",
+        "Manage keys stored in the OS keyring",
+        &["set", "login", "delete", "list"],
+    ),
+    subcommand("config", "Inspect the effective configuration", &["show"]),
+    subcommand("doctor", "Check the setup and report problems", &[]),
+    subcommand(
+        "history",
+        "Browse previously generated messages",
+        &["list", "show", "copy"],
+    ),
+    subcommand("mcp", "Serve generate_commit_message over MCP", &[]),
+    subcommand("models", "List the models the key can use", &[]),
+    subcommand("serve", "Run a daemon answering generate requests", &[]),
+    subcommand(
+        "stash",
+        "Stash the working tree with a generated summary",
+        &[],
+    ),
+    subcommand("release", "Generate release notes for a commit range", &[]),
+    subcommand(
+        "changelog",
+        "Keep a Changelog section for a commit range",
+        &[],
+    ),
+    subcommand(
+        "branch-name",
+        "Suggest a branch name for the local changes",
+        &[],
+    ),
+    subcommand("next-version", "Suggest the next semantic version", &[]),
+    subcommand("squash", "Write one message for a series of commits", &[]),
+    subcommand("pr", "Write a pull request title and description", &[]),
+    subcommand("explain", "Walk a reviewer through the changes", &[]),
+    subcommand("revert", "Write the message of a revert commit", &[]),
+    subcommand("lint", "Check a commit message against the rules", &[]),
+    subcommand("batch", "Generate messages for several repositories", &[]),
+    subcommand("learn", "Learn from the edited message of HEAD", &[]),
+    subcommand(
+        "prompt",
+        "Export or import the guideline",
+        &["export", "import", "reset"],
+    ),
+    subcommand(
+        "hook",
+        "Install a prepare-commit-msg hook",
+        &["install", "uninstall"],
+    ),
+    subcommand("self-update", "Install the latest release", &[]),
+    subcommand(
+        "completions",
+        "Print a shell completion script",
+        &completions::SHELLS,
+    ),
+];
+
+#[derive(Debug, Default)]
+pub struct Options {
+    pub help: bool,
+    pub watch: bool,
+    pub stdin: bool,
+    pub candidates: usize,
+    pub commit: bool,
+    pub fixup: bool,
+    pub notes: bool,
+    pub review: bool,
+    pub interactive_select: bool,
+    pub no_cache: bool,
+    pub chunked: bool,
+    pub suggest_split: bool,
+    pub split: bool,
+    pub repo: Option<PathBuf>,
+    pub overrides: Overrides,
+    pub tag: Option<String>,
+    pub write: bool,
+    pub socket: Option<String>,
+    pub port: Option<String>,
+    pub prefix: Option<String>,
+    pub check: bool,
+    pub body_only: bool,
+    pub create: bool,
+    pub update: bool,
+    pub recurse: bool,
+    pub images: bool,
+    pub minify: bool,
+    pub fix: bool,
+    pub quiet: bool,
+    pub timings: bool,
+    pub verbose: u8,
+    pub debug: Option<PathBuf>,
+    /// `-o`: where to write the message instead of printing it; `-` is stdout.
+    pub output: Option<PathBuf>,
+    /// `--subject`: the header to write a body for.
+    pub subject: Option<String>,
+    /// `--type`: the Conventional Commits type to use.
+    pub kind: Option<String>,
+    /// `--scope`: the scope to use.
+    pub scope: Option<String>,
+    /// `--no-scope`: leave the scope out.
+    pub no_scope: bool,
+    /// `--breaking`: mark the message as a breaking change.
+    pub breaking: bool,
+    /// `--instruct`: extra instructions for this one message.
+    pub instructions: Vec<String>,
+    /// `--ci`: run as a GitHub Actions step.
+    pub ci: bool,
+    /// `--rpc`: answer one JSON request from stdin.
+    pub rpc: bool,
+    pub positional: Vec<String>,
+}
+
+pub fn parse_options(args: &[String]) -> Result<Options, Error> {
+    let mut options = Options::default();
+    let mut i = 0;
+    while i < args.len() {
+        let a = &args[i];
+        if a == "--help" || a == "-h" {
+            options.help = true;
+        } else if a == "--timings" {
+            options.timings = true;
+        } else if a == "-q" || a == "--quiet" {
+            options.quiet = true;
+        } else if a == "-v" || a == "--verbose" {
+            options.verbose += 1;
+        } else if a == "-vv" {
+            options.verbose += 2;
+        } else if a == "--debug" {
+            options.debug = Some(default_debug_file());
+        } else if let Some(path) = a.strip_prefix("--debug=") {
+            options.debug = Some(PathBuf::from(path));
+        } else if a == "--watch" {
+            options.watch = true;
+        } else if a == "--write" {
+            options.write = true;
+        } else if a == "--check" {
+            options.check = true;
+        } else if a == "--body-only" {
+            options.body_only = true;
+        } else if a == "--create" {
+            options.create = true;
+        } else if a == "--update" {
+            options.update = true;
+        } else if a == "--recurse" {
+            options.recurse = true;
+        } else if a == "--images" {
+            options.images = true;
+        } else if a == "--minify" {
+            options.minify = true;
+        } else if a == "--stdin" {
+            options.stdin = true;
+        } else if a == "--commit" {
+            options.commit = true;
+        } else if a == "--interactive-select" {
+            options.interactive_select = true;
+        } else if a == "--fixup" {
+            options.fixup = true;
+        } else if a == "--notes" {
+            options.notes = true;
+        } else if let Some(value) = flag_value(args, &mut i, "--candidates")? {
+            options.candidates = parse_candidates(&value, "--candidates")?;
+        } else if let Some(value) = flag_value(args, &mut i, "-n")? {
+            options.candidates = parse_candidates(&value, "-n")?;
+        } else if a == "--review" {
+            options.review = true;
+        } else if a == "--fix" {
+            options.fix = true;
+        } else if a == "--no-cache" {
+            options.no_cache = true;
+        } else if a == "--chunked" {
+            options.chunked = true;
+        } else if a == "--suggest-split" {
+            options.suggest_split = true;
+        } else if a == "--split" {
+            options.split = true;
+        } else if let Some(value) = flag_value(args, &mut i, "--api-key")? {
+            options.overrides.api_key = Some(value);
+        } else if let Some(value) = flag_value(args, &mut i, "--repo")? {
+            options.repo = Some(PathBuf::from(value));
+        } else if let Some(value) = flag_value(args, &mut i, "--output-file")? {
+            options.output = Some(PathBuf::from(value));
+        } else if let Some(value) = flag_value(args, &mut i, "-o")? {
+            options.output = Some(PathBuf::from(value));
+        } else if let Some(value) = flag_value(args, &mut i, "-C")? {
+            options.repo = Some(PathBuf::from(value));
+        } else if let Some(value) = flag_value(args, &mut i, "-k")? {
+            options.overrides.api_key = Some(value);
+        } else if let Some(value) = flag_value(args, &mut i, "--profile")? {
+            options.overrides.profile = Some(value);
+        } else if let Some(value) = flag_value(args, &mut i, "--model")? {
+            options.overrides.model = Some(value);
+        } else if let Some(value) = flag_value(args, &mut i, "--style")? {
+            options.overrides.style = Some(value);
+        } else if let Some(value) = flag_value(args, &mut i, "--instruct")? {
+            options.instructions.push(value);
+        } else if a == "--ci" {
+            options.ci = true;
+        } else if a == "--rpc" {
+            options.rpc = true;
+        } else if a == "--breaking" {
+            options.breaking = true;
+        } else if a == "--no-scope" {
+            options.no_scope = true;
+        } else if let Some(value) = flag_value(args, &mut i, "--scope")? {
+            options.scope = Some(value);
+        } else if let Some(value) = flag_value(args, &mut i, "--type")? {
+            options.kind = Some(value);
+        } else if let Some(value) = flag_value(args, &mut i, "--subject")? {
+            options.subject = Some(value);
+        } else if let Some(value) = flag_value(args, &mut i, "--preset")? {
+            options.overrides.preset = Some(value);
+        } else if let Some(value) = flag_value(args, &mut i, "--provider")? {
+            options.overrides.provider = Some(value);
+        } else if let Some(value) = flag_value(args, &mut i, "--tag")? {
+            options.tag = Some(value);
+        } else if let Some(value) = flag_value(args, &mut i, "--prefix")? {
+            options.prefix = Some(value);
+        } else if let Some(value) = flag_value(args, &mut i, "--socket")? {
+            options.socket = Some(value);
+        } else if let Some(value) = flag_value(args, &mut i, "--port")? {
+            options.port = Some(value);
+        } else if a == "-" || !a.starts_with('-') {
+            // A lone `-` names standard input.
+            options.positional.push(a.clone());
+        }
+        i += 1;
+    }
+    Ok(options)
+}
+
+fn default_debug_file() -> PathBuf {
+    env::temp_dir().join(format!("{}-debug.log", env!("CARGO_PKG_NAME")))
+}
+
+/// Applies `-v` and `--debug` before anything worth logging happens.
+pub fn init_logging(options: &Options) {
+    log::set_level(options.verbose);
+    if let Some(path) = &options.debug {
+        match log::open_debug_file(path) {
+            Ok(()) if options.quiet => {}
+            Ok(()) => eprintln!("writing debug log to {}", path.display()),
+            Err(e) => eprintln!("cannot open debug log {}: {}", path.display(), e),
+        }
+    }
+}
+
+/// A number of tokens, or `off`/`auto` for `0`/`-1`.
+fn parse_thinking_budget(setting: &Setting) -> Result<i32, Error> {
+    match setting.value.trim().to_lowercase().as_str() {
+        "off" | "none" => Ok(0),
+        "auto" | "dynamic" => Ok(-1),
+        value => value.parse().map_err(|_| {
+            Error::Config(tr(
+                Message::InvalidThinkingBudget,
+                &[&setting.value, &setting.source],
+            ))
+        }),
+    }
+}
+
+/// A positive number of candidates given with `flag`.
+fn parse_candidates(value: &str, flag: &str) -> Result<usize, Error> {
+    match value.trim().parse() {
+        Ok(candidates) if candidates > 0 => Ok(candidates),
+        _ => Err(Error::Config(tr(
+            Message::InvalidCandidates,
+            &[&value, &flag],
+        ))),
+    }
+}
+
+/// A positive number of requests or tokens per minute.
+fn parse_per_minute(setting: &Setting) -> Result<u64, Error> {
+    match setting.value.trim().parse() {
+        Ok(limit) if limit > 0 => Ok(limit),
+        _ => Err(Error::Config(tr(
+            Message::InvalidRateLimit,
+            &[&setting.value, &setting.source],
+        ))),
+    }
+}
+
+/// A positive number of concurrent requests.
+fn parse_concurrency(setting: &Setting) -> Result<usize, Error> {
+    match setting.value.trim().parse() {
+        Ok(concurrency) if concurrency > 0 => Ok(concurrency),
+        _ => Err(Error::Config(tr(
+            Message::InvalidConcurrency,
+            &[&setting.value, &setting.source],
+        ))),
+    }
+}
+
+/// A size in bytes, optionally with a `k` or `M` suffix (`400k`, `1M`).
+fn parse_max_diff_bytes(setting: &Setting) -> Result<usize, Error> {
+    let value = setting.value.trim();
+    let (number, unit) = match value.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+        Some((i, _)) => value.split_at(i),
+        None => (value, ""),
+    };
+    let unit = match unit.trim().to_lowercase().as_str() {
+        "" | "b" => Some(1),
+        "k" | "kb" => Some(1024),
+        "m" | "mb" => Some(1024 * 1024),
+        _ => None,
+    };
+    let bytes = match (number.parse::<usize>(), unit) {
+        (Ok(number), Some(unit)) if number > 0 => number.checked_mul(unit),
+        _ => None,
+    };
+    match bytes {
+        Some(bytes) => Ok(bytes),
+        None => Err(Error::Config(tr(
+            Message::InvalidMaxDiffBytes,
+            &[&setting.value, &setting.source],
+        ))),
+    }
+}
+
+fn parse_bool_setting(setting: &Setting) -> Result<bool, Error> {
+    parse_bool(&setting.value).ok_or_else(|| {
+        Error::Config(tr(
+            Message::InvalidBoolean,
+            &[&setting.value, &setting.source],
+        ))
+    })
+}
+
+/// Whether edits of generated messages are recorded and shown to the model
+/// (`gemini.learn`, on by default).
+pub fn learning(config: &Config) -> Result<bool, Error> {
+    match &config.learn {
+        Some(setting) => parse_bool_setting(setting),
+        None => Ok(true),
+    }
+}
+
+/// How many recent commit subjects to send; 0 sends none.
+fn parse_recent_commits(setting: &Setting) -> Result<usize, Error> {
+    setting.value.trim().parse().map_err(|_| {
+        Error::Config(tr(
+            Message::InvalidRecentCommits,
+            &[&setting.value, &setting.source],
+        ))
+    })
+}
+
+/// The message style selected by `--style` or the configuration.
+pub fn style(config: &Config) -> Result<Style, Error> {
+    Style::parse(&config.style.value).ok_or_else(|| {
+        Error::Config(tr(
+            Message::UnsupportedStyle,
+            &[&config.style.value, &config.style.source],
+        ))
+    })
+}
+
+/// The preset selected by `--preset` or the configuration, looked up in the
+/// presets files of the repository at `repo` first; otherwise the style.
+/// Messages fill in the repository's `commit.template` when one is set.
+pub fn preset(config: &Config, repo: Option<&Path>) -> Result<Preset, Error> {
+    let preset = configured_preset(config, repo)?;
+    Ok(match git::commit_template(repo) {
+        Some(template) => preset.template(&template),
+        None => preset,
+    })
+}
+
+/// The guideline replacing the preset's: the text of `gemini.guidelineFile`,
+/// or else the one saved by `prompt import`.
+pub fn guideline(config: &Config) -> Result<Option<String>, Error> {
+    let text = match &config.guideline_file {
+        Some(setting) => fs::read_to_string(&setting.value).map_err(|e| {
+            Error::Config(tr(
+                Message::UnreadableGuideline,
+                &[&setting.value, &setting.source, &e],
+            ))
+        })?,
+        None => match Preset::imported_guideline_file() {
+            Some(path) => fs::read_to_string(path).unwrap_or_default(),
+            None => String::new(),
+        },
+    };
+    Ok((!text.trim().is_empty()).then_some(text))
+}
+
+/// [`preset`] before the `commit.template` is filled in, as `prompt export`
+/// shows it.
+pub fn configured_preset(config: &Config, repo: Option<&Path>) -> Result<Preset, Error> {
+    let mut preset = match &config.preset {
+        Some(setting) => find_preset(setting, repo)?,
+        None => Preset::from_style(style(config)?),
+    };
+    if let Some(guideline) = guideline(config)? {
+        preset.guideline = guideline;
+    }
+    if let Some(setting) = &config.mood {
+        let mood = Mood::parse(&setting.value).ok_or_else(|| {
+            Error::Config(tr(
+                Message::UnsupportedMood,
+                &[&setting.value, &setting.source, &Mood::NAMES.join(", ")],
+            ))
+        })?;
+        preset.subject.mood = Some(mood);
+    }
+    if let Some(setting) = &config.capitalize {
+        preset.subject.capitalize = Some(parse_bool_setting(setting)?);
+    }
+    if let Some(setting) = &config.period {
+        preset.subject.period = Some(parse_bool_setting(setting)?);
+    }
+    Ok(preset)
+}
+
+fn find_preset(setting: &Setting, repo: Option<&Path>) -> Result<Preset, Error> {
+    let files = preset::files(git::toplevel(repo).ok().as_deref());
+    Preset::find(&setting.value, &files)
+        .map_err(|e| Error::Config(e.to_string()))?
+        .ok_or_else(|| {
+            let paths: Vec<String> = files.iter().map(|f| f.display().to_string()).collect();
+            Error::Config(tr(
+                Message::UnsupportedPreset,
+                &[
+                    &setting.value,
+                    &setting.source,
+                    &preset::BUILTIN.join(", "),
+                    &paths.join(", "),
+                ],
+            ))
+        })
+}
+
+/// Applies what the command line asks of this one message to `preset`.
+pub fn adjust_preset(mut preset: Preset, options: &Options) -> Result<Preset, Error> {
+    let name = preset.name.clone();
+    let is_conventional = preset.style == Style::Conventional;
+    let conventional = |flag: &str| match is_conventional {
+        true => Ok(()),
+        false => Err(Error::Config(tr(
+            Message::NeedsConventional,
+            &[&flag, &name],
+        ))),
+    };
+    if let Some(kind) = &options.kind {
+        conventional("--type")?;
+        preset = preset.force_type(kind);
+    }
+    if options.scope.is_some() && options.no_scope {
+        return Err(Error::Config(tr(Message::ScopeConflict, &[])));
+    }
+    if let Some(scope) = &options.scope {
+        conventional("--scope")?;
+        preset = preset.force_scope(Some(scope));
+    } else if options.no_scope {
+        conventional("--no-scope")?;
+        preset = preset.force_scope(None);
+    }
+    if options.breaking {
+        conventional("--breaking")?;
+        preset = preset.force_breaking();
+    }
+    for instruction in &options.instructions {
+        preset = preset.instruct(instruction);
+    }
+    Ok(preset)
+}
+
+/// Builds the generator described by the effective configuration, writing
+/// messages as `preset` asks.
+pub fn build_generator(
+    config: Config,
+    preset: &Preset,
+) -> Result<Generator, Box<dyn std::error::Error>> {
+    let concurrency = match &config.concurrency {
+        Some(setting) => parse_concurrency(setting)?,
+        None => DEFAULT_CONCURRENCY,
+    };
+    let recent_commits = match &config.recent_commits {
+        Some(setting) => parse_recent_commits(setting)?,
+        None => DEFAULT_RECENT_COMMITS,
+    };
+    let repo_context = match &config.repo_context {
+        Some(setting) => parse_bool_setting(setting)?,
+        None => false,
+    };
+    let images = match &config.images {
+        Some(setting) => parse_bool_setting(setting)?,
+        None => false,
+    };
+    let minify = match &config.minify {
+        Some(setting) => parse_bool_setting(setting)?,
+        None => false,
+    };
+    let corrections = if learning(&config)? {
+        DEFAULT_CORRECTIONS
+    } else {
+        0
+    };
+    let mut prompt_builder = preset.prompt_builder();
+    if let Some(setting) = &config.max_diff_bytes {
+        prompt_builder = prompt_builder.max_diff_bytes(parse_max_diff_bytes(setting)?);
+    }
+    let trailers = config
+        .trailers
+        .iter()
+        .map(|setting| {
+            Footer::parse(&setting.value).ok_or_else(|| {
+                Error::Config(tr(
+                    Message::InvalidTrailer,
+                    &[&setting.value, &setting.source],
+                ))
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let generator = if config.provider.value == "mock" {
+        Generator::new(Mock)
+    } else if let Some(command) = config.provider.value.strip_prefix("exec:") {
+        if command.trim().is_empty() {
+            return Err(Error::Config(tr(
+                Message::UnsupportedProvider,
+                &[&config.provider.value, &config.provider.source],
+            ))
+            .into());
+        }
+        Generator::new(Exec::new(command.trim()))
+    } else {
+        Generator::new(gemini(config)?)
+    };
+    Ok(generator
+        .concurrency(concurrency)
+        .prompt_builder(prompt_builder)
+        .recent_commits(recent_commits)
+        .repository_context(repo_context)
+        .corrections(corrections)
+        .images(images)
+        .minify(minify)
+        .trailers(trailers))
+}
+
+/// The Gemini client for `config`, replaying or recording responses as
+/// `GCM_REPLAY` and `GCM_RECORD` ask.
+fn gemini(config: Config) -> Result<Gemini, Box<dyn std::error::Error>> {
+    if config.provider.value != DEFAULT_PROVIDER {
+        return Err(Error::Config(tr(
+            Message::UnsupportedProvider,
+            &[&config.provider.value, &config.provider.source],
+        ))
+        .into());
+    }
+    let var = |name| env::var_os(name).filter(|v| !v.is_empty());
+    let replay = var("GCM_REPLAY");
+    let api_key = match config.api_key {
+        Some(setting) => setting.value,
+        // Replayed responses were recorded already, no key is sent; a proxy
+        // may hold the key itself.
+        None if replay.is_some() || config.api_base.is_some() => String::new(),
+        None => return Err(Error::Config(tr(Message::NoApiKey, &[])).into()),
+    };
+    let mut gemini = Gemini::new(api_key).model(config.model.value);
+    if let Some(setting) = config.api_base {
+        gemini = gemini.base_url(setting.value);
+    }
+    if let Some(setting) = config.thinking_budget {
+        gemini = gemini.thinking_budget(parse_thinking_budget(&setting)?);
+    }
+    let rate_limit = RateLimit {
+        requests_per_minute: config
+            .requests_per_minute
+            .as_ref()
+            .map(parse_per_minute)
+            .transpose()?,
+        tokens_per_minute: config
+            .tokens_per_minute
+            .as_ref()
+            .map(parse_per_minute)
+            .transpose()?,
+    };
+    gemini = gemini.rate_limit(rate_limit);
+    if let Some(dir) = replay {
+        gemini = gemini.replay(dir);
+    } else if let Some(dir) = var("GCM_RECORD") {
+        gemini = gemini.record(dir);
+    }
+    Ok(gemini)
+}
+
+/// A Gemini client for the configured key and base URL; `None` without a
+/// key, unless a base URL points at a proxy that may add it.
+pub fn gemini_client(config: &Config) -> Option<Gemini> {
+    let key = match (&config.api_key, &config.api_base) {
+        (Some(key), _) => key.value.clone(),
+        (None, Some(_)) => String::new(),
+        (None, None) => return None,
+    };
+    let gemini = Gemini::new(key).model(config.model.value.clone());
+    Some(match &config.api_base {
+        Some(base) => gemini.base_url(base.value.clone()),
+        None => gemini,
+    })
+}
+
+/// Resolves the configuration of the repository selected by `-C` (or the
+/// current one) and returns a generator running git there.
+pub fn setup(options: &Options) -> Result<Generator, Box<dyn std::error::Error>> {
+    let repo = options.repo.as_deref();
+    git::git_dir(repo)?;
+    let config = Config::resolve(options.overrides.clone(), &GitConfig::load_in(repo));
+    let preset = preset(&config, repo)?;
+    let generator = build_generator(config, &preset)?;
+    Ok(match repo {
+        Some(repo) => generator.repo(repo),
+        None => generator,
+    })
+}
+
+/// Fails with [`Error::Validation`] unless `message` follows `preset`.
+pub fn validate(message: &CommitMessage, preset: &Preset) -> Result<(), Error> {
+    preset.check(message).map_err(Error::Validation)
+}
+
+/// Generates `count` candidates for `diff`, reusing the messages cached for
+/// the same diff, prompt and model unless `use_cache` is false.
+pub fn cached_candidates(
+    generator: &Generator,
+    model: &str,
+    diff: &str,
+    count: usize,
+    use_cache: bool,
+) -> Result<Vec<CommitMessage>, Box<dyn std::error::Error>> {
+    let count = count.max(1);
+    let key = cache::key(&[
+        model,
+        if generator.is_chunked() {
+            "chunked"
+        } else {
+            ""
+        },
+        &generator.prompt(&cache::normalize_diff(diff)).to_string(),
+        &count.to_string(),
+        &generator
+            .configured_trailers()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n"),
+    ]);
+    let cache = if use_cache { Cache::open() } else { None };
+    if let Some(messages) = cache.as_ref().and_then(|cache| cache.get(&key)) {
+        log::info(&format!("using cached messages {}", key));
+        return Ok(messages.iter().map(|m| CommitMessage::parse(m)).collect());
+    }
+    let candidates = generator.generate_candidates(diff, count)?;
+    if let Some(cache) = cache {
+        let messages: Vec<String> = candidates.iter().map(ToString::to_string).collect();
+        if let Err(e) = cache.put(&key, &messages) {
+            eprintln!("could not write the cache: {}", e);
+        }
+    }
+    Ok(candidates)
+}
+
+/// Answers a `{"repo_path": "...", "staged": true}` request, as sent by the
+/// MCP tool and the `serve` daemon. Settings are resolved from the target
+/// repository, so per-repo keys and profiles apply.
+pub fn generate_for_request(overrides: &Overrides, request: &Value) -> Result<String, String> {
+    let repo_path = request
+        .get("repo_path")
+        .or_else(|| request.get("repo"))
+        .and_then(Value::as_str)
+        .ok_or("repo_path is required")?;
+    let staged = request
+        .get("staged")
+        .and_then(Value::as_bool)
+        .unwrap_or(true);
+    let repo = Path::new(repo_path);
+
+    let config = Config::resolve(overrides.clone(), &GitConfig::load_in(Some(repo)));
+    let preset = preset(&config, Some(repo)).map_err(|e| e.to_string())?;
+    let generator = build_generator(config, &preset)
+        .map_err(|e| e.to_string())?
+        .repo(repo)
+        .diff_source(if staged {
+            DiffSource::Staged
+        } else {
+            DiffSource::Unstaged
+        });
+    match generator.generate().map_err(|e| e.to_string())? {
+        Some(message) => Ok(message.to_string()),
+        None => Err(Error::NothingToCommit.to_string()),
+    }
+}
@@ -0,0 +1,106 @@
+use crate::cli::{flag_value, read_line};
+use gemini_commit_message::auth::{delete_api_key, save_api_key};
+use gemini_commit_message::config::GitConfig;
+use std::process::Command;
+
+const DEFAULT_PROFILE: &str = "default";
+
+fn set_global_git_config(key: &str, value: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let status = Command::new("git")
+        .args(["config", "--global", key, value])
+        .status()?;
+    if !status.success() {
+        return Err(format!("git config --global {} failed: {}", key, status).into());
+    }
+    Ok(())
+}
+
+fn print_auth_usage() {
+    println!("Usage:");
+    println!(
+        "  <program> auth set [--profile NAME] [API_KEY]   Save the key in the OS keyring (reads stdin if omitted)"
+    );
+    println!(
+        "  <program> auth login [--profile NAME]           Interactively set up the key and model of a profile"
+    );
+    println!(
+        "  <program> auth delete [--profile NAME]          Remove the key from the OS keyring"
+    );
+    println!(
+        "  <program> auth list                             List profiles defined in git config"
+    );
+}
+
+pub fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut profile: Option<String> = None;
+    let mut positional: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < args.len() {
+        if let Some(value) = flag_value(args, &mut i, "--profile")? {
+            profile = Some(value);
+        } else {
+            positional.push(args[i].clone());
+        }
+        i += 1;
+    }
+    let profile = profile.as_deref();
+    let profile_label = profile.unwrap_or(DEFAULT_PROFILE);
+
+    match positional.first().map(String::as_str) {
+        Some("set") => {
+            let api_key = match positional.get(1) {
+                Some(key) => key.clone(),
+                None => read_line("Gemini API key: ")?,
+            };
+            if api_key.is_empty() {
+                println!("No API key given, keyring left unchanged.");
+                return Ok(());
+            }
+            save_api_key(profile, &api_key)?;
+            println!(
+                "API key for profile '{}' saved to the OS keyring.",
+                profile_label
+            );
+        }
+        Some("login") => {
+            let api_key = read_line(&format!("Gemini API key for profile '{}': ", profile_label))?;
+            if api_key.is_empty() {
+                println!("No API key given, nothing changed.");
+                return Ok(());
+            }
+            save_api_key(profile, &api_key)?;
+            if let Some(name) = profile {
+                set_global_git_config(&format!("gemini.{}.provider", name), "gemini")?;
+                let model = read_line("Model (leave empty for the default): ")?;
+                if !model.is_empty() {
+                    set_global_git_config(&format!("gemini.{}.model", name), &model)?;
+                }
+            }
+            println!("Logged in as profile '{}'.", profile_label);
+        }
+        Some("delete") => {
+            delete_api_key(profile)?;
+            println!(
+                "API key for profile '{}' removed from the OS keyring.",
+                profile_label
+            );
+        }
+        Some("list") => {
+            let git_config = GitConfig::load();
+            let active = git_config.get("gemini.profile").map(|e| e.value.clone());
+            for name in git_config.profiles() {
+                let marker = if active.as_deref() == Some(name.as_str()) {
+                    "*"
+                } else {
+                    " "
+                };
+                let model = git_config
+                    .get_gemini(Some(&name), "model")
+                    .map_or("-", |e| e.value.as_str());
+                println!("{} {} (model: {})", marker, name, model);
+            }
+        }
+        _ => print_auth_usage(),
+    }
+    Ok(())
+}
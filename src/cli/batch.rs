@@ -0,0 +1,116 @@
+//! `batch [PATH...]`: a message for the staged changes of several
+//! repositories, and with `--commit` a commit in each of them.
+
+use crate::cli::{self, Options};
+use gemini_commit_message::config::{Config, GitConfig};
+use gemini_commit_message::{DiffSource, git};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directories that hold dependencies or build output rather than work.
+const SKIPPED: [&str; 3] = ["node_modules", "target", "vendor"];
+
+/// The repositories at and below `dir`, submodules included.
+fn find_repositories(dir: &Path, found: &mut Vec<PathBuf>) {
+    // A submodule or linked worktree has a `.git` file instead of a directory.
+    if dir.join(".git").exists() {
+        found.push(dir.to_path_buf());
+    }
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut children: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            !name.starts_with('.') && !SKIPPED.contains(&name.as_ref())
+        })
+        .map(|entry| entry.path())
+        .collect();
+    children.sort();
+    for child in children {
+        find_repositories(&child, found);
+    }
+}
+
+/// Generates (and commits) the message for one repository; `false` when
+/// nothing is staged there.
+fn process(options: &Options, repo: &Path) -> Result<bool, Box<dyn std::error::Error>> {
+    let diff = DiffSource::Staged.read(Some(repo))?;
+    if diff.trim().is_empty() {
+        return Ok(false);
+    }
+    let config = Config::resolve(options.overrides.clone(), &GitConfig::load_in(Some(repo)));
+    let learn = cli::learning(&config)?;
+    let preset = cli::adjust_preset(cli::preset(&config, Some(repo))?, options)?;
+    let generator = cli::build_generator(config, &preset)?
+        .chunked(options.chunked)
+        .repo(repo);
+    let mut message = generator.generate_for_diff(&diff)?;
+    preset.apply(&mut message);
+    println!("{}\n", message);
+    cli::history::record(Some(repo), &diff, std::slice::from_ref(&message), None);
+    cli::validate(&message, &preset)?;
+    if options.commit {
+        cli::commit::commit(Some(repo), &message.to_string())?;
+        if learn {
+            cli::learn::after_commit(Some(repo));
+        }
+    }
+    Ok(true)
+}
+
+pub fn run(options: Options) -> Result<(), Box<dyn std::error::Error>> {
+    if options.help {
+        println!("Usage: <program> batch [PATH...] [--recurse] [--commit] [OPTIONS]");
+        println!("  Writes a message for the staged changes of each repository PATH");
+        println!("  (default: the current directory) and commits it with --commit.");
+        println!("  With --recurse, also every repository and submodule below each PATH.");
+        println!("  Repositories with nothing staged are skipped.");
+        return Ok(());
+    }
+    let roots: Vec<PathBuf> = if options.positional.is_empty() {
+        vec![options.repo.clone().unwrap_or_else(|| PathBuf::from("."))]
+    } else {
+        options.positional.iter().map(PathBuf::from).collect()
+    };
+    let mut repositories = Vec::new();
+    for root in &roots {
+        if options.recurse {
+            find_repositories(root, &mut repositories);
+        } else {
+            repositories.push(root.clone());
+        }
+    }
+
+    let (mut done, mut failed) = (0, 0);
+    for repo in &repositories {
+        let name = repo.display();
+        if let Err(e) = git::git_dir(Some(repo)) {
+            eprintln!("{}: {}", name, e);
+            failed += 1;
+            continue;
+        }
+        eprintln!("== {}", name);
+        match process(&options, repo) {
+            Ok(true) => done += 1,
+            Ok(false) => eprintln!("nothing staged\n"),
+            Err(e) => {
+                eprintln!("{}: {}\n", name, e);
+                failed += 1;
+            }
+        }
+    }
+    eprintln!(
+        "{} repositories: {} with a message, {} failed",
+        repositories.len(),
+        done,
+        failed
+    );
+    if failed > 0 {
+        return Err(format!("{} of {} repositories failed", failed, repositories.len()).into());
+    }
+    Ok(())
+}
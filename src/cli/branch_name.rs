@@ -0,0 +1,66 @@
+//! `branch-name`: a kebab-case branch name for the uncommitted changes.
+
+use crate::cli::{self, Options};
+use gemini_commit_message::config::{Config, GitConfig};
+use gemini_commit_message::prompt::{BRANCH_NAME_GUIDELINE, PromptBuilder};
+use gemini_commit_message::{DiffSource, git};
+
+const MAX_LEN: usize = 50;
+
+/// Lowercases `text` and joins its words with `-`, dropping anything git
+/// would reject or quote.
+fn kebab_case(text: &str) -> String {
+    let mut name = String::new();
+    for c in text.trim().chars() {
+        if c.is_ascii_alphanumeric() {
+            name.push(c.to_ascii_lowercase());
+        } else if !name.is_empty() && !name.ends_with('-') {
+            name.push('-');
+        }
+        if name.len() >= MAX_LEN {
+            break;
+        }
+    }
+    name.trim_end_matches('-').to_string()
+}
+
+pub fn run(options: Options) -> Result<(), Box<dyn std::error::Error>> {
+    if options.help {
+        println!("Usage: <program> branch-name [--prefix PREFIX] [--create] [-C PATH] [OPTIONS]");
+        println!("  Suggests a kebab-case branch name for the staged and unstaged changes.");
+        println!("  PREFIX (e.g. feat/) defaults to git config gemini.branchPrefix.");
+        println!("  With --create, also creates the branch and switches to it.");
+        return Ok(());
+    }
+    let repo = options.repo.as_deref();
+    let generator = cli::setup(&options)?
+        .diff_source(DiffSource::WorkingTree)
+        .prompt_builder(PromptBuilder::new().guideline(BRANCH_NAME_GUIDELINE));
+    let Some(message) = generator.generate()? else {
+        println!("No local changes to name a branch after");
+        return Ok(());
+    };
+
+    let prefix = match &options.prefix {
+        Some(prefix) => prefix.clone(),
+        None => {
+            let git_config = GitConfig::load_in(repo);
+            let profile = Config::resolve(options.overrides.clone(), &git_config)
+                .profile
+                .map(|setting| setting.value);
+            git_config
+                .get_gemini(profile.as_deref(), "branchPrefix")
+                .map_or(String::new(), |entry| entry.value.clone())
+        }
+    };
+    let name = format!("{}{}", prefix, kebab_case(&message.header));
+    git::output(repo, &["check-ref-format", "--branch", &name])
+        .map_err(|_| format!("'{}' is not a valid branch name", name))?;
+
+    if options.create {
+        git::output(repo, &["switch", "-c", &name])?;
+        eprintln!("switched to a new branch '{}'", name);
+    }
+    println!("{}", name);
+    Ok(())
+}
@@ -0,0 +1,42 @@
+//! `changelog <from>..<to>`: a Keep a Changelog section grouped by commit type.
+
+use crate::cli::Options;
+use gemini_commit_message::{changelog, commits, git};
+use std::fs;
+
+pub fn run(options: Options) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(range) = options.positional.first().filter(|_| !options.help) else {
+        println!("Usage: <program> changelog <FROM>..<TO> [--write] [-C PATH]");
+        println!("  Groups the Conventional Commits of the range into a Keep a Changelog section.");
+        println!(
+            "  TO defaults to HEAD; a tag as TO names the release, otherwise it is [Unreleased]."
+        );
+        println!(
+            "  With --write the section is inserted into CHANGELOG.md at the repository root."
+        );
+        return Ok(());
+    };
+    let repo = options.repo.as_deref();
+    git::git_dir(repo)?;
+    let (from, to) = commits::split_range(range);
+    let commits = commits::log(repo, &format!("{}..{}", from, to))?;
+
+    let tag_ref = format!("refs/tags/{}", to);
+    let is_tag = git::output(repo, &["rev-parse", "--verify", "--quiet", &tag_ref]).is_ok();
+    let section = if is_tag {
+        let date = git::output(repo, &["log", "-1", "--format=%cs", to])?;
+        changelog::section(to.trim_start_matches('v'), Some(date.trim()), &commits)
+    } else {
+        changelog::section("Unreleased", None, &commits)
+    };
+
+    if options.write {
+        let path = git::toplevel(repo)?.join("CHANGELOG.md");
+        let existing = fs::read_to_string(&path).ok();
+        fs::write(&path, changelog::insert(existing.as_deref(), &section))?;
+        eprintln!("updated {}", path.display());
+    } else {
+        print!("{}", section);
+    }
+    Ok(())
+}
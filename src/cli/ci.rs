@@ -0,0 +1,101 @@
+//! `--ci`: describe the pushed range or pull request inside GitHub Actions.
+//!
+//! The message is printed as a notice annotation, added to the job summary
+//! and set as the `message` step output. Errors become `::error` lines, no
+//! `.env` file is read and nothing is copied to a clipboard.
+
+use crate::cli::{self, Options};
+use gemini_commit_message::config::{Config, GitConfig};
+use gemini_commit_message::{Error, git};
+use serde_json::Value;
+use std::env;
+use std::fs::OpenOptions;
+use std::io::Write;
+
+const TITLE: &str = "Suggested commit message";
+
+/// Escapes `text` for a workflow command, which ends at the first newline.
+fn escape(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// A `::level title=...::message` workflow command.
+pub fn annotation(level: &str, title: &str, message: &str) -> String {
+    format!(
+        "::{} title={}::{}",
+        level,
+        escape(title).replace(':', "%3A").replace(',', "%2C"),
+        escape(message)
+    )
+}
+
+/// The range to describe: the pull request against its base branch, the
+/// commits of a push, or the last commit outside of either.
+fn range() -> String {
+    let var = |name| env::var(name).ok().filter(|v| !v.is_empty());
+    if let Some(base) = var("GITHUB_BASE_REF") {
+        return format!("origin/{}...HEAD", base);
+    }
+    let event: Option<Value> = var("GITHUB_EVENT_PATH")
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|text| serde_json::from_str(&text).ok());
+    if let Some(event) = event
+        && let (Some(before), Some(after)) = (event["before"].as_str(), event["after"].as_str())
+        // A new branch is pushed with an all-zero `before`.
+        && !before.chars().all(|c| c == '0')
+    {
+        return format!("{}..{}", before, after);
+    }
+    "HEAD~1..HEAD".to_string()
+}
+
+/// Appends `text` to the file named by the environment variable `name`.
+fn append(name: &str, text: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(path) = env::var_os(name).filter(|v| !v.is_empty()) else {
+        return Ok(());
+    };
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    file.write_all(text.as_bytes())?;
+    Ok(())
+}
+
+fn publish(message: &str, range: &str) -> Result<(), Box<dyn std::error::Error>> {
+    println!("{}", annotation("notice", TITLE, message));
+    append(
+        "GITHUB_STEP_SUMMARY",
+        &format!(
+            "### {}\n\nFor `{}`:\n\n```\n{}\n```\n",
+            TITLE, range, message
+        ),
+    )?;
+    // A delimiter that cannot occur in the message.
+    let delimiter = format!("gemini-commit-message-{}", std::process::id());
+    append(
+        "GITHUB_OUTPUT",
+        &format!("message<<{0}\n{1}\n{0}\n", delimiter, message),
+    )
+}
+
+pub fn run(options: Options) -> Result<(), Box<dyn std::error::Error>> {
+    let repo = options.repo.as_deref();
+    git::git_dir(repo)?;
+    let config = Config::resolve(options.overrides.clone(), &GitConfig::load_in(repo));
+    let preset = cli::adjust_preset(cli::preset(&config, repo)?, &options)?;
+    let mut generator = cli::build_generator(config, &preset)?.chunked(options.chunked);
+    if let Some(repo) = repo {
+        generator = generator.repo(repo);
+    }
+
+    let range = range();
+    let diff = git::diff_range(repo, &range)?;
+    if diff.trim().is_empty() {
+        return Err(Error::NothingToCommit.into());
+    }
+    let mut message = generator.generate_for_diff(&diff)?;
+    preset.apply(&mut message);
+    publish(&message.to_string(), &range)?;
+    cli::validate(&message, &preset)?;
+    Ok(())
+}
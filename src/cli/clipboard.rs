@@ -0,0 +1,147 @@
+//! Copying the message, with a `clip.exe` fallback for Windows consoles and
+//! WSL, where arboard may not reach a clipboard.
+//!
+//! On X11 and Wayland the copied text is only there for as long as the
+//! process that set it answers requests for it, so a copy of this program
+//! started with [`SERVE_ARG`] keeps serving it in the background until
+//! something else is copied.
+
+use arboard::Clipboard;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+/// The hidden first argument that runs [`serve`] instead of the program.
+pub const SERVE_ARG: &str = "__serve-clipboard";
+
+/// Whether the clipboard is lost when the process that set it exits.
+const OWNED_BY_PROCESS: bool = cfg!(all(
+    unix,
+    not(any(
+        target_os = "macos",
+        target_os = "android",
+        target_os = "emscripten"
+    ))
+));
+
+pub fn copy(text: &str) -> Result<(), Box<dyn std::error::Error>> {
+    // Windows editors expect CRLF; elsewhere the message is pasted as is.
+    let native = if cfg!(windows) {
+        text.replace('\n', "\r\n")
+    } else {
+        text.to_string()
+    };
+    let copied: Result<(), Box<dyn std::error::Error>> = if OWNED_BY_PROCESS {
+        spawn_server(&native)
+    } else {
+        Clipboard::new()
+            .and_then(|mut clipboard| clipboard.set_text(native))
+            .map_err(Into::into)
+    };
+    match copied {
+        Ok(()) => Ok(()),
+        Err(e) => clip_exe(text).map_err(|_| e),
+    }
+}
+
+/// Starts [`serve`] with `text` and waits until it owns the clipboard. It
+/// runs in its own process group, so Ctrl-C in the terminal does not end it.
+fn spawn_server(text: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut command = Command::new(std::env::current_exe()?);
+    command
+        .arg(SERVE_ARG)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .current_dir("/");
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    let mut child = command.spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    let mut answer = String::new();
+    if let Some(stdout) = child.stdout.take() {
+        io::BufRead::read_line(&mut io::BufReader::new(stdout), &mut answer)?;
+    }
+    // Reaps the server should it finish while this process still runs,
+    // e.g. in --watch when the next message replaces this one.
+    std::thread::spawn(move || child.wait());
+    match answer.trim_end() {
+        "ok" => Ok(()),
+        "" => Err("the clipboard server exited".into()),
+        error => Err(error.to_string().into()),
+    }
+}
+
+/// Copies stdin to the clipboard and serves it until something else is
+/// copied; reports `ok` or the error on stdout first.
+pub fn serve() -> Result<(), Box<dyn std::error::Error>> {
+    let mut text = String::new();
+    io::Read::read_to_string(&mut io::stdin(), &mut text)?;
+    let mut clipboard = match Clipboard::new() {
+        Ok(clipboard) => clipboard,
+        Err(e) => {
+            println!("{}", e);
+            return Err(e.into());
+        }
+    };
+    println!("ok");
+    io::stdout().flush()?;
+    hold(&mut clipboard, text)?;
+    Ok(())
+}
+
+/// Sets `text` and returns once something else owns the clipboard.
+#[cfg(all(
+    unix,
+    not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+))]
+fn hold(clipboard: &mut Clipboard, text: String) -> Result<(), arboard::Error> {
+    use arboard::SetExtLinux;
+    clipboard.set().wait().text(text)
+}
+
+#[cfg(not(all(
+    unix,
+    not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+)))]
+fn hold(clipboard: &mut Clipboard, text: String) -> Result<(), arboard::Error> {
+    clipboard.set_text(text)
+}
+
+/// Whether a clipboard can be reached, without changing its contents.
+pub fn available() -> Result<(), Box<dyn std::error::Error>> {
+    match Clipboard::new() {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            let on_path = std::env::var_os("PATH").is_some_and(|path| {
+                std::env::split_paths(&path).any(|dir| dir.join("clip.exe").is_file())
+            });
+            if on_path { Ok(()) } else { Err(e.into()) }
+        }
+    }
+}
+
+/// `clip.exe` decodes its input with the console code page unless it is
+/// UTF-16LE with a byte order mark, which keeps non-ASCII text intact.
+fn clip_exe(text: &str) -> io::Result<()> {
+    let mut child = Command::new("clip.exe")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    let mut bytes = vec![0xFF, 0xFE];
+    bytes.extend(
+        text.replace('\n', "\r\n")
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes),
+    );
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(&bytes)?;
+    }
+    if child.wait()?.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other("clip.exe failed"))
+    }
+}
@@ -0,0 +1,87 @@
+//! `--commit`: pick one of the candidates, commit it and optionally record
+//! the candidates in git notes.
+
+use crate::cli::read_line;
+use gemini_commit_message::i18n::{Message, tr};
+use gemini_commit_message::{CommitMessage, git};
+use std::io::{self, IsTerminal};
+use std::path::Path;
+
+/// Notes ref holding what the model suggested for each commit.
+pub const NOTES_REF: &str = "refs/notes/gemini-commit-message";
+
+pub fn print_candidates(candidates: &[CommitMessage]) {
+    for (i, candidate) in candidates.iter().enumerate() {
+        println!("=== Candidate {} ===", i + 1);
+        println!("{}", candidate);
+        println!();
+    }
+}
+
+/// Asks which candidate to use; a single candidate needs no question, and
+/// without a terminal to ask on the first, best ranked one is taken.
+pub fn choose(candidates: &[CommitMessage]) -> Result<&CommitMessage, Box<dyn std::error::Error>> {
+    if candidates.len() == 1 || !io::stdin().is_terminal() {
+        return Ok(&candidates[0]);
+    }
+    print_candidates(candidates);
+    loop {
+        let answer = read_line(&tr(Message::ChooseCandidate, &[&candidates.len()]))?;
+        match answer.parse::<usize>() {
+            Ok(n) if (1..=candidates.len()).contains(&n) => return Ok(&candidates[n - 1]),
+            _ => eprintln!("{}", tr(Message::EnterNumber, &[&candidates.len()])),
+        }
+    }
+}
+
+/// Runs `git commit <args>` on the terminal, showing git's own output. Being
+/// git itself, it signs as `commit.gpgSign`, `user.signingKey` and
+/// `gpg.format` say and can ask for a passphrase; a commit that should have
+/// been signed but was not is reported.
+fn git_commit(repo: Option<&Path>, args: &[&str]) -> Result<(), Box<dyn std::error::Error>> {
+    let signing = git::commit_signing(repo);
+    let status = git::command(repo).arg("commit").args(args).status()?;
+    if !status.success() {
+        return Err(match signing {
+            Some(signing) => tr(Message::SignedCommitFailed, &[&status, &signing]),
+            None => format!("git commit failed ({})", status),
+        }
+        .into());
+    }
+    if let Some(signing) = signing
+        && !git::is_signed(repo, "HEAD")
+    {
+        eprintln!("{}", tr(Message::CommitNotSigned, &[&signing]));
+    }
+    Ok(())
+}
+
+/// Runs `git commit` with `message`, showing git's own output.
+pub fn commit(repo: Option<&Path>, message: &str) -> Result<(), Box<dyn std::error::Error>> {
+    git_commit(repo, &["-m", message])
+}
+
+/// Runs `git commit --fixup` for `commit`, showing git's own output.
+pub fn fixup(repo: Option<&Path>, commit: &str) -> Result<(), Box<dyn std::error::Error>> {
+    git_commit(repo, &["--fixup", commit])
+}
+
+/// Attaches every candidate and the message that was actually committed
+/// (after hooks) to `HEAD` under [`NOTES_REF`].
+pub fn add_note(
+    repo: Option<&Path>,
+    candidates: &[CommitMessage],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let committed = git::output(repo, &["log", "-1", "--format=%B", "HEAD"])?;
+    let mut note = String::new();
+    for (i, candidate) in candidates.iter().enumerate() {
+        note.push_str(&format!("Candidate {}:\n\n{}\n\n", i + 1, candidate));
+    }
+    note.push_str(&format!("Committed:\n\n{}\n", committed.trim_end()));
+    let notes_ref = format!("--ref={}", NOTES_REF);
+    git::output(
+        repo,
+        &["notes", &notes_ref, "add", "-f", "-m", &note, "HEAD"],
+    )?;
+    Ok(())
+}
@@ -0,0 +1,215 @@
+//! `completions <shell>`: completion scripts generated from [`FLAGS`] and [`SUBCOMMANDS`].
+
+use crate::cli::{FLAGS, SUBCOMMANDS};
+use gemini_commit_message::Error;
+
+pub const SHELLS: [&str; 4] = ["bash", "zsh", "fish", "powershell"];
+
+const BIN: &str = env!("CARGO_PKG_NAME");
+
+fn flag_words() -> Vec<String> {
+    let mut words = Vec::new();
+    for flag in FLAGS {
+        words.push(format!("--{}", flag.long));
+        if let Some(short) = flag.short {
+            words.push(format!("-{}", short));
+        }
+    }
+    words
+}
+
+/// Single-quoted for shells where `''` is not an escape.
+fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
+
+fn bash() -> String {
+    let mut script = String::new();
+    let function = format!("_{}", BIN.replace('-', "_"));
+    let commands: Vec<&str> = SUBCOMMANDS.iter().map(|s| s.name).collect();
+    let flags = flag_words().join(" ");
+    script.push_str(&format!("{}() {{\n", function));
+    script.push_str("    local cur=\"${COMP_WORDS[COMP_CWORD]}\"\n");
+    script.push_str("    local words=\"");
+    script.push_str(&flags);
+    script.push_str("\"\n");
+    script.push_str("    if [ \"$COMP_CWORD\" -eq 1 ]; then\n");
+    script.push_str(&format!(
+        "        words=\"{} $words\"\n",
+        commands.join(" ")
+    ));
+    script.push_str("    elif [ \"$COMP_CWORD\" -eq 2 ]; then\n");
+    script.push_str("        case \"${COMP_WORDS[1]}\" in\n");
+    for subcommand in SUBCOMMANDS.iter().filter(|s| !s.actions.is_empty()) {
+        script.push_str(&format!(
+            "            {}) words=\"{} $words\" ;;\n",
+            subcommand.name,
+            subcommand.actions.join(" ")
+        ));
+    }
+    script.push_str("        esac\n");
+    script.push_str("    fi\n");
+    script.push_str("    COMPREPLY=($(compgen -W \"$words\" -- \"$cur\"))\n");
+    script.push_str("}\n");
+    script.push_str(&format!("complete -o default -F {} {}\n", function, BIN));
+    script
+}
+
+fn zsh() -> String {
+    let mut script = String::new();
+    let function = format!("_{}", BIN.replace('-', "_"));
+    script.push_str(&format!("#compdef {}\n\n", BIN));
+    script.push_str(&format!("{}() {{\n", function));
+    script.push_str("    local -a commands flags\n");
+    script.push_str("    commands=(\n");
+    for subcommand in SUBCOMMANDS {
+        script.push_str(&format!(
+            "        {}\n",
+            quote(&format!("{}:{}", subcommand.name, subcommand.help))
+        ));
+    }
+    script.push_str("    )\n");
+    script.push_str("    flags=(\n");
+    for flag in FLAGS {
+        let value = flag.value.map_or(String::new(), |v| format!(":{}:", v));
+        script.push_str(&format!(
+            "        {}\n",
+            quote(&format!("--{}[{}]{}", flag.long, flag.help, value))
+        ));
+        if let Some(short) = flag.short {
+            script.push_str(&format!(
+                "        {}\n",
+                quote(&format!("-{}[{}]{}", short, flag.help, value))
+            ));
+        }
+    }
+    script.push_str("    )\n");
+    script.push_str("    if (( CURRENT == 2 )); then\n");
+    script.push_str("        _describe -t commands 'command' commands\n");
+    script.push_str("    elif (( CURRENT == 3 )); then\n");
+    script.push_str("        case ${words[2]} in\n");
+    for subcommand in SUBCOMMANDS.iter().filter(|s| !s.actions.is_empty()) {
+        script.push_str(&format!(
+            "            {}) compadd {} ;;\n",
+            subcommand.name,
+            subcommand.actions.join(" ")
+        ));
+    }
+    script.push_str("        esac\n");
+    script.push_str("    fi\n");
+    script.push_str("    _arguments -s $flags\n");
+    script.push_str("}\n\n");
+    script.push_str(&format!(
+        "if [ \"$funcstack[1]\" = \"{}\" ]; then\n    {} \"$@\"\nelse\n    compdef {} {}\nfi\n",
+        function, function, function, BIN
+    ));
+    script
+}
+
+fn fish() -> String {
+    let mut script = String::new();
+    script.push_str(&format!("complete -c {} -f\n", BIN));
+    for subcommand in SUBCOMMANDS {
+        script.push_str(&format!(
+            "complete -c {} -n __fish_use_subcommand -a {} -d {}\n",
+            BIN,
+            subcommand.name,
+            quote(subcommand.help)
+        ));
+        if !subcommand.actions.is_empty() {
+            script.push_str(&format!(
+                "complete -c {} -n '__fish_seen_subcommand_from {}' -a {}\n",
+                BIN,
+                subcommand.name,
+                quote(&subcommand.actions.join(" "))
+            ));
+        }
+    }
+    for flag in FLAGS {
+        let mut line = format!("complete -c {} -l {}", BIN, flag.long);
+        if let Some(short) = flag.short {
+            line.push_str(&format!(" -s {}", short));
+        }
+        if flag.value.is_some() {
+            line.push_str(" -r");
+        }
+        line.push_str(&format!(" -d {}\n", quote(flag.help)));
+        script.push_str(&line);
+    }
+    script
+}
+
+fn powershell() -> String {
+    let list = |words: &[String]| {
+        words
+            .iter()
+            .map(|w| format!("'{}'", w.replace('\'', "''")))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let mut script = String::new();
+    script.push_str(&format!(
+        "Register-ArgumentCompleter -Native -CommandName '{}' -ScriptBlock {{\n",
+        BIN
+    ));
+    script.push_str("    param($wordToComplete, $commandAst, $cursorPosition)\n");
+    script.push_str(
+        "    $elements = @($commandAst.CommandElements | ForEach-Object { $_.ToString() })\n",
+    );
+    script.push_str("    $position = $elements.Count\n");
+    script.push_str("    if ($wordToComplete) { $position -= 1 }\n");
+    script.push_str(&format!("    $words = @({})\n", list(&flag_words())));
+    let commands: Vec<String> = SUBCOMMANDS.iter().map(|s| s.name.to_string()).collect();
+    script.push_str(&format!(
+        "    if ($position -eq 1) {{ $words += @({}) }}\n",
+        list(&commands)
+    ));
+    script.push_str("    elseif ($position -eq 2) {\n");
+    script.push_str("        switch ($elements[1]) {\n");
+    for subcommand in SUBCOMMANDS.iter().filter(|s| !s.actions.is_empty()) {
+        let actions: Vec<String> = subcommand.actions.iter().map(|a| a.to_string()).collect();
+        script.push_str(&format!(
+            "            '{}' {{ $words += @({}) }}\n",
+            subcommand.name,
+            list(&actions)
+        ));
+    }
+    script.push_str("        }\n");
+    script.push_str("    }\n");
+    script.push_str(
+        "    $words | Where-Object { $_ -like \"$wordToComplete*\" } | ForEach-Object {\n",
+    );
+    script.push_str(
+        "        [System.Management.Automation.CompletionResult]::new($_, $_, 'ParameterValue', $_)\n",
+    );
+    script.push_str("    }\n");
+    script.push_str("}\n");
+    script
+}
+
+pub fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let script = match args.first().map(String::as_str) {
+        Some("bash") => bash(),
+        Some("zsh") => zsh(),
+        Some("fish") => fish(),
+        Some("powershell") => powershell(),
+        Some(shell) if shell != "-h" && shell != "--help" => {
+            return Err(Error::Config(format!(
+                "no completions for '{}'; supported shells are {}",
+                shell,
+                SHELLS.join(", ")
+            ))
+            .into());
+        }
+        _ => {
+            println!("Usage: <program> completions <{}>", SHELLS.join("|"));
+            println!(
+                "  e.g. <program> completions bash > ~/.local/share/bash-completion/completions/{}",
+                BIN
+            );
+            return Ok(());
+        }
+    };
+    print!("{}", script);
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use crate::cli::Options;
+use gemini_commit_message::config::{Config, GitConfig, Setting, mask_secret};
+
+/// Prints every effective value next to the source it was taken from.
+fn show(config: &Config) {
+    let print = |name: &str, setting: Option<&Setting>, masked: bool| match setting {
+        Some(setting) => {
+            let value = if masked {
+                mask_secret(&setting.value)
+            } else {
+                setting.value.clone()
+            };
+            println!("{:<11} {:<28} {}", name, value, setting.source);
+        }
+        None => println!("{:<11} {:<28} not set", name, "-"),
+    };
+    print("profile", config.profile.as_ref(), false);
+    print("provider", Some(&config.provider), false);
+    print("model", Some(&config.model), false);
+    print("style", Some(&config.style), false);
+    print("preset", config.preset.as_ref(), false);
+    print("thinking", config.thinking_budget.as_ref(), false);
+    print("concurrency", config.concurrency.as_ref(), false);
+    print("guideline", config.guideline_file.as_ref(), false);
+    print("maxdiff", config.max_diff_bytes.as_ref(), false);
+    print("rpm", config.requests_per_minute.as_ref(), false);
+    print("tpm", config.tokens_per_minute.as_ref(), false);
+    print("recent", config.recent_commits.as_ref(), false);
+    print("repocontext", config.repo_context.as_ref(), false);
+    print("learn", config.learn.as_ref(), false);
+    print("images", config.images.as_ref(), false);
+    print("minify", config.minify.as_ref(), false);
+    print("mood", config.mood.as_ref(), false);
+    print("capitalize", config.capitalize.as_ref(), false);
+    print("period", config.period.as_ref(), false);
+    print("apibase", config.api_base.as_ref(), false);
+    print("unstaged", config.unstaged.as_ref(), false);
+    print("apikey", config.api_key.as_ref(), true);
+    for trailer in &config.trailers {
+        print("trailer", Some(trailer), false);
+    }
+}
+
+pub fn run(options: Options) -> Result<(), Box<dyn std::error::Error>> {
+    if options.help || options.positional.first().map(String::as_str) != Some("show") {
+        println!("Usage: <program> config show [--profile NAME] [--model MODEL] [--api-key KEY]");
+        return Ok(());
+    }
+    show(&Config::resolve(
+        options.overrides,
+        &GitConfig::load_in(options.repo.as_deref()),
+    ));
+    Ok(())
+}
@@ -0,0 +1,165 @@
+//! `doctor`: checks everything a generation depends on and prints the
+//! outcome of each step, so that a failure points at its cause.
+
+use crate::cli::{self, Options};
+use gemini_commit_message::config::{Config, DEFAULT_PROVIDER, GitConfig};
+use gemini_commit_message::git;
+use gemini_commit_message::provider::gemini::DEFAULT_BASE_URL;
+use std::process::Command;
+
+/// The outcome of one check: passed, failed, or not applicable.
+enum Outcome {
+    Pass(String),
+    Fail(String),
+    Skip(String),
+}
+
+fn report(name: &str, outcome: &Outcome) -> bool {
+    let (status, detail, passed) = match outcome {
+        Outcome::Pass(detail) => ("ok", detail, true),
+        Outcome::Fail(detail) => ("FAIL", detail, false),
+        Outcome::Skip(detail) => ("skip", detail, true),
+    };
+    println!("{:<4}  {:<13} {}", status, name, detail);
+    passed
+}
+
+fn staged(options: &Options) -> Outcome {
+    match git::output(
+        options.repo.as_deref(),
+        &["diff", "--cached", "--name-only"],
+    ) {
+        Ok(files) => match files.lines().count() {
+            0 => Outcome::Fail("nothing staged; run git add first".to_string()),
+            count => Outcome::Pass(format!("{} file(s) staged", count)),
+        },
+        Err(e) => Outcome::Fail(e.to_string()),
+    }
+}
+
+/// Builds the generator from the configuration, which fails on any invalid
+/// setting; a missing key is reported on its own.
+fn configuration(options: &Options, config: &Config) -> Outcome {
+    if config.provider.value == DEFAULT_PROVIDER && cli::gemini_client(config).is_none() {
+        return Outcome::Skip("needs an API key".to_string());
+    }
+    let repo = options.repo.as_deref();
+    let built = cli::preset(config, repo)
+        .and_then(|preset| cli::adjust_preset(preset, options))
+        .map_err(|e| e.to_string())
+        .and_then(|preset| {
+            cli::build_generator(config.clone(), &preset)
+                .map(|_| preset)
+                .map_err(|e| e.to_string())
+        });
+    match built {
+        Ok(preset) => Outcome::Pass(format!(
+            "provider {}, model {}, preset {}",
+            config.provider.value, config.model.value, preset.name
+        )),
+        Err(e) => Outcome::Fail(e),
+    }
+}
+
+fn api_key(config: &Config) -> Outcome {
+    if config.provider.value != DEFAULT_PROVIDER {
+        return Outcome::Skip(format!("not used by {}", config.provider.value));
+    }
+    match (&config.api_key, &config.api_base) {
+        (Some(key), _) => Outcome::Pass(format!("from {}", key.source)),
+        (None, Some(base)) => Outcome::Skip(format!("left to the proxy at {}", base.value)),
+        (None, None) => {
+            Outcome::Fail("not set; see `config show` for where it is read from".to_string())
+        }
+    }
+}
+
+/// Lists the models when there is a key, which also proves the key works;
+/// otherwise any HTTP answer from the endpoint counts as reachable.
+fn network(config: &Config) -> Outcome {
+    if config.provider.value != DEFAULT_PROVIDER {
+        return Outcome::Skip(format!("not used by {}", config.provider.value));
+    }
+    let endpoint = config
+        .api_base
+        .as_ref()
+        .map_or(DEFAULT_BASE_URL, |base| base.value.as_str());
+    let Some(gemini) = cli::gemini_client(config) else {
+        return match ureq::get(endpoint).call() {
+            Ok(_) | Err(ureq::Error::StatusCode(_)) => {
+                Outcome::Pass(format!("{} is reachable", endpoint))
+            }
+            Err(e) => Outcome::Fail(format!("cannot reach {}: {}", endpoint, e)),
+        };
+    };
+    match gemini.list_models() {
+        Ok(models) if models.iter().any(|m| m.name == config.model.value) => {
+            Outcome::Pass(format!("the key can use {}", config.model.value))
+        }
+        Ok(_) => Outcome::Fail(format!(
+            "the key cannot use {}; see `models`",
+            config.model.value
+        )),
+        Err(e) => Outcome::Fail(format!("{}: {}", endpoint, e)),
+    }
+}
+
+/// Whether `--commit` will be able to sign, as far as finding the program
+/// `git commit` signs with goes.
+fn signing(options: &Options) -> Outcome {
+    let repo = options.repo.as_deref();
+    let Some(signing) = git::commit_signing(repo) else {
+        return Outcome::Skip("commit.gpgSign is off".to_string());
+    };
+    let program = git::signing_program(repo);
+    match Command::new(&program).arg("--version").output() {
+        Ok(_) => Outcome::Pass(format!("{} with {}", signing, program)),
+        Err(e) => Outcome::Fail(format!("{}: cannot run {}: {}", signing, program, e)),
+    }
+}
+
+fn clipboard() -> Outcome {
+    match cli::clipboard::available() {
+        Ok(()) => Outcome::Pass("available".to_string()),
+        Err(e) => Outcome::Fail(format!("{}; use --quiet or -o to get the message", e)),
+    }
+}
+
+pub fn run(options: Options) -> Result<(), Box<dyn std::error::Error>> {
+    if options.help {
+        println!("Usage: <program> doctor [-C PATH] [--profile NAME] [--api-key KEY]");
+        println!("  Checks the repository, staged changes, configuration, API key, access to");
+        println!("  the Gemini API, commit signing and the clipboard, and prints the result");
+        println!("  of each.");
+        return Ok(());
+    }
+    let repo = options.repo.as_deref();
+    let mut failed = 0;
+    let mut check = |name: &str, outcome: Outcome| {
+        if !report(name, &outcome) {
+            failed += 1;
+        }
+    };
+
+    match git::git_dir(repo) {
+        Ok(dir) => {
+            check("repository", Outcome::Pass(dir.display().to_string()));
+            check("staged", staged(&options));
+        }
+        Err(e) => {
+            check("repository", Outcome::Fail(e.to_string()));
+            check("staged", Outcome::Skip("no repository".to_string()));
+        }
+    }
+    let config = Config::resolve(options.overrides.clone(), &GitConfig::load_in(repo));
+    check("api key", api_key(&config));
+    check("configuration", configuration(&options, &config));
+    check("network", network(&config));
+    check("signing", signing(&options));
+    check("clipboard", clipboard());
+
+    if failed > 0 {
+        return Err(format!("{} check(s) failed", failed).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,31 @@
+//! `explain [RANGE]`: a walkthrough of the changes for a reviewer instead of
+//! a commit message.
+
+use crate::cli::{self, Options};
+use gemini_commit_message::prompt::{EXPLAIN_GUIDELINE, PromptBuilder};
+use gemini_commit_message::{DiffSource, git};
+
+pub fn run(options: Options) -> Result<(), Box<dyn std::error::Error>> {
+    if options.help {
+        println!("Usage: <program> explain [RANGE] [--stdin] [-C PATH] [OPTIONS]");
+        println!("  Walks a reviewer through the staged changes (or RANGE, e.g. main...HEAD):");
+        println!("  what changed, behavioral changes, risk areas and suggested tests.");
+        return Ok(());
+    }
+    let repo = options.repo.as_deref();
+    // Corrections are about commit messages, not reviews.
+    let generator = cli::setup(&options)?
+        .corrections(0)
+        .prompt_builder(PromptBuilder::new().guideline(EXPLAIN_GUIDELINE));
+    let diff = match options.positional.first() {
+        Some(range) => git::diff_range(repo, range)?,
+        None if options.stdin => DiffSource::Stdin.read(repo)?,
+        None => DiffSource::Staged.read(repo)?,
+    };
+    if diff.trim().is_empty() {
+        println!("No changes to explain");
+        return Ok(());
+    }
+    println!("{}", generator.generate_text_for_diff(&diff)?);
+    Ok(())
+}
@@ -0,0 +1,105 @@
+//! `history`: browse and re-copy previously generated messages.
+
+use crate::cli::{self, Options};
+use gemini_commit_message::CommitMessage;
+use gemini_commit_message::git;
+use gemini_commit_message::history::{Entry, History, Status};
+use gemini_commit_message::log;
+use std::path::Path;
+
+/// Entries listed without a number.
+const LISTED: usize = 20;
+
+/// The top of the work tree `repo` is in, empty outside one.
+fn toplevel(repo: Option<&Path>) -> String {
+    git::toplevel(repo)
+        .map(|path| path.display().to_string())
+        .unwrap_or_default()
+}
+
+/// Logs the candidates generated for `diff`; `chosen` is the index of the
+/// one committed, if any. Failing to write the history never fails a run.
+pub fn record(
+    repo: Option<&Path>,
+    diff: &str,
+    candidates: &[CommitMessage],
+    chosen: Option<usize>,
+) {
+    let Some(history) = History::open() else {
+        return;
+    };
+    let repo = toplevel(repo);
+    let entries: Vec<Entry> = candidates
+        .iter()
+        .enumerate()
+        .map(|(i, candidate)| {
+            let status = match chosen {
+                None => Status::Shown,
+                Some(chosen) if chosen == i => Status::Chosen,
+                Some(_) => Status::Rejected,
+            };
+            Entry::new(&repo, diff, &candidate.to_string(), status)
+        })
+        .collect();
+    if let Err(e) = history.record(&entries) {
+        log::info(&format!("cannot write the history: {}", e));
+    }
+}
+
+pub fn run(options: Options) -> Result<(), Box<dyn std::error::Error>> {
+    let action = options.positional.first().map(String::as_str);
+    if options.help || !matches!(action, None | Some("list" | "show" | "copy")) {
+        println!("Usage: <program> history [list | show N | copy N] [-C PATH]");
+        println!(
+            "  Lists the last {} messages generated in this repository",
+            LISTED
+        );
+        println!("  (anywhere, outside of one), most recent first. `show N` prints");
+        println!("  message N in full and `copy N` copies it to the clipboard.");
+        return Ok(());
+    }
+    let history = History::open().ok_or("no cache directory for the history")?;
+    let repo = toplevel(options.repo.as_deref());
+    let entries: Vec<Entry> = history
+        .entries()
+        .into_iter()
+        .rev()
+        .filter(|entry| repo.is_empty() || entry.repo == repo)
+        .collect();
+
+    let Some(number) = options.positional.get(1) else {
+        if entries.is_empty() {
+            println!("No messages generated yet");
+        }
+        for (i, entry) in entries.iter().take(LISTED).enumerate() {
+            let header = entry.message.lines().next().unwrap_or_default();
+            let place = if repo.is_empty() {
+                format!("  {}", entry.repo)
+            } else {
+                String::new()
+            };
+            println!(
+                "{:>3}  {}  {:<8}  {}{}",
+                i + 1,
+                entry.date(),
+                entry.status.name(),
+                header,
+                place
+            );
+        }
+        return Ok(());
+    };
+    let entry = number
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| entries.get(n.checked_sub(1)?))
+        .ok_or_else(|| format!("no message {} in the history", number))?;
+    match action {
+        Some("copy") => {
+            cli::clipboard::copy(&entry.message)?;
+            eprintln!("copied message {}", number);
+        }
+        _ => println!("{}", entry.message),
+    }
+    Ok(())
+}
@@ -0,0 +1,127 @@
+//! `hook`: installs a `prepare-commit-msg` hook that fills in the generated
+//! message when `git commit` opens the editor, and runs as that hook.
+//!
+//! Paths come from `git rev-parse --git-path`, so linked worktrees (whose
+//! `.git` is a file) share the main repository's hooks and keep their own
+//! `COMMIT_EDITMSG`, and `core.hooksPath` is honoured.
+
+use crate::cli::{self, Options};
+use gemini_commit_message::{Error, git};
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const HOOK: &str = "prepare-commit-msg";
+
+/// Marks a hook as installed by this tool, so it is never overwritten or
+/// removed by mistake.
+const MARKER: &str = "# Installed by gemini-commit-message";
+
+/// The hook file, in the common hooks directory of every worktree.
+fn hook_path(repo: Option<&Path>) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(git::git_path(repo, "hooks")?.join(HOOK))
+}
+
+fn install(repo: Option<&Path>) -> Result<(), Box<dyn std::error::Error>> {
+    let path = hook_path(repo)?;
+    if let Ok(existing) = fs::read_to_string(&path)
+        && !existing.contains(MARKER)
+    {
+        return Err(format!(
+            "{} already exists; add `{} hook {} \"$@\"` to it yourself",
+            path.display(),
+            env!("CARGO_PKG_NAME"),
+            HOOK
+        )
+        .into());
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(
+        &path,
+        format!(
+            "#!/bin/sh\n{}: writes a generated message when none was given.\nexec {} hook {} \"$@\"\n",
+            MARKER,
+            env!("CARGO_PKG_NAME"),
+            HOOK
+        ),
+    )?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+    }
+    eprintln!("installed {}", path.display());
+    Ok(())
+}
+
+fn uninstall(repo: Option<&Path>) -> Result<(), Box<dyn std::error::Error>> {
+    let path = hook_path(repo)?;
+    match fs::read_to_string(&path) {
+        Ok(existing) if existing.contains(MARKER) => {
+            fs::remove_file(&path)?;
+            eprintln!("removed {}", path.display());
+            Ok(())
+        }
+        Ok(_) => Err(format!("{} was not installed by this tool", path.display()).into()),
+        Err(_) => Ok(()),
+    }
+}
+
+/// Runs as the hook: writes the message for the staged changes above what
+/// git put in `file`. A message given with `-m`, `-F`, a template, a merge
+/// or an amend (any `source`) is left alone, and a failure never stops
+/// the commit.
+fn prepare(options: &Options, file: Option<&str>, source: Option<&str>) {
+    if source.is_some_and(|source| !source.is_empty()) {
+        return;
+    }
+    let repo = options.repo.as_deref();
+    let written = git::toplevel(repo)
+        .map_err(|e| e.to_string())
+        .and_then(|root| {
+            let request = json!({"repo_path": root, "staged": true});
+            cli::generate_for_request(&options.overrides, &request)
+        })
+        .and_then(|message| {
+            let path = match file {
+                Some(file) => PathBuf::from(file),
+                None => git::git_path(repo, "COMMIT_EDITMSG").map_err(|e| e.to_string())?,
+            };
+            let existing = fs::read_to_string(&path).unwrap_or_default();
+            fs::write(&path, format!("{}\n{}", message.trim_end(), existing))
+                .map_err(|e| format!("cannot write {}: {}", path.display(), e))
+        });
+    if let Err(e) = written
+        && !options.quiet
+    {
+        eprintln!("{}: {}", env!("CARGO_PKG_NAME"), e);
+    }
+}
+
+pub fn run(options: Options) -> Result<(), Box<dyn std::error::Error>> {
+    let positional: Vec<&str> = options.positional.iter().map(String::as_str).collect();
+    if options.help || positional.is_empty() {
+        println!("Usage: <program> hook <install|uninstall> [-C PATH]");
+        println!("  install puts a prepare-commit-msg hook in the repository's hooks directory");
+        println!("  (shared by its worktrees, or core.hooksPath), so that `git commit` opens the");
+        println!("  editor with a generated message. uninstall removes it again.");
+        return Ok(());
+    }
+    let repo = options.repo.as_deref();
+    git::git_dir(repo)?;
+    match positional[0] {
+        "install" => install(repo),
+        "uninstall" => uninstall(repo),
+        HOOK => {
+            prepare(
+                &options,
+                positional.get(1).copied(),
+                positional.get(2).copied(),
+            );
+            Ok(())
+        }
+        other => Err(Error::Config(format!("unknown hook command '{}'", other)).into()),
+    }
+}
@@ -0,0 +1,37 @@
+//! `learn`: records how the last commit's message was edited from the
+//! generated one, e.g. from a `post-commit` hook.
+
+use crate::cli::{self, Options};
+use gemini_commit_message::config::{Config, GitConfig};
+use gemini_commit_message::{git, learn, log};
+use std::path::Path;
+
+/// Learns from `HEAD` after committing; a failure is only logged.
+pub fn after_commit(repo: Option<&Path>) {
+    match learn::learn(repo) {
+        Ok(Some(_)) => log::info("recorded the edit of the generated message"),
+        Ok(None) => {}
+        Err(e) => log::info(&format!("cannot learn from the commit: {}", e)),
+    }
+}
+
+pub fn run(options: Options) -> Result<(), Box<dyn std::error::Error>> {
+    if options.help {
+        println!("Usage: <program> learn [-C PATH]");
+        println!("  Compares the message of HEAD with the one generated for its changes and,");
+        println!("  if it was edited, shows the pair to the model in future prompts.");
+        println!("  Run it from .git/hooks/post-commit; git config gemini.learn false turns");
+        println!("  learning off.");
+        return Ok(());
+    }
+    let repo = options.repo.as_deref();
+    git::git_dir(repo)?;
+    let config = Config::resolve(options.overrides, &GitConfig::load_in(repo));
+    if !cli::learning(&config)? {
+        return Ok(());
+    }
+    if learn::learn(repo)?.is_some() && !options.quiet {
+        eprintln!("learned from the edited message");
+    }
+    Ok(())
+}
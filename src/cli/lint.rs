@@ -0,0 +1,114 @@
+//! `lint <file|->`: check a written message, e.g. from a `commit-msg` hook,
+//! and optionally let the model rewrite it.
+
+use crate::cli::{self, Options};
+use gemini_commit_message::config::GitConfig;
+use gemini_commit_message::i18n::{Message, tr};
+use gemini_commit_message::lint::{self, Problem, Rules, Severity};
+use gemini_commit_message::prompt::{
+    self, COMMIT_MESSAGE_GUIDELINE, LINT_REWRITE_GUIDELINE, PromptBuilder,
+};
+use gemini_commit_message::{CommitMessage, Error, git};
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
+fn has_errors(problems: &[Problem]) -> bool {
+    problems.iter().any(|p| p.severity == Severity::Error)
+}
+
+fn report(problems: &[Problem]) {
+    for problem in problems {
+        eprintln!("{}", problem);
+    }
+}
+
+/// Asks the model for a version of `original` that fixes `problems`.
+fn rewrite(
+    options: &Options,
+    original: &str,
+    problems: &[Problem],
+) -> Result<CommitMessage, Box<dyn std::error::Error>> {
+    let generator = cli::setup(options)?.prompt_builder(PromptBuilder::new().guideline(format!(
+        "{}\n{}",
+        COMMIT_MESSAGE_GUIDELINE, LINT_REWRITE_GUIDELINE
+    )));
+    let problems: Vec<String> = problems.iter().map(|p| format!("- {}", p)).collect();
+    let mut content = format!(
+        "### Message\n\n{}\n\n### Problems\n\n{}",
+        original,
+        problems.join("\n")
+    );
+    let diff = git::output(options.repo.as_deref(), &["diff", "--cached"]).unwrap_or_default();
+    if !diff.trim().is_empty() {
+        content.push_str(&format!(
+            "\n\n### Staged Diff\n\n{}",
+            prompt::fenced("diff", &diff)
+        ));
+    }
+    Ok(CommitMessage::parse(
+        &generator.generate_text("Commit Message", &content)?,
+    ))
+}
+
+pub fn run(options: Options) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(source) = options.positional.first().filter(|_| !options.help) else {
+        println!("Usage: <program> lint <FILE|-> [--fix] [-C PATH] [OPTIONS]");
+        println!("  Checks a commit message against Conventional Commits and the rules of a");
+        println!("  JSON commitlint configuration (.commitlintrc, .commitlintrc.json or the");
+        println!("  \"commitlint\" key of package.json). Fails with exit code 5 on errors.");
+        println!("  With --fix, the model rewrites a failing message, in place for a FILE.");
+        println!("  As a commit-msg hook: exec <program> lint \"$1\"");
+        return Ok(());
+    };
+    let repo = options.repo.as_deref();
+    let text = if source == "-" {
+        let mut text = String::new();
+        io::stdin().read_to_string(&mut text)?;
+        text
+    } else {
+        fs::read_to_string(source).map_err(|e| format!("cannot read {}: {}", source, e))?
+    };
+    let comment_char = GitConfig::load_in(repo)
+        .get("core.commentChar")
+        .and_then(|entry| entry.value.chars().next())
+        .filter(|c| *c != 'a') // `auto` picks a character git did not need, `#` by default
+        .unwrap_or('#');
+    let original = lint::strip_comments(&text, comment_char);
+    if original.is_empty() {
+        // git aborts an empty commit itself.
+        return Ok(());
+    }
+
+    let root = git::toplevel(repo).unwrap_or_else(|_| repo.unwrap_or(Path::new(".")).into());
+    if let Some(path) = Rules::unreadable_config(&root) {
+        eprintln!(
+            "ignoring {}: only JSON commitlint configurations are read",
+            path.display()
+        );
+    }
+    let rules = Rules::load(&root)?;
+    let problems = lint::lint(&CommitMessage::parse(&original), &rules);
+    report(&problems);
+    if !has_errors(&problems) {
+        return Ok(());
+    }
+    if !options.fix {
+        return Err(Error::Validation(tr(Message::LintFailed, &[&problems.len()])).into());
+    }
+
+    let rewritten = rewrite(&options, &original, &problems)?;
+    let remaining = lint::lint(&rewritten, &rules);
+    if has_errors(&remaining) {
+        eprintln!("the rewritten message still fails:\n{}", rewritten);
+        report(&remaining);
+        return Err(Error::Validation(tr(Message::LintFailed, &[&remaining.len()])).into());
+    }
+    if source == "-" {
+        println!("{}", rewritten);
+    } else {
+        fs::write(source, format!("{}\n", rewritten))?;
+        eprintln!("rewrote the message:\n{}", rewritten);
+    }
+    Ok(())
+}
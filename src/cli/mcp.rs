@@ -0,0 +1,120 @@
+//! A Model Context Protocol server speaking newline-delimited JSON-RPC 2.0
+//! on stdin/stdout, so agents and editors can call the generator as a tool.
+
+use crate::cli::{self, Options};
+use gemini_commit_message::config::Overrides;
+use serde_json::{Value, json};
+use std::io::{self, BufRead, Write};
+
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+fn tools() -> Value {
+    json!([
+        {
+            "name": "generate_commit_message",
+            "description": "Generate a Conventional Commits message for the changes of a git repository.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "repo_path": {
+                        "type": "string",
+                        "description": "Path of the git repository (or any directory inside it)."
+                    },
+                    "staged": {
+                        "type": "boolean",
+                        "description": "Describe the staged changes (default) or the unstaged working tree changes."
+                    }
+                },
+                "required": ["repo_path"]
+            }
+        }
+    ])
+}
+
+fn call_tool(overrides: &Overrides, params: &Value) -> Result<Value, (i64, String)> {
+    let name = params.get("name").and_then(Value::as_str).unwrap_or("");
+    let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+    let result = match name {
+        "generate_commit_message" => cli::generate_for_request(overrides, &arguments),
+        _ => return Err((INVALID_PARAMS, format!("unknown tool '{}'", name))),
+    };
+    // Tool failures are reported inside the result so the model can see them.
+    Ok(match result {
+        Ok(text) => json!({ "content": [{ "type": "text", "text": text }], "isError": false }),
+        Err(text) => json!({ "content": [{ "type": "text", "text": text }], "isError": true }),
+    })
+}
+
+fn handle(overrides: &Overrides, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+    match method {
+        "initialize" => Ok(json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": { "tools": {} },
+            "serverInfo": {
+                "name": env!("CARGO_PKG_NAME"),
+                "version": env!("CARGO_PKG_VERSION")
+            }
+        })),
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(json!({ "tools": tools() })),
+        "tools/call" => call_tool(overrides, params),
+        _ => Err((METHOD_NOT_FOUND, format!("method '{}' not found", method))),
+    }
+}
+
+fn respond(out: &mut impl Write, response: Value) -> io::Result<()> {
+    writeln!(out, "{}", response)?;
+    out.flush()
+}
+
+pub fn run(options: Options) -> Result<(), Box<dyn std::error::Error>> {
+    if options.help {
+        println!("Usage: <program> mcp [--profile NAME] [--model MODEL] [--api-key KEY]");
+        println!("  Serves the generate_commit_message tool over MCP on stdin/stdout.");
+        return Ok(());
+    }
+
+    let stdin = io::stdin();
+    let mut stdout = io::stdout().lock();
+    for line in stdin.lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let request: Value = match serde_json::from_str(&line) {
+            Ok(request) => request,
+            Err(e) => {
+                respond(
+                    &mut stdout,
+                    json!({
+                        "jsonrpc": "2.0",
+                        "id": null,
+                        "error": { "code": PARSE_ERROR, "message": e.to_string() }
+                    }),
+                )?;
+                continue;
+            }
+        };
+
+        let method = request.get("method").and_then(Value::as_str).unwrap_or("");
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+        // Notifications such as `notifications/initialized` carry no id and get no reply.
+        let Some(id) = request.get("id").cloned() else {
+            continue;
+        };
+        let response = match handle(&options.overrides, method, &params) {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": code, "message": message }
+            }),
+        };
+        respond(&mut stdout, response)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,54 @@
+//! `models`: the Gemini models the configured key can use.
+
+use crate::cli::{self, Options};
+use gemini_commit_message::Error;
+use gemini_commit_message::config::{Config, DEFAULT_PROVIDER, GitConfig};
+use gemini_commit_message::i18n::{Message, tr};
+
+pub fn run(options: Options) -> Result<(), Box<dyn std::error::Error>> {
+    if options.help {
+        println!("Usage: <program> models [--profile NAME] [--api-key KEY]");
+        println!("  Lists the models the key can generate with and their token limits;");
+        println!("  the one currently configured is marked with *.");
+        return Ok(());
+    }
+    let config = Config::resolve(
+        options.overrides,
+        &GitConfig::load_in(options.repo.as_deref()),
+    );
+    if config.provider.value != DEFAULT_PROVIDER {
+        return Err(Error::Config(tr(
+            Message::ModelsNeedGemini,
+            &[&config.provider.value, &config.provider.source],
+        ))
+        .into());
+    }
+    let Some(gemini) = cli::gemini_client(&config) else {
+        return Err(Error::Config(tr(Message::NoApiKey, &[])).into());
+    };
+    let mut models = gemini.list_models()?;
+    models.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let width = models.iter().map(|m| m.name.len()).max().unwrap_or(0);
+    println!(
+        "  {:width$}  {:>9}  {:>7}  NAME",
+        "MODEL", "INPUT", "OUTPUT"
+    );
+    for model in &models {
+        let current = if model.name == config.model.value {
+            '*'
+        } else {
+            ' '
+        };
+        println!(
+            "{} {:width$}  {:>9}  {:>7}  {}",
+            current,
+            model.name,
+            model.input_token_limit,
+            model.output_token_limit,
+            model.display_name,
+            width = width
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,73 @@
+//! `next-version`: the semver bump implied by the commits since the last tag.
+
+use crate::cli::{self, Options};
+use gemini_commit_message::prompt::{self, BREAKING_CHANGE_CHECK_GUIDELINE, PromptBuilder};
+use gemini_commit_message::version::{self, Bump, Version};
+use gemini_commit_message::{commits, git};
+
+pub fn run(options: Options) -> Result<(), Box<dyn std::error::Error>> {
+    if options.help {
+        println!("Usage: <program> next-version [--check] [-C PATH] [OPTIONS]");
+        println!("  Prints the next version from the commits since the last tag:");
+        println!("  breaking changes bump major (minor before 1.0.0), feat minor, others patch.");
+        println!("  With --check, the model also looks for unmarked breaking changes.");
+        println!("  e.g. git tag -a \"$(<program> next-version)\"");
+        return Ok(());
+    }
+    let repo = options.repo.as_deref();
+    git::git_dir(repo)?;
+    let tag = git::output(repo, &["describe", "--tags", "--abbrev=0"])
+        .ok()
+        .map(|tag| tag.trim().to_string());
+    let (current, range) = match &tag {
+        Some(tag) => (
+            Version::parse(tag).ok_or_else(|| format!("{} is not a semantic version", tag))?,
+            format!("{}..HEAD", tag),
+        ),
+        None => (
+            Version {
+                prefix: String::new(),
+                major: 0,
+                minor: 0,
+                patch: 0,
+            },
+            "HEAD".to_string(),
+        ),
+    };
+    let commits = commits::log(repo, &range)?;
+    let Some(mut bump) = version::bump_for(&commits) else {
+        eprintln!("No commits since {}", current);
+        println!("{}", current);
+        return Ok(());
+    };
+
+    if options.check && bump < Bump::Major {
+        let generator = cli::setup(&options)?
+            .prompt_builder(PromptBuilder::new().guideline(BREAKING_CHANGE_CHECK_GUIDELINE));
+        let diff = match &tag {
+            Some(tag) => git::diff_range(repo, &format!("{}...HEAD", tag))?,
+            None => git::output(repo, &["diff", &git::empty_tree(repo)?, "HEAD"])?,
+        };
+        let content = format!(
+            "### Commits\n\n{}\n\n### Diff\n\n{}",
+            commits::to_prompt_text(&commits),
+            prompt::fenced("diff", &diff)
+        );
+        let review = generator.generate_text("Changes", &content)?;
+        if review != "NONE" {
+            eprintln!("Possible unmarked breaking changes:\n{}", review);
+            bump = Bump::Major;
+        }
+    }
+
+    let next = current.bump(bump);
+    eprintln!(
+        "{} -> {} ({}, {} commits)",
+        current,
+        next,
+        bump,
+        commits.len()
+    );
+    println!("{}", next);
+    Ok(())
+}
@@ -0,0 +1,127 @@
+//! `pr [BASE]`: pull / merge request title and description for the current branch.
+
+use crate::cli::{self, Options};
+use gemini_commit_message::commits;
+use gemini_commit_message::forge::{self, Forge};
+use gemini_commit_message::git;
+use gemini_commit_message::prompt::{self, PULL_REQUEST_GUIDELINE, PromptBuilder};
+use std::path::Path;
+
+const DEFAULT_BASE: &str = "origin/main";
+
+/// The branch `origin/HEAD` points at, falling back to `origin/main`.
+pub fn default_base(repo: Option<&Path>) -> String {
+    git::output(repo, &["rev-parse", "--abbrev-ref", "origin/HEAD"])
+        .map(|base| base.trim().to_string())
+        .ok()
+        .filter(|base| !base.is_empty() && base != "origin/HEAD")
+        .unwrap_or_else(|| DEFAULT_BASE.to_string())
+}
+
+/// Splits the generated text into a title and a body.
+fn split_title(text: &str) -> (&str, &str) {
+    match text.split_once('\n') {
+        Some((title, body)) => (title.trim().trim_start_matches("# "), body.trim()),
+        None => (text.trim(), ""),
+    }
+}
+
+/// The forge behind the remote of `base`, a token for it and the current branch.
+fn connect(
+    repo: Option<&Path>,
+    base: &str,
+) -> Result<(Forge, String, String), Box<dyn std::error::Error>> {
+    let remote = base.split_once('/').map_or("origin", |(remote, _)| remote);
+    let url = git::output(repo, &["remote", "get-url", remote])?;
+    let forge = Forge::detect(&url).ok_or_else(|| {
+        format!(
+            "{} ({}) is neither a GitHub nor a GitLab remote",
+            remote,
+            url.trim()
+        )
+    })?;
+    let token = forge
+        .token_from_env()
+        .ok_or("Set GITHUB_TOKEN (or GH_TOKEN) / GITLAB_TOKEN to reach the request")?;
+    let head = git::output(repo, &["rev-parse", "--abbrev-ref", "HEAD"])?;
+    Ok((forge, token, head.trim().to_string()))
+}
+
+fn create(
+    repo: Option<&Path>,
+    base: &str,
+    title: &str,
+    body: &str,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let target = base.split_once('/').map_or(base, |(_, target)| target);
+    let (forge, token, head) = connect(repo, base)?;
+    forge.create_pull_request(&token, &head, target, title, &forge::marked(body))
+}
+
+/// Replaces the generated section of the open request's description,
+/// keeping whatever was written around it.
+fn update(
+    repo: Option<&Path>,
+    base: &str,
+    body: &str,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let (forge, token, head) = connect(repo, base)?;
+    let request = forge
+        .find_pull_request(&token, &head)?
+        .ok_or_else(|| format!("no open pull request from {}", head))?;
+    let description = forge::replace_marked(&request.body, body);
+    forge.update_pull_request(&token, request.number, &description)?;
+    Ok(request.url)
+}
+
+pub fn run(options: Options) -> Result<(), Box<dyn std::error::Error>> {
+    if options.help {
+        println!(
+            "Usage: <program> pr [BASE] [--body-only] [--create | --update] [-C PATH] [OPTIONS]"
+        );
+        println!("  Writes a title and description for the commits in BASE..HEAD");
+        println!("  (BASE defaults to origin/HEAD, or origin/main).");
+        println!("  With --body-only, prints only the description, e.g.");
+        println!("    <program> pr --body-only | gh pr create --title \"...\" --body-file -");
+        println!("  With --create, opens the request through the GitHub or GitLab API using");
+        println!("  GITHUB_TOKEN / GH_TOKEN or GITLAB_TOKEN. Push the branch first.");
+        println!("  With --update, rewrites the description of the branch's open request;");
+        println!("  only the part between the gemini-commit-message markers is replaced.");
+        return Ok(());
+    }
+    let repo = options.repo.as_deref();
+    let generator = cli::setup(&options)?
+        .prompt_builder(PromptBuilder::new().guideline(PULL_REQUEST_GUIDELINE));
+    let base = match options.positional.first() {
+        Some(base) => base.clone(),
+        None => default_base(repo),
+    };
+
+    let commits = commits::log(repo, &format!("{}..HEAD", base))?;
+    if commits.is_empty() {
+        println!("No commits in {}..HEAD", base);
+        return Ok(());
+    }
+    let diff = git::diff_range(repo, &format!("{}...HEAD", base))?;
+    let content = format!(
+        "### Commits\n\n{}\n\n### Diff\n\n{}",
+        commits::to_prompt_text(&commits),
+        prompt::fenced("diff", &diff)
+    );
+    let text = generator.generate_text("Branch", &content)?;
+    let (title, body) = split_title(&text);
+
+    if options.create {
+        let url = create(repo, &base, title, body)?;
+        eprintln!("created {}", url);
+    } else if options.update {
+        let url = update(repo, &base, body)?;
+        eprintln!("updated {}", url);
+    }
+    if options.body_only {
+        println!("{}", body);
+    } else {
+        println!("{}\n\n{}", title, body);
+    }
+    Ok(())
+}
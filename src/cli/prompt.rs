@@ -0,0 +1,102 @@
+//! `prompt export|import|reset`: edit the guideline sent to the model
+//! without rebuilding. `export` writes the active one, `import` makes an
+//! edited copy active for every repository and `reset` goes back to the
+//! preset's own.
+
+use crate::cli::{self, Options};
+use gemini_commit_message::Error;
+use gemini_commit_message::config::{Config, GitConfig};
+use gemini_commit_message::preset::Preset;
+use std::fs;
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+fn usage() {
+    println!("Usage: <program> prompt <export [FILE]|import <FILE|->|reset> [OPTIONS]");
+    println!("  export writes the guideline of the active preset (or the imported one) to");
+    println!("  FILE or stdout. import makes the edited FILE the guideline of every preset;");
+    println!("  examples, types and subject rules still apply. reset removes it again.");
+    println!("  `git config gemini.guidelineFile PATH` takes precedence over the import.");
+}
+
+/// Where `import` saves the guideline.
+fn imported() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Preset::imported_guideline_file()
+        .ok_or_else(|| "no config directory; set HOME or XDG_CONFIG_HOME".into())
+}
+
+fn export(options: &Options, file: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let repo = options.repo.as_deref();
+    let config = Config::resolve(options.overrides.clone(), &GitConfig::load_in(repo));
+    let preset = cli::configured_preset(&config, repo)?;
+    let text = format!("{}\n", preset.guideline.trim());
+    match file {
+        None | Some("-") => print!("{}", text),
+        Some(file) => {
+            fs::write(file, text).map_err(|e| format!("cannot write {}: {}", file, e))?;
+            eprintln!(
+                "wrote the guideline of preset '{}' to {}",
+                preset.name, file
+            );
+        }
+    }
+    Ok(())
+}
+
+fn import(options: &Options, file: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let text = match file {
+        "-" => {
+            let mut text = String::new();
+            io::stdin().read_to_string(&mut text)?;
+            text
+        }
+        file => fs::read_to_string(file).map_err(|e| format!("cannot read {}: {}", file, e))?,
+    };
+    if text.trim().is_empty() {
+        return Err(Error::Config(format!("{} holds no guideline", file)).into());
+    }
+    let path = imported()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, format!("{}\n", text.trim()))?;
+    eprintln!("the guideline in {} is now active", path.display());
+    let config = Config::resolve(
+        options.overrides.clone(),
+        &GitConfig::load_in(options.repo.as_deref()),
+    );
+    if let Some(setting) = &config.guideline_file {
+        eprintln!(
+            "but gemini.guidelineFile = {} ({}) takes precedence here",
+            setting.value, setting.source
+        );
+    }
+    Ok(())
+}
+
+fn reset() -> Result<(), Box<dyn std::error::Error>> {
+    let path = imported()?;
+    if path.exists() {
+        fs::remove_file(&path)?;
+        eprintln!("removed {}", path.display());
+    }
+    Ok(())
+}
+
+pub fn run(options: Options) -> Result<(), Box<dyn std::error::Error>> {
+    let positional: Vec<&str> = options.positional.iter().map(String::as_str).collect();
+    match positional.as_slice() {
+        _ if options.help => {
+            usage();
+            Ok(())
+        }
+        ["export"] => export(&options, None),
+        ["export", file] => export(&options, Some(file)),
+        ["import", file] => import(&options, file),
+        ["reset"] => reset(),
+        _ => {
+            usage();
+            Err(Error::Config("prompt needs export, import FILE or reset".to_string()).into())
+        }
+    }
+}
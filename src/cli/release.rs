@@ -0,0 +1,34 @@
+//! `release <from>..<to>`: grouped release notes, optionally as an annotated tag.
+
+use crate::cli::{self, Options};
+use gemini_commit_message::commits;
+use gemini_commit_message::git;
+use gemini_commit_message::prompt::{PromptBuilder, RELEASE_NOTES_GUIDELINE};
+
+pub fn run(options: Options) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(range) = options.positional.first().filter(|_| !options.help) else {
+        println!("Usage: <program> release <FROM>..<TO> [--tag NAME] [-C PATH] [OPTIONS]");
+        println!("  Writes release notes for the commits in the range (TO defaults to HEAD).");
+        println!("  With --tag NAME, also creates an annotated tag NAME on TO with the notes.");
+        return Ok(());
+    };
+    let repo = options.repo.as_deref();
+    let (from, to) = commits::split_range(range);
+    let range = format!("{}..{}", from, to);
+
+    let generator = cli::setup(&options)?
+        .prompt_builder(PromptBuilder::new().guideline(RELEASE_NOTES_GUIDELINE));
+    let commits = commits::log(repo, &range)?;
+    if commits.is_empty() {
+        println!("No commits in {}", range);
+        return Ok(());
+    }
+    let notes = generator.generate_text("Commits", &commits::to_prompt_text(&commits))?;
+
+    if let Some(tag) = &options.tag {
+        git::output(repo, &["tag", "-a", tag, "-m", &notes, to])?;
+        eprintln!("created tag {} on {}", tag, to);
+    }
+    println!("{}", notes);
+    Ok(())
+}
@@ -0,0 +1,114 @@
+//! `revert <COMMIT>`: the message of a commit undoing another one, with the
+//! `This reverts commit <hash>.` line and an explanation of what is undone.
+
+use crate::cli::{self, Options};
+use gemini_commit_message::config::{Config, GitConfig};
+use gemini_commit_message::prompt::{self, PromptBuilder, REVERT_GUIDELINE};
+use gemini_commit_message::style::Style;
+use gemini_commit_message::{CommitMessage, Error, git};
+use std::fs;
+use std::path::Path;
+
+/// Runs `git revert --no-commit` and commits with `message`; on conflicts
+/// the message is left in `MERGE_MSG` for the `git commit` that follows.
+fn commit(
+    repo: Option<&Path>,
+    commit: &str,
+    merge: bool,
+    message: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = vec!["revert", "--no-commit"];
+    if merge {
+        args.extend(["-m", "1"]);
+    }
+    args.push(commit);
+    if !git::command(repo).args(&args).status()?.success() {
+        let path = git::git_path(repo, "MERGE_MSG")?;
+        fs::write(&path, format!("{}\n", message))?;
+        return Err(format!(
+            "git revert stopped; resolve the conflicts and run git commit, which uses the message in {}",
+            path.display()
+        )
+        .into());
+    }
+    cli::commit::commit(repo, message)
+}
+
+pub fn run(options: Options) -> Result<(), Box<dyn std::error::Error>> {
+    if options.help {
+        println!("Usage: <program> revert <COMMIT> [--commit] [-C PATH] [OPTIONS]");
+        println!("  Writes the message of a commit reverting COMMIT: `revert: <its header>`,");
+        println!("  `This reverts commit <hash>.` and what the revert undoes. --commit runs");
+        println!("  git revert and commits with the message.");
+        return Ok(());
+    }
+    let Some(revision) = options.positional.first() else {
+        return Err(Error::Config("revert needs the commit to revert".to_string()).into());
+    };
+    let repo = options.repo.as_deref();
+    git::git_dir(repo)?;
+    let config = Config::resolve(options.overrides.clone(), &GitConfig::load_in(repo));
+    let preset = cli::preset(&config, repo)?;
+
+    let commit = git::output(
+        repo,
+        &["rev-parse", "--verify", &format!("{}^{{commit}}", revision)],
+    )
+    .map_err(|_| format!("{} is not a commit", revision))?
+    .trim()
+    .to_string();
+    let original = git::output(repo, &["log", "-1", "--format=%B", &commit])?;
+    let subject = original
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    let parents = git::output(repo, &["rev-list", "--parents", "-n", "1", &commit])?
+        .split_whitespace()
+        .count()
+        - 1;
+    // A merge is reverted against its first parent, like `git revert -m 1`.
+    let parent = match parents {
+        0 => git::empty_tree(repo)?,
+        _ => format!("{}^", commit),
+    };
+    let diff = git::output(repo, &["diff", &parent, &commit])?;
+
+    let mut guideline = REVERT_GUIDELINE.trim().to_string();
+    for instruction in &options.instructions {
+        guideline.push_str(&format!(
+            "\n\nAdditional instruction from the author for this commit, which takes precedence: {}",
+            instruction.trim()
+        ));
+    }
+    let mut generator = cli::build_generator(config, &preset)?
+        .prompt_builder(PromptBuilder::new().guideline(guideline));
+    if let Some(repo) = repo {
+        generator = generator.repo(repo);
+    }
+    let content = format!(
+        "### Reverted commit {}\n\n{}\n\n### Diff undone by the revert\n\n{}",
+        commit,
+        original.trim(),
+        prompt::fenced("diff", &diff)
+    );
+    let explanation = generator.generate_text("Revert", &content)?;
+
+    let header = match preset.style {
+        Style::Conventional => format!("revert: {}", subject),
+        _ => format!("Revert \"{}\"", subject),
+    };
+    let message = CommitMessage::parse(&format!(
+        "{}\n\nThis reverts commit {}.\n\n{}",
+        header,
+        commit,
+        explanation.trim()
+    ))
+    .to_string();
+    if options.commit {
+        return self::commit(repo, &commit, parents > 1, &message);
+    }
+    println!("{}", message);
+    Ok(())
+}
@@ -0,0 +1,136 @@
+//! `--review`: show what is about to be sent and ask before calling the model.
+
+use crate::cli::read_line;
+use gemini_commit_message::diff::{self, FileDiff, TrimReport};
+use gemini_commit_message::i18n::{Message, tr};
+use std::env;
+use std::io::{self, IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+struct Colors {
+    bold: &'static str,
+    green: &'static str,
+    red: &'static str,
+    reset: &'static str,
+}
+
+/// ANSI colors on a terminal unless `NO_COLOR` is set.
+fn colors() -> Colors {
+    if io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none() {
+        Colors {
+            bold: "\x1b[1m",
+            green: "\x1b[32m",
+            red: "\x1b[31m",
+            reset: "\x1b[0m",
+        }
+    } else {
+        Colors {
+            bold: "",
+            green: "",
+            red: "",
+            reset: "",
+        }
+    }
+}
+
+fn summary(files: &[FileDiff], prompt: &str, model: &str) -> String {
+    let c = colors();
+    let mut text = format!(
+        "{}Request to {}: {} bytes (~{} tokens), {} files{}\n\n",
+        c.bold,
+        model,
+        prompt.len(),
+        prompt.len() / 4,
+        files.len(),
+        c.reset
+    );
+    let width = files.iter().map(|f| f.path.len()).max().unwrap_or(0);
+    for file in files {
+        text.push_str(&format!(
+            "  {:width$}  {}+{:<5}{} {}-{:<5}{} {} hunk{}\n",
+            file.path,
+            c.green,
+            file.added,
+            c.reset,
+            c.red,
+            file.removed,
+            c.reset,
+            file.hunks,
+            if file.hunks == 1 { "" } else { "s" },
+            width = width
+        ));
+    }
+    text
+}
+
+/// Shows `text` through `$PAGER` (default `less -FRX`) on a terminal.
+fn page(text: &str) {
+    if io::stdout().is_terminal() {
+        let pager = env::var("PAGER").unwrap_or_else(|_| "less -FRX".to_string());
+        let mut words = pager.split_whitespace();
+        if let Some(program) = words.next()
+            && let Ok(mut child) = Command::new(program)
+                .args(words)
+                .stdin(Stdio::piped())
+                .spawn()
+        {
+            if let Some(mut stdin) = child.stdin.take() {
+                // The pager may quit before reading everything.
+                let _ = stdin.write_all(text.as_bytes());
+            }
+            let _ = child.wait();
+            return;
+        }
+    }
+    print!("{}", text);
+    if !text.ends_with('\n') {
+        println!();
+    }
+}
+
+/// Returns `Ok(())` once the user agrees to send `prompt`, and an error if
+/// they decline.
+pub fn confirm(diff: &str, prompt: &str, model: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let files = diff::split_files(diff);
+    page(&summary(&files, prompt, model));
+    loop {
+        let answer = read_line(&tr(Message::ConfirmSend, &[&model]))?;
+        match answer.to_lowercase().as_str() {
+            "y" | "yes" => return Ok(()),
+            "p" | "prompt" => page(prompt),
+            _ => return Err(tr(Message::Aborted, &[]).into()),
+        }
+    }
+}
+
+/// Tells on stderr that the diff is over the size limit: its largest files
+/// and whether it is summarized file by file (`chunked`) or shortened.
+pub fn over_limit(report: &TrimReport, chunked: bool) {
+    let size = |bytes: usize| diff::human_size(bytes as u64);
+    eprintln!(
+        "{}",
+        tr(
+            Message::DiffOverLimit,
+            &[&size(report.bytes), &size(report.max_bytes)]
+        )
+    );
+    for (path, bytes) in &report.largest {
+        eprintln!("  {:>9}  {}", size(*bytes), path);
+    }
+    if chunked {
+        eprintln!("{}", tr(Message::OverLimitChunked, &[]));
+        return;
+    }
+    let list = |paths: &[String]| match paths.is_empty() {
+        true => "-".to_string(),
+        false => paths.join(", "),
+    };
+    let cut: Vec<String> = report.cut.iter().cloned().collect();
+    eprintln!(
+        "{}",
+        tr(
+            Message::OverLimitTrimmed,
+            &[&list(&cut), &list(&report.omitted)]
+        )
+    );
+}
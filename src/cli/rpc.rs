@@ -0,0 +1,30 @@
+//! `--rpc`: one request and one response, for editor extensions that would
+//! rather spawn a process per message than manage the `serve` daemon.
+//!
+//! The request is a JSON object on stdin such as
+//! `{"repo": "/path", "staged": true, "lang": "ja"}` and the answer is a
+//! single `{"message": ...}` or `{"error": ...}` line on stdout. `lang`
+//! sets the language of the error texts.
+
+use crate::cli::{Options, serve};
+use gemini_commit_message::i18n::{self, Language};
+use serde_json::Value;
+use std::io::{self, Read};
+
+pub fn run(options: Options) -> Result<(), Box<dyn std::error::Error>> {
+    let mut text = String::new();
+    io::stdin().read_to_string(&mut text)?;
+    let mut request = text.trim().to_string();
+    if let Ok(Value::Object(mut fields)) = serde_json::from_str::<Value>(&request) {
+        if let Some(lang) = fields.get("lang").and_then(Value::as_str) {
+            i18n::set_language(Language::parse(lang));
+        }
+        if !fields.contains_key("repo") && !fields.contains_key("repo_path") {
+            let repo = options.repo.as_deref().and_then(|repo| repo.to_str());
+            fields.insert("repo".to_string(), Value::from(repo.unwrap_or(".")));
+        }
+        request = Value::Object(fields).to_string();
+    }
+    println!("{}", serve::respond(&options.overrides, &request));
+    Ok(())
+}
@@ -0,0 +1,123 @@
+//! `--interactive-select`: choose the files and hunks the model sees,
+//! leaving what is staged as it is.
+
+use crate::cli::read_line;
+use gemini_commit_message::diff::{self, FileDiff};
+use gemini_commit_message::i18n::{Message, tr};
+use gemini_commit_message::{Error, log};
+
+/// A hunk, or a whole file without hunks, that can be left out.
+struct Item {
+    file: usize,
+    hunk: Option<usize>,
+    label: String,
+}
+
+/// Characters of a hunk's `@@` line shown in the list.
+const MAX_LABEL: usize = 60;
+
+fn items(files: &[FileDiff]) -> Vec<Item> {
+    let mut items = Vec::new();
+    for (index, file) in files.iter().enumerate() {
+        let (_, hunks) = diff::hunks(file);
+        if hunks.is_empty() {
+            items.push(Item {
+                file: index,
+                hunk: None,
+                label: file.path.clone(),
+            });
+        }
+        for (hunk, text) in hunks.iter().enumerate() {
+            let count = |sign: char| text.lines().skip(1).filter(|l| l.starts_with(sign)).count();
+            let header: String = text
+                .lines()
+                .next()
+                .unwrap_or("")
+                .chars()
+                .take(MAX_LABEL)
+                .collect();
+            items.push(Item {
+                file: index,
+                hunk: Some(hunk),
+                label: format!("{} {} (+{} -{})", file.path, header, count('+'), count('-')),
+            });
+        }
+    }
+    items
+}
+
+/// Toggles the items named by `answer`: numbers and ranges such as `2 4-6`,
+/// `a` for all or `n` for none. Returns false if it cannot be understood.
+fn toggle(answer: &str, selected: &mut [bool]) -> bool {
+    match answer {
+        "a" | "all" => selected.fill(true),
+        "n" | "none" => selected.fill(false),
+        _ => {
+            let mut numbers = Vec::new();
+            for word in answer.split([' ', ',']).filter(|w| !w.is_empty()) {
+                let (start, end) = word.split_once('-').unwrap_or((word, word));
+                match (start.parse::<usize>(), end.parse::<usize>()) {
+                    (Ok(start), Ok(end)) if 1 <= start && start <= end && end <= selected.len() => {
+                        numbers.extend(start..=end)
+                    }
+                    _ => return false,
+                }
+            }
+            for number in numbers {
+                selected[number - 1] = !selected[number - 1];
+            }
+        }
+    }
+    true
+}
+
+/// Lists the files and hunks of `diff` with checkboxes and returns the
+/// diff of the ones still checked once the user is done.
+pub fn run(diff: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let files = diff::split_files(diff);
+    let items = items(&files);
+    let mut selected = vec![true; items.len()];
+    loop {
+        for (i, item) in items.iter().enumerate() {
+            let mark = if selected[i] { 'x' } else { ' ' };
+            eprintln!("[{}] {:>3} {}", mark, i + 1, item.label);
+        }
+        let answer = read_line(&tr(Message::SelectHunks, &[]))?;
+        if answer.is_empty() {
+            break;
+        }
+        if !toggle(&answer.to_lowercase(), &mut selected) {
+            eprintln!("{}", tr(Message::EnterNumber, &[&items.len()]));
+        }
+    }
+
+    let mut text = String::new();
+    for (index, file) in files.iter().enumerate() {
+        let (header, hunks) = diff::hunks(file);
+        let chosen: Vec<&Item> = items
+            .iter()
+            .zip(&selected)
+            .filter(|(item, selected)| item.file == index && **selected)
+            .map(|(item, _)| item)
+            .collect();
+        if chosen.is_empty() {
+            continue;
+        }
+        text.push_str(header);
+        for item in chosen {
+            if let Some(hunk) = item.hunk {
+                text.push_str(hunks[hunk]);
+            }
+        }
+    }
+    if text.trim().is_empty() {
+        return Err(Error::NothingToCommit.into());
+    }
+    let left_out = selected.iter().filter(|s| !**s).count();
+    log::info(&format!(
+        "left {} of {} hunks out of the prompt",
+        left_out,
+        items.len()
+    ));
+    Ok(text)
+}
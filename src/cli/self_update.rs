@@ -0,0 +1,79 @@
+//! `self-update`: replaces the running binary with the latest release.
+
+use crate::cli::Options;
+use gemini_commit_message::update::{self, CHECKSUMS};
+use gemini_commit_message::version::Version;
+use std::env;
+use std::fs;
+use std::path::Path;
+
+/// Writes `bytes` next to `exe` and moves them into its place. A running
+/// executable cannot be overwritten on Windows, but it can be renamed.
+fn replace(exe: &Path, bytes: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+    let new = exe.with_extension("new");
+    fs::write(&new, bytes).map_err(|e| format!("cannot write {}: {}", new.display(), e))?;
+    fs::set_permissions(&new, fs::metadata(exe)?.permissions())?;
+    if cfg!(windows) {
+        let old = exe.with_extension("old");
+        let _ = fs::remove_file(&old);
+        fs::rename(exe, &old)?;
+    }
+    fs::rename(&new, exe).map_err(|e| {
+        let _ = fs::remove_file(&new);
+        format!("cannot replace {}: {}", exe.display(), e)
+    })?;
+    Ok(())
+}
+
+pub fn run(options: Options) -> Result<(), Box<dyn std::error::Error>> {
+    if options.help {
+        println!("Usage: <program> self-update [--check]");
+        println!("  Downloads the latest release for this platform from GitHub, verifies it");
+        println!("  against the release's SHA256SUMS and replaces the running binary.");
+        println!("  With --check, only reports whether a newer version exists.");
+        println!("  GCM_UPDATE_REPO=owner/name picks another repository.");
+        return Ok(());
+    }
+    let repository = update::repository().ok_or("set GCM_UPDATE_REPO=owner/name")?;
+    let current = Version::parse(env!("CARGO_PKG_VERSION")).ok_or("unparsable own version")?;
+    let release = update::latest(&repository)?;
+    let latest = release
+        .version()
+        .ok_or_else(|| format!("the latest release {} is not a version", release.tag))?;
+    let newer =
+        (latest.major, latest.minor, latest.patch) > (current.major, current.minor, current.patch);
+    if !newer {
+        println!("{} is up to date", current);
+        return Ok(());
+    }
+    if options.check {
+        println!("{} is available (current: {})", release.tag, current);
+        return Ok(());
+    }
+
+    let name = update::asset_name();
+    let url = release
+        .asset_url(&name)
+        .ok_or_else(|| format!("{} has no binary for this platform ({})", release.tag, name))?;
+    let sums_url = release
+        .asset_url(CHECKSUMS)
+        .ok_or_else(|| format!("{} has no {}", release.tag, CHECKSUMS))?;
+    let sums = String::from_utf8(update::download(sums_url)?)?;
+    let expected = update::expected_checksum(&sums, &name)
+        .ok_or_else(|| format!("{} does not list {}", CHECKSUMS, name))?;
+    eprintln!("downloading {}", name);
+    let bytes = update::download(url)?;
+    let actual = update::checksum(&bytes);
+    if actual != expected {
+        return Err(format!(
+            "checksum mismatch for {}: expected {}, got {}",
+            name, expected, actual
+        )
+        .into());
+    }
+
+    let exe = env::current_exe()?;
+    replace(&exe, &bytes)?;
+    println!("updated {} to {}", current, release.tag);
+    Ok(())
+}
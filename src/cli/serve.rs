@@ -0,0 +1,139 @@
+//! A long-running daemon for editor integrations.
+//!
+//! Each connection sends newline-delimited JSON requests such as
+//! `{"repo_path": "/path/to/repo", "staged": true}` and receives one line per
+//! request: `{"message": "..."}` or `{"error": "..."}`. Keeping the process
+//! alive keeps the HTTP connection pool warm between requests.
+
+use crate::cli::{self, Options};
+use gemini_commit_message::config::Overrides;
+use serde_json::{Value, json};
+use std::env;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::thread;
+
+/// The `{"message": ...}` or `{"error": ...}` answer to one JSON request.
+pub fn respond(overrides: &Overrides, request: &str) -> Value {
+    match serde_json::from_str::<Value>(request) {
+        Ok(request) => match cli::generate_for_request(overrides, &request) {
+            Ok(message) => json!({ "message": message }),
+            Err(error) => json!({ "error": error }),
+        },
+        Err(e) => json!({ "error": format!("invalid request: {}", e) }),
+    }
+}
+
+fn handle_connection(overrides: Overrides, reader: impl Read, mut writer: impl Write) {
+    for line in BufReader::new(reader).lines() {
+        let Ok(line) = line else {
+            return;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = respond(&overrides, &line);
+        if writeln!(writer, "{}", response)
+            .and_then(|_| writer.flush())
+            .is_err()
+        {
+            return;
+        }
+    }
+}
+
+fn serve_tcp(overrides: Overrides, port: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let listener = TcpListener::bind(("127.0.0.1", port.parse::<u16>()?))?;
+    eprintln!("listening on {}", listener.local_addr()?);
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let overrides = overrides.clone();
+        thread::spawn(move || {
+            if let Ok(reader) = stream.try_clone() {
+                handle_connection(overrides, reader, stream);
+            }
+        });
+    }
+    Ok(())
+}
+
+fn default_socket_path() -> PathBuf {
+    match env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) => PathBuf::from(dir).join("gemini-commit-message.sock"),
+        None => env::temp_dir().join(format!(
+            "gemini-commit-message-{}.sock",
+            env::var("USER").unwrap_or_default()
+        )),
+    }
+}
+
+#[cfg(unix)]
+fn serve_unix(overrides: Overrides, path: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    use std::fs;
+    use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    if let Ok(metadata) = fs::symlink_metadata(&path) {
+        if !metadata.file_type().is_socket() {
+            return Err(format!("{} exists and is not a socket", path.display()).into());
+        }
+        // A socket nobody listens on is left over from a previous run.
+        if UnixStream::connect(&path).is_err() {
+            fs::remove_file(&path)?;
+        }
+    }
+    let listener = UnixListener::bind(&path)?;
+    // Only the owner may ask for messages about their repositories.
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+    eprintln!("listening on {}", path.display());
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let overrides = overrides.clone();
+        thread::spawn(move || {
+            if let Ok(reader) = stream.try_clone() {
+                handle_connection(overrides, reader, stream);
+            }
+        });
+    }
+    Ok(())
+}
+
+pub fn run(options: Options) -> Result<(), Box<dyn std::error::Error>> {
+    if options.help {
+        println!("Usage: <program> serve [--socket PATH | --port PORT] [OPTIONS]");
+        println!(
+            "  Listens on a Unix socket (default: {})",
+            default_socket_path().display()
+        );
+        println!("  or on 127.0.0.1:PORT and answers one JSON line per request. The port is bound");
+        println!("  to loopback only, but any local user can connect to it; on shared machines");
+        println!("  prefer the socket, which only its owner can use.");
+        println!(r#"    {{"repo_path": "/path/to/repo", "staged": true}}"#);
+        println!(r#"    -> {{"message": "..."}} or {{"error": "..."}}"#);
+        return Ok(());
+    }
+
+    if let Some(port) = &options.port {
+        return serve_tcp(options.overrides, port);
+    }
+    let path = options
+        .socket
+        .map(PathBuf::from)
+        .unwrap_or_else(default_socket_path);
+    #[cfg(unix)]
+    {
+        serve_unix(options.overrides, path)
+    }
+    #[cfg(not(unix))]
+    {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            format!(
+                "Unix sockets are not available here ({}), use --port instead",
+                path.display()
+            ),
+        )
+        .into())
+    }
+}
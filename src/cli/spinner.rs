@@ -0,0 +1,72 @@
+//! A one-line progress indicator on stderr while waiting for git or the model.
+
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+const FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+const INTERVAL: Duration = Duration::from_millis(100);
+
+/// Redraws `<frame> <phase> <elapsed>` until finished or dropped. Does nothing
+/// unless stderr is a terminal, so pipes and logs stay clean.
+pub struct Spinner {
+    phase: Arc<Mutex<String>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Spinner {
+    pub fn start(enabled: bool) -> Self {
+        let phase = Arc::new(Mutex::new(String::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = (enabled && io::stderr().is_terminal()).then(|| {
+            let phase = Arc::clone(&phase);
+            let stop = Arc::clone(&stop);
+            thread::spawn(move || {
+                let started = Instant::now();
+                let mut frame = 0;
+                while !stop.load(Ordering::Relaxed) {
+                    let text = phase.lock().map(|p| p.clone()).unwrap_or_default();
+                    eprint!(
+                        "\r\x1b[2K{} {} {:.1}s",
+                        FRAMES[frame % FRAMES.len()],
+                        text,
+                        started.elapsed().as_secs_f32()
+                    );
+                    let _ = io::stderr().flush();
+                    frame += 1;
+                    thread::sleep(INTERVAL);
+                }
+                eprint!("\r\x1b[2K");
+                let _ = io::stderr().flush();
+            })
+        });
+        Self {
+            phase,
+            stop,
+            thread,
+        }
+    }
+
+    pub fn phase(&self, text: impl Into<String>) {
+        if let Ok(mut phase) = self.phase.lock() {
+            *phase = text.into();
+        }
+    }
+
+    /// Clears the line; call before printing anything else.
+    pub fn finish(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for Spinner {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
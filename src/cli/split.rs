@@ -0,0 +1,149 @@
+//! `--suggest-split`: propose separate commits for a staged change that mixes concerns.
+//! `--split`: stage and commit the proposed groups one by one.
+
+use crate::cli::read_line;
+use gemini_commit_message::i18n::{Message, tr};
+use gemini_commit_message::prompt::{
+    self, COMMIT_MESSAGE_GUIDELINE, PromptBuilder, SPLIT_GUIDELINE,
+};
+use gemini_commit_message::split::{self, CommitGroup};
+use gemini_commit_message::{Generator, git};
+use std::path::Path;
+
+/// Asks the model how `diff` should be split.
+pub fn suggest(
+    generator: Generator,
+    diff: &str,
+) -> Result<Vec<CommitGroup>, Box<dyn std::error::Error>> {
+    let generator = generator.prompt_builder(
+        PromptBuilder::new()
+            .guideline(format!("{}\n{}", COMMIT_MESSAGE_GUIDELINE, SPLIT_GUIDELINE)),
+    );
+    let text = generator.generate_text("Git Diff", &prompt::fenced("diff", diff))?;
+    split::parse_groups(&text)
+}
+
+/// Paths of the staged files, to point out any the model left out.
+pub fn staged_files(repo: Option<&Path>) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let base = git::head_tree(repo)?;
+    let names = git::output(repo, &["diff", "--cached", "--name-only", &base])?;
+    Ok(names.lines().map(str::to_string).collect())
+}
+
+pub fn print_groups(groups: &[CommitGroup], files: Option<&[String]>) {
+    for (i, group) in groups.iter().enumerate() {
+        println!("=== Commit {} of {} ===", i + 1, groups.len());
+        for file in &group.files {
+            println!("  {}", file);
+        }
+        println!();
+        println!("{}", group.message);
+        println!();
+    }
+    if let Some(files) = files {
+        let unassigned = split::unassigned(groups, files);
+        if !unassigned.is_empty() {
+            println!("=== Not assigned to any commit ===");
+            for file in unassigned {
+                println!("  {}", file);
+            }
+        }
+    }
+}
+
+/// Stages the staged version of `files` (kept in `staged_tree`) without
+/// touching the working tree.
+fn restage(
+    repo: Option<&Path>,
+    staged_tree: &str,
+    files: &[String],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let source = format!("--source={}", staged_tree);
+    let mut args = vec!["restore", "--staged", &source, "--"];
+    args.extend(files.iter().map(String::as_str));
+    git::output(repo, &args)?;
+    Ok(())
+}
+
+fn commit_groups(
+    repo: Option<&Path>,
+    groups: &[CommitGroup],
+    staged_tree: &str,
+    pending: &mut Vec<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for (i, group) in groups.iter().enumerate() {
+        let files: Vec<String> = group
+            .files
+            .iter()
+            .filter(|file| pending.contains(file))
+            .cloned()
+            .collect();
+        if files.is_empty() {
+            continue;
+        }
+        println!("=== Commit {} of {} ===", i + 1, groups.len());
+        for file in &files {
+            println!("  {}", file);
+        }
+        println!();
+        println!("{}", group.message);
+        println!();
+        let answer = read_line(&tr(Message::CommitGroup, &[]))?;
+        let edit = match answer.to_lowercase().as_str() {
+            "" | "y" | "yes" => false,
+            "e" | "edit" => true,
+            "q" | "quit" => break,
+            _ => continue,
+        };
+
+        restage(repo, staged_tree, &files)?;
+        let message = group.message.to_string();
+        let committed = if edit || git::commit_signing(repo).is_some() {
+            // Inherit the terminal so that the editor can run and a signing
+            // key can ask for its passphrase.
+            git::command(repo)
+                .args(["commit", "-q", "-e", "-m", &message])
+                .status()?
+                .success()
+        } else {
+            git::output(repo, &["commit", "-q", "-m", &message]).is_ok()
+        };
+        if committed {
+            pending.retain(|file| !files.contains(file));
+        } else {
+            git::output(repo, &["read-tree", &git::head_tree(repo)?])?;
+            eprintln!("{}", tr(Message::CommitGroupFailed, &[]));
+        }
+    }
+    Ok(())
+}
+
+/// Splits the staged change into the proposed commits, asking before each
+/// one. Whatever is not committed is staged again at the end.
+pub fn interactive(
+    repo: Option<&Path>,
+    groups: &[CommitGroup],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let files = staged_files(repo)?;
+    let unknown: Vec<&String> = groups
+        .iter()
+        .flat_map(|group| &group.files)
+        .filter(|file| !files.contains(file))
+        .collect();
+    for file in unknown {
+        eprintln!("{}", tr(Message::NotStaged, &[file]));
+    }
+
+    let staged_tree = git::output(repo, &["write-tree"])?.trim().to_string();
+    git::output(repo, &["read-tree", &git::head_tree(repo)?])?;
+    let mut pending = files;
+    let result = commit_groups(repo, groups, &staged_tree, &mut pending);
+    if !pending.is_empty() {
+        restage(repo, &staged_tree, &pending)?;
+        println!("{}", tr(Message::LeftStaged, &[]));
+        for file in &pending {
+            println!("  {}", file);
+        }
+    }
+    result
+}
@@ -0,0 +1,59 @@
+//! `squash [<from>..<to>]`: one commit message for a whole series, in the
+//! format of the configured preset.
+
+use crate::cli::{self, Options, pr};
+use gemini_commit_message::config::{Config, GitConfig};
+use gemini_commit_message::prompt::{self, SQUASH_GUIDELINE};
+use gemini_commit_message::{CommitMessage, commits, git};
+use std::fs;
+
+pub fn run(options: Options) -> Result<(), Box<dyn std::error::Error>> {
+    if options.help {
+        println!("Usage: <program> squash [<FROM>..<TO>] [--write] [-C PATH] [OPTIONS]");
+        println!("  Writes one message covering the net change of the commits in the range");
+        println!("  (default: the current branch since origin/HEAD, or origin/main).");
+        println!(
+            "  After `git merge --squash feature`, run `<program> squash HEAD..feature --write`"
+        );
+        println!("  to put the message into SQUASH_MSG for the following `git commit`.");
+        return Ok(());
+    }
+    let repo = options.repo.as_deref();
+    git::git_dir(repo)?;
+    let config = Config::resolve(options.overrides.clone(), &GitConfig::load_in(repo));
+    let preset = cli::adjust_preset(cli::preset(&config, repo)?, &options)?;
+    let generator = cli::build_generator(config, &preset)?
+        .prompt_builder(preset.prompt_builder().instructions(SQUASH_GUIDELINE));
+    let generator = match repo {
+        Some(repo) => generator.repo(repo),
+        None => generator,
+    };
+    let range = match options.positional.first() {
+        Some(range) => range.clone(),
+        None => pr::default_base(repo),
+    };
+    let (from, to) = commits::split_range(&range);
+
+    let commits = commits::log(repo, &format!("{}..{}", from, to))?;
+    if commits.is_empty() {
+        println!("No commits in {}..{}", from, to);
+        return Ok(());
+    }
+    let diff = git::diff_range(repo, &format!("{}...{}", from, to))?;
+    let content = format!(
+        "### Commits\n\n{}\n\n### Diff\n\n{}",
+        commits::to_prompt_text(&commits),
+        prompt::fenced("diff", &diff)
+    );
+    let mut message = CommitMessage::parse(&generator.generate_text("Commit Series", &content)?);
+    preset.apply(&mut message);
+
+    if options.write {
+        let path = git::git_path(repo, "SQUASH_MSG")?;
+        fs::write(&path, format!("{}\n", message))?;
+        eprintln!("wrote {}", path.display());
+    } else {
+        println!("{}", message);
+    }
+    Ok(())
+}
@@ -0,0 +1,26 @@
+//! `stash`: `git stash push` with a generated summary instead of "WIP on main".
+
+use crate::cli::{self, Options};
+use gemini_commit_message::prompt::{PromptBuilder, STASH_GUIDELINE};
+use gemini_commit_message::{DiffSource, git};
+
+pub fn run(options: Options) -> Result<(), Box<dyn std::error::Error>> {
+    if options.help {
+        println!("Usage: <program> stash [-C PATH] [OPTIONS]");
+        println!("  Summarizes the uncommitted changes and runs git stash push -m <summary>.");
+        return Ok(());
+    }
+
+    let generator = cli::setup(&options)?
+        .diff_source(DiffSource::WorkingTree)
+        .prompt_builder(PromptBuilder::new().guideline(STASH_GUIDELINE));
+    let Some(message) = generator.generate()? else {
+        println!("No local changes to stash");
+        return Ok(());
+    };
+
+    let summary = message.header;
+    git::output(options.repo.as_deref(), &["stash", "push", "-m", &summary])?;
+    println!("{}", summary);
+    Ok(())
+}
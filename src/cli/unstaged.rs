@@ -0,0 +1,85 @@
+//! What to do when nothing is staged but tracked files have changes, as
+//! for those used to `git commit -am`: ask, stage them with `git add -u`,
+//! describe them without staging, or report that there is nothing to commit.
+
+use crate::cli::read_line;
+use gemini_commit_message::config::Config;
+use gemini_commit_message::i18n::{Message, tr};
+use gemini_commit_message::{DiffSource, Error, diff, git};
+use std::io::{self, IsTerminal};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Mode {
+    /// Ask on a terminal; elsewhere the same as `Never`.
+    #[default]
+    Ask,
+    Stage,
+    Describe,
+    Never,
+}
+
+impl Mode {
+    /// The mode set as `gemini.unstaged` (or `GEMINI_UNSTAGED`).
+    pub fn from_config(config: &Config) -> Result<Self, Error> {
+        let Some(setting) = &config.unstaged else {
+            return Ok(Mode::Ask);
+        };
+        match setting.value.trim().to_lowercase().as_str() {
+            "ask" => Ok(Mode::Ask),
+            "stage" => Ok(Mode::Stage),
+            "describe" => Ok(Mode::Describe),
+            "never" => Ok(Mode::Never),
+            _ => Err(Error::Config(tr(
+                Message::InvalidUnstaged,
+                &[&setting.value, &setting.source],
+            ))),
+        }
+    }
+}
+
+fn ask(files: usize, commit: bool) -> Result<Mode, Box<dyn std::error::Error>> {
+    if commit {
+        let answer = read_line(&tr(Message::UnstagedCommitOffer, &[&files]))?;
+        return Ok(match answer.to_lowercase().as_str() {
+            "y" | "yes" => Mode::Stage,
+            _ => Mode::Never,
+        });
+    }
+    let answer = read_line(&tr(Message::UnstagedOffer, &[&files]))?;
+    Ok(match answer.to_lowercase().as_str() {
+        "s" | "stage" => Mode::Stage,
+        "d" | "describe" => Mode::Describe,
+        _ => Mode::Never,
+    })
+}
+
+/// The diff to describe in place of the empty staged one, or `None` when
+/// there is nothing to commit after all. Committing needs the changes
+/// staged, so `commit` turns `Describe` into `Stage`.
+pub fn fallback(
+    repo: Option<&Path>,
+    mode: Mode,
+    commit: bool,
+) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let unstaged = DiffSource::Unstaged.read(repo)?;
+    if unstaged.trim().is_empty() {
+        return Ok(None);
+    }
+    let files = diff::split_files(&unstaged).len();
+    let mode = match mode {
+        Mode::Ask if io::stdin().is_terminal() => ask(files, commit)?,
+        Mode::Ask => Mode::Never,
+        Mode::Describe if commit => Mode::Stage,
+        mode => mode,
+    };
+    match mode {
+        Mode::Stage => {
+            git::output(repo, &["add", "-u"])?;
+            eprintln!("{}", tr(Message::StagedTracked, &[&files]));
+            DiffSource::Staged.read(repo).map(Some)
+        }
+        Mode::Describe => Ok(Some(unstaged)),
+        Mode::Ask | Mode::Never => Ok(None),
+    }
+}
@@ -0,0 +1,75 @@
+//! `--watch`: regenerate the suggestion whenever the staged changes move.
+
+use gemini_commit_message::{DiffSource, Generator, git};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+fn modified(path: &PathBuf) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Polls the index and calls `on_message` with a fresh message every time
+/// the staged diff changes. Runs until interrupted.
+pub fn run(
+    generator: &Generator,
+    repo: Option<&Path>,
+    on_message: impl Fn(&str),
+) -> Result<(), Box<dyn std::error::Error>> {
+    let index = git::git_path(repo, "index")?;
+    let mut last_modified = None;
+    let mut last_diff: Option<String> = None;
+    eprintln!("watching {} (Ctrl-C to stop)", index.display());
+
+    loop {
+        let current = modified(&index);
+        if last_diff.is_none() || current != last_modified {
+            last_modified = current;
+            // The index is also rewritten by `git status` and friends, so only
+            // a changed diff triggers a new request.
+            let diff = match DiffSource::Staged.read(repo) {
+                Ok(diff) => diff,
+                // E.g. `index.lock` held by a concurrent `git add`; read
+                // again on the next poll.
+                Err(e) => {
+                    eprintln!("reading the staged changes failed: {}", e);
+                    last_modified = None;
+                    thread::sleep(POLL_INTERVAL);
+                    continue;
+                }
+            };
+            if last_diff.as_ref() != Some(&diff) {
+                if diff.is_empty() {
+                    eprintln!("Nothing staged, waiting for changes...");
+                } else {
+                    match generator.generate_for_diff(&diff) {
+                        Ok(message) => {
+                            eprintln!("--- {} ---", clock_time());
+                            on_message(&message.to_string());
+                        }
+                        Err(e) => eprintln!("generation failed: {}", e),
+                    }
+                }
+                last_diff = Some(diff);
+            }
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// `HH:MM:SS` (UTC) of the current time, enough to tell refreshes apart.
+fn clock_time() -> String {
+    let secs = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    format!(
+        "{:02}:{:02}:{:02} UTC",
+        secs / 3600 % 24,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
@@ -0,0 +1,69 @@
+use crate::git;
+use crate::message::CommitMessage;
+use std::path::Path;
+
+/// A commit read from `git log`.
+#[derive(Debug, Clone)]
+pub struct Commit {
+    pub id: String,
+    pub message: CommitMessage,
+}
+
+impl Commit {
+    pub fn short_id(&self) -> &str {
+        &self.id[..self.id.len().min(7)]
+    }
+}
+
+/// Commits reachable from the `range` (`a..b`, a single revision, ...), newest first.
+/// In a shallow clone that lacks the start of `range`, every commit up to
+/// its end is listed instead, with a warning.
+pub fn log(repo: Option<&Path>, range: &str) -> Result<Vec<Commit>, Box<dyn std::error::Error>> {
+    // Unit and record separators cannot appear in commit messages.
+    let format = "--format=%H%x1f%B%x1e";
+    let text = match git::output(repo, &["log", format, range, "--"]) {
+        Ok(text) => text,
+        Err(e) if git::shallow_boundary(repo).is_none() => return Err(e),
+        Err(_) => {
+            let (_, to) = split_range(range);
+            eprintln!(
+                "warning: {} is incomplete in this shallow clone; listing every commit up to {}",
+                range, to
+            );
+            git::output(repo, &["log", format, to, "--"])?
+        }
+    };
+    Ok(text
+        .split('\x1e')
+        .filter_map(|record| {
+            let (id, message) = record.trim_start_matches('\n').split_once('\x1f')?;
+            Some(Commit {
+                id: id.to_string(),
+                message: CommitMessage::parse(message),
+            })
+        })
+        .collect())
+}
+
+/// Renders commits as a plain list for a prompt: short id and full message.
+pub fn to_prompt_text(commits: &[Commit]) -> String {
+    commits
+        .iter()
+        .map(|commit| {
+            format!(
+                "- {} {}",
+                commit.short_id(),
+                commit.message.to_string().replace('\n', "\n  ")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Splits `from..to` into its ends; a single revision means `rev..HEAD`.
+pub fn split_range(range: &str) -> (&str, &str) {
+    match range.split_once("..") {
+        Some((from, to)) => (from, if to.is_empty() { "HEAD" } else { to }),
+        None => (range, "HEAD"),
+    }
+}
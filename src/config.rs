@@ -0,0 +1,392 @@
+use crate::auth;
+use crate::git;
+use std::env;
+use std::fmt;
+use std::path::Path;
+
+pub use crate::provider::gemini::DEFAULT_MODEL;
+pub const DEFAULT_PROVIDER: &str = "gemini";
+
+/// One `key=value` line from `git config --list`, with the scope it came from
+/// (`system`, `global`, `local`, `worktree` or `command`) and its origin file.
+#[derive(Debug, Clone)]
+pub struct ConfigEntry {
+    pub scope: String,
+    pub origin: String,
+    pub key: String,
+    pub value: String,
+}
+
+/// Snapshot of every git config value visible from the current directory.
+///
+/// Git lists entries from the least to the most specific scope, so the last
+/// match for a key is the effective one, exactly as `git config --get` does.
+#[derive(Debug, Default)]
+pub struct GitConfig {
+    entries: Vec<ConfigEntry>,
+}
+
+/// Section and variable names are case-insensitive in git, subsections are not.
+fn normalize_key(key: &str) -> String {
+    match (key.find('.'), key.rfind('.')) {
+        (Some(first), Some(last)) if first != last => format!(
+            "{}{}{}",
+            key[..first].to_lowercase(),
+            &key[first..last],
+            key[last..].to_lowercase()
+        ),
+        _ => key.to_lowercase(),
+    }
+}
+
+impl GitConfig {
+    /// Reads the config once; outside a repository only system and global
+    /// values are returned, and a missing `git` yields an empty snapshot.
+    pub fn load() -> Self {
+        Self::load_in(None)
+    }
+
+    /// Like [`GitConfig::load`], but as seen from the repository at `repo`.
+    pub fn load_in(repo: Option<&Path>) -> Self {
+        let output = match git::command(repo)
+            .args(["config", "-z", "--list", "--show-scope", "--show-origin"])
+            .output()
+        {
+            Ok(output) if output.status.success() => output,
+            _ => return Self::default(),
+        };
+
+        let text = String::from_utf8_lossy(&output.stdout);
+        let mut fields = text.split('\0');
+        let mut entries = Vec::new();
+        while let (Some(scope), Some(origin), Some(pair)) =
+            (fields.next(), fields.next(), fields.next())
+        {
+            let (key, value) = pair.split_once('\n').unwrap_or((pair, "true"));
+            entries.push(ConfigEntry {
+                scope: scope.to_string(),
+                origin: origin.strip_prefix("file:").unwrap_or(origin).to_string(),
+                key: normalize_key(key),
+                value: value.to_string(),
+            });
+        }
+        Self { entries }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&ConfigEntry> {
+        let key = normalize_key(key);
+        self.entries.iter().rev().find(|entry| entry.key == key)
+    }
+
+    /// Every value of a multi-valued key, least specific scope first.
+    pub fn get_all(&self, key: &str) -> Vec<&ConfigEntry> {
+        let key = normalize_key(key);
+        self.entries
+            .iter()
+            .filter(|entry| entry.key == key)
+            .collect()
+    }
+
+    /// Looks up `gemini.<profile>.<name>` and falls back to `gemini.<name>`.
+    pub fn get_gemini(&self, profile: Option<&str>, name: &str) -> Option<&ConfigEntry> {
+        profile
+            .and_then(|profile| self.get(&format!("gemini.{}.{}", profile, name)))
+            .or_else(|| self.get(&format!("gemini.{}", name)))
+    }
+
+    /// Names of the profiles defined as `[gemini "<name>"]` sections.
+    pub fn profiles(&self) -> Vec<String> {
+        let mut profiles: Vec<String> = self
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                let rest = entry.key.strip_prefix("gemini.")?;
+                let (profile, _) = rest.rsplit_once('.')?;
+                Some(profile.to_string())
+            })
+            .collect();
+        profiles.sort();
+        profiles.dedup();
+        profiles
+    }
+}
+
+/// Where an effective setting came from, highest precedence first.
+#[derive(Debug, Clone)]
+pub enum Source {
+    Flag(&'static str),
+    Env(&'static str),
+    RepoConfig(String),
+    UserConfig(String),
+    Keyring,
+    Default,
+}
+
+impl Source {
+    fn from_entry(entry: &ConfigEntry) -> Self {
+        let location = format!("{} in {}", entry.key, entry.origin);
+        match entry.scope.as_str() {
+            "local" | "worktree" | "command" => Source::RepoConfig(location),
+            _ => Source::UserConfig(location),
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Flag(flag) => write!(f, "command line {}", flag),
+            Source::Env(name) => write!(f, "environment variable {}", name),
+            Source::RepoConfig(location) => write!(f, "repo config ({})", location),
+            Source::UserConfig(location) => write!(f, "user config ({})", location),
+            Source::Keyring => write!(f, "OS keyring"),
+            Source::Default => write!(f, "default"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Setting {
+    pub value: String,
+    pub source: Source,
+}
+
+impl Setting {
+    fn new(value: impl Into<String>, source: Source) -> Self {
+        Self {
+            value: value.into(),
+            source,
+        }
+    }
+}
+
+/// Values given on the command line; they win over every other source.
+#[derive(Debug, Clone, Default)]
+pub struct Overrides {
+    pub api_key: Option<String>,
+    pub profile: Option<String>,
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub style: Option<String>,
+    pub preset: Option<String>,
+}
+
+/// The effective configuration.
+///
+/// Every value is resolved with the same precedence: command line flag,
+/// environment variable, repo config (`git config --local`), user config
+/// (`git config --global`/`--system`), OS keyring, and finally the default.
+/// A `.env` file in the working directory only feeds the environment variables.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub profile: Option<Setting>,
+    pub provider: Setting,
+    pub model: Setting,
+    pub style: Setting,
+    pub preset: Option<Setting>,
+    pub thinking_budget: Option<Setting>,
+    pub concurrency: Option<Setting>,
+    pub recent_commits: Option<Setting>,
+    pub learn: Option<Setting>,
+    pub images: Option<Setting>,
+    pub minify: Option<Setting>,
+    /// A file whose text replaces the preset's guideline.
+    pub guideline_file: Option<Setting>,
+    /// The size of the diff sent at most, e.g. `400000` or `1M`.
+    pub max_diff_bytes: Option<Setting>,
+    /// Client-side limits on the requests and tokens sent per minute.
+    pub requests_per_minute: Option<Setting>,
+    pub tokens_per_minute: Option<Setting>,
+    /// The summary's mood, capitalization and trailing period.
+    pub mood: Option<Setting>,
+    pub capitalize: Option<Setting>,
+    pub period: Option<Setting>,
+    pub api_base: Option<Setting>,
+    /// What to do when nothing is staged but tracked files changed.
+    pub unstaged: Option<Setting>,
+    pub repo_context: Option<Setting>,
+    pub api_key: Option<Setting>,
+    /// `Token: value` trailers appended to every message, already expanded.
+    pub trailers: Vec<Setting>,
+}
+
+/// Replaces `${NAME}` with the environment variable and `${git:key}` with
+/// the git config value; `None` if one of them is unset or empty.
+fn expand(template: &str, git_config: &GitConfig) -> Option<String> {
+    let mut text = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("${") {
+        let end = start + rest[start..].find('}')?;
+        let name = &rest[start + 2..end];
+        let value = match name.strip_prefix("git:") {
+            Some(key) => git_config.get(key).map(|entry| entry.value.clone()),
+            None => env::var(name).ok(),
+        }
+        .filter(|value| !value.trim().is_empty())?;
+        text.push_str(&rest[..start]);
+        text.push_str(value.trim());
+        rest = &rest[end + 1..];
+    }
+    text.push_str(rest);
+    Some(text)
+}
+
+fn from_env(names: &[&'static str]) -> Option<Setting> {
+    names.iter().find_map(|name| match env::var(name) {
+        Ok(value) if !value.is_empty() => Some(Setting::new(value, Source::Env(name))),
+        _ => None,
+    })
+}
+
+fn from_git(entry: Option<&ConfigEntry>) -> Option<Setting> {
+    entry.map(|entry| Setting::new(entry.value.clone(), Source::from_entry(entry)))
+}
+
+impl Config {
+    pub fn resolve(overrides: Overrides, git_config: &GitConfig) -> Self {
+        let profile = overrides
+            .profile
+            .map(|value| Setting::new(value, Source::Flag("--profile")))
+            .or_else(|| from_env(&["GEMINI_PROFILE"]))
+            .or_else(|| from_git(git_config.get("gemini.profile")));
+        let profile_name = profile.as_ref().map(|setting| setting.value.as_str());
+
+        let provider = overrides
+            .provider
+            .map(|value| Setting::new(value, Source::Flag("--provider")))
+            .or_else(|| from_env(&["GEMINI_PROVIDER"]))
+            .or_else(|| {
+                // `GCM_MOCK=1` is a shortcut for demos and tests.
+                matches!(env::var("GCM_MOCK").as_deref(), Ok("1" | "true"))
+                    .then(|| Setting::new("mock", Source::Env("GCM_MOCK")))
+            })
+            .or_else(|| from_git(git_config.get_gemini(profile_name, "provider")))
+            .unwrap_or_else(|| Setting::new(DEFAULT_PROVIDER, Source::Default));
+
+        let model = overrides
+            .model
+            .map(|value| Setting::new(value, Source::Flag("--model")))
+            .or_else(|| from_env(&["GEMINI_MODEL"]))
+            .or_else(|| from_git(git_config.get_gemini(profile_name, "model")))
+            .unwrap_or_else(|| Setting::new(DEFAULT_MODEL, Source::Default));
+
+        let style = overrides
+            .style
+            .map(|value| Setting::new(value, Source::Flag("--style")))
+            .or_else(|| from_env(&["GEMINI_STYLE"]))
+            .or_else(|| from_git(git_config.get_gemini(profile_name, "style")))
+            .unwrap_or_else(|| Setting::new("conventional", Source::Default));
+
+        let preset = overrides
+            .preset
+            .map(|value| Setting::new(value, Source::Flag("--preset")))
+            .or_else(|| from_env(&["GEMINI_PRESET"]))
+            .or_else(|| from_git(git_config.get_gemini(profile_name, "preset")));
+
+        let thinking_budget = from_env(&["GEMINI_THINKING_BUDGET"])
+            .or_else(|| from_git(git_config.get_gemini(profile_name, "thinkingBudget")));
+        let concurrency = from_env(&["GEMINI_CONCURRENCY"])
+            .or_else(|| from_git(git_config.get_gemini(profile_name, "concurrency")));
+        let recent_commits = from_env(&["GEMINI_RECENT_COMMITS"])
+            .or_else(|| from_git(git_config.get_gemini(profile_name, "recentCommits")));
+        let learn = from_env(&["GEMINI_LEARN"])
+            .or_else(|| from_git(git_config.get_gemini(profile_name, "learn")));
+        let images = from_env(&["GEMINI_IMAGES"])
+            .or_else(|| from_git(git_config.get_gemini(profile_name, "images")));
+        let minify = from_env(&["GEMINI_MINIFY"])
+            .or_else(|| from_git(git_config.get_gemini(profile_name, "minify")));
+        let guideline_file = from_env(&["GEMINI_GUIDELINE_FILE"])
+            .or_else(|| from_git(git_config.get_gemini(profile_name, "guidelineFile")));
+        let max_diff_bytes = from_env(&["GEMINI_MAX_DIFF_BYTES"])
+            .or_else(|| from_git(git_config.get_gemini(profile_name, "maxDiffBytes")));
+        let requests_per_minute = from_env(&["GEMINI_REQUESTS_PER_MINUTE"])
+            .or_else(|| from_git(git_config.get_gemini(profile_name, "requestsPerMinute")));
+        let tokens_per_minute = from_env(&["GEMINI_TOKENS_PER_MINUTE"])
+            .or_else(|| from_git(git_config.get_gemini(profile_name, "tokensPerMinute")));
+        let mood = from_env(&["GEMINI_MOOD"])
+            .or_else(|| from_git(git_config.get_gemini(profile_name, "mood")));
+        let capitalize = from_env(&["GEMINI_CAPITALIZE"])
+            .or_else(|| from_git(git_config.get_gemini(profile_name, "capitalize")));
+        let period = from_env(&["GEMINI_PERIOD"])
+            .or_else(|| from_git(git_config.get_gemini(profile_name, "period")));
+        let api_base = from_env(&["GEMINI_API_BASE"])
+            .or_else(|| from_git(git_config.get_gemini(profile_name, "apiBase")));
+        let unstaged = from_env(&["GEMINI_UNSTAGED"])
+            .or_else(|| from_git(git_config.get_gemini(profile_name, "unstaged")));
+        let repo_context = from_env(&["GEMINI_REPO_CONTEXT"])
+            .or_else(|| from_git(git_config.get_gemini(profile_name, "repoContext")));
+
+        // A profile only ever uses its own stored credentials, never the default ones.
+        let stored_key = match profile_name {
+            Some(name) => git_config.get(&format!("gemini.{}.apikey", name)),
+            None => git_config.get("gemini.apikey"),
+        };
+        let api_key = overrides
+            .api_key
+            .map(|value| Setting::new(value, Source::Flag("--api-key")))
+            .or_else(|| from_env(&["GEMINI_API_KEY", "GOOGLE_API_KEY"]))
+            .or_else(|| from_git(stored_key))
+            .or_else(|| {
+                auth::load_api_key(profile_name).map(|value| Setting::new(value, Source::Keyring))
+            });
+
+        // Trailers whose values are unavailable, e.g. an unset variable, are left out.
+        let mut trailer_entries = git_config.get_all("gemini.trailer");
+        if let Some(name) = profile_name {
+            trailer_entries.extend(git_config.get_all(&format!("gemini.{}.trailer", name)));
+        }
+        let trailers = trailer_entries
+            .into_iter()
+            .filter_map(|entry| {
+                let value = expand(&entry.value, git_config)?;
+                Some(Setting::new(value, Source::from_entry(entry)))
+            })
+            .collect();
+
+        Self {
+            profile,
+            provider,
+            model,
+            style,
+            preset,
+            thinking_budget,
+            concurrency,
+            recent_commits,
+            learn,
+            images,
+            minify,
+            guideline_file,
+            max_diff_bytes,
+            requests_per_minute,
+            tokens_per_minute,
+            mood,
+            capitalize,
+            period,
+            unstaged,
+            api_base,
+            repo_context,
+            api_key,
+            trailers,
+        }
+    }
+}
+
+/// A git boolean: `true`, `yes`, `on` or `1`, and their opposites.
+pub fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Some(true),
+        "false" | "no" | "off" | "0" | "" => Some(false),
+        _ => None,
+    }
+}
+
+/// Keeps just enough of a secret to tell keys apart.
+pub fn mask_secret(secret: &str) -> String {
+    let chars: Vec<char> = secret.chars().collect();
+    if chars.len() <= 8 {
+        return "*".repeat(chars.len());
+    }
+    let head: String = chars[..4].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}…{}", head, tail)
+}
@@ -0,0 +1,579 @@
+use crate::{encoding, git};
+use std::io::{self, Read};
+use std::path::Path;
+
+/// Where the changes to describe are read from.
+#[derive(Debug, Clone, Default)]
+pub enum DiffSource {
+    /// `git diff --cached`: what the next commit will contain, including the
+    /// very first commit of a repository.
+    #[default]
+    Staged,
+    /// `git diff`: working tree changes that are not staged yet.
+    Unstaged,
+    /// `git diff HEAD`: staged and unstaged changes together, like `git stash` sees them.
+    WorkingTree,
+    /// A diff piped in on standard input; no repository is needed.
+    Stdin,
+    /// A diff that was produced elsewhere.
+    Text(String),
+}
+
+impl DiffSource {
+    /// Reads the diff of the repository at `repo`, or of the current directory.
+    pub fn read(&self, repo: Option<&Path>) -> Result<String, Box<dyn std::error::Error>> {
+        match self {
+            DiffSource::Staged => {
+                let base = git::head_tree(repo)?;
+                git::output(repo, &["diff", "--cached", &base])
+            }
+            DiffSource::Unstaged => git::output(repo, &["diff"]),
+            DiffSource::WorkingTree => {
+                let base = git::head_tree(repo)?;
+                git::output(repo, &["diff", &base])
+            }
+            DiffSource::Stdin => {
+                let mut bytes = Vec::new();
+                io::stdin().read_to_end(&mut bytes)?;
+                Ok(encoding::to_string(bytes))
+            }
+            DiffSource::Text(text) => Ok(text.clone()),
+        }
+    }
+}
+
+/// One file's part of a unified diff.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileDiff {
+    /// The path after `b/` in the header, i.e. the new path of a rename.
+    pub path: String,
+    pub hunks: usize,
+    pub added: usize,
+    pub removed: usize,
+    /// The `diff --git` header and everything up to the next file.
+    pub text: String,
+}
+
+/// The path of a `diff --git a/<old> b/<new>` header.
+fn header_path(line: &str) -> String {
+    let rest = line.trim_start_matches("diff --git ");
+    match rest.rsplit_once(" b/") {
+        Some((_, path)) => path.to_string(),
+        None => rest.to_string(),
+    }
+}
+
+/// Splits a `git diff` into files; text before the first header is dropped.
+pub fn split_files(diff: &str) -> Vec<FileDiff> {
+    let mut files: Vec<FileDiff> = Vec::new();
+    let mut in_hunk = false;
+    for line in diff.split_inclusive('\n') {
+        if line.starts_with("diff --git ") {
+            files.push(FileDiff {
+                path: header_path(line.trim_end()),
+                hunks: 0,
+                added: 0,
+                removed: 0,
+                text: String::new(),
+            });
+            in_hunk = false;
+        }
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+        file.text.push_str(line);
+        if line.starts_with("@@") {
+            file.hunks += 1;
+            in_hunk = true;
+        } else if in_hunk && line.starts_with('+') {
+            file.added += 1;
+        } else if in_hunk && line.starts_with('-') {
+            file.removed += 1;
+        }
+    }
+    files
+}
+
+/// The header of `file`, everything before its first `@@` line, and its
+/// hunks; binary files and mode changes have no hunks.
+pub fn hunks(file: &FileDiff) -> (&str, Vec<&str>) {
+    let mut starts = Vec::new();
+    let mut at = 0;
+    for line in file.text.split_inclusive('\n') {
+        if line.starts_with("@@") {
+            starts.push(at);
+        }
+        at += line.len();
+    }
+    let Some(&first) = starts.first() else {
+        return (&file.text, Vec::new());
+    };
+    starts.push(file.text.len());
+    let hunks = starts.windows(2).map(|w| &file.text[w[0]..w[1]]).collect();
+    (&file.text[..first], hunks)
+}
+
+/// The old and new blob ids of `file` from its `index` line.
+fn blob_ids(file: &FileDiff) -> Option<(&str, &str)> {
+    let line = file.text.lines().find(|l| l.starts_with("index "))?;
+    let (old, rest) = line["index ".len()..].split_once("..")?;
+    Some((old, rest.split(' ').next()?))
+}
+
+/// A blob id unless it is git's all-zero id of a missing side.
+fn existing(id: &str) -> Option<&str> {
+    (!id.is_empty() && !id.bytes().all(|b| b == b'0')).then_some(id)
+}
+
+/// The blob id of the old version of `file`, or `None` when it was added
+/// or the diff has no `index` line.
+pub fn old_blob(file: &FileDiff) -> Option<&str> {
+    existing(blob_ids(file)?.0)
+}
+
+/// The blob id of the new version of `file`, or `None` when it was deleted
+/// or the diff has no `index` line.
+pub fn new_blob(file: &FileDiff) -> Option<&str> {
+    existing(blob_ids(file)?.1)
+}
+
+/// Runs of added or removed lines longer than this are collapsed by [`minify`].
+const MAX_RUN: usize = 12;
+/// Lines kept at the start and end of a collapsed run.
+const RUN_HEAD: usize = 8;
+const RUN_TAIL: usize = 3;
+
+/// Shortens a diff for the model: context lines are dropped (the `@@` line
+/// still names the enclosing function), long runs of added or removed lines
+/// keep only their start and end, and a hunk repeating an earlier one is
+/// replaced by a reference to it.
+pub fn minify(diff: &str) -> String {
+    let mut text = String::new();
+    // Hunk bodies seen so far, with where they were first seen.
+    let mut seen: Vec<(String, String)> = Vec::new();
+    for file in split_files(diff) {
+        let mut lines = file.text.lines().peekable();
+        while let Some(line) = lines.next_if(|l| !l.starts_with("@@")) {
+            text.push_str(line);
+            text.push('\n');
+        }
+        while let Some(header) = lines.next() {
+            let mut body: Vec<&str> = Vec::new();
+            while let Some(line) = lines.next_if(|l| !l.starts_with("@@")) {
+                // `\ No newline at end of file` belongs with the context.
+                if !line.starts_with(' ') && !line.starts_with('\\') {
+                    body.push(line);
+                }
+            }
+            let joined = body.join("\n");
+            let place = format!(
+                "{} {}",
+                file.path,
+                header.split(" @@").next().unwrap_or(header)
+            );
+            if let Some((_, first)) = seen.iter().find(|(b, _)| *b == joined && !b.is_empty()) {
+                text.push_str(&format!("{}\n[same change as {} @@]\n", header, first));
+                continue;
+            }
+            seen.push((joined, place));
+            text.push_str(header);
+            text.push('\n');
+            let mut i = 0;
+            while i < body.len() {
+                // A pasted patch may hold lines that are neither, e.g. `「`.
+                let sign = body[i].chars().next().filter(|c| matches!(c, '+' | '-'));
+                let run = match sign {
+                    Some(sign) => body[i..].iter().take_while(|l| l.starts_with(sign)).count(),
+                    None => 1,
+                };
+                if let Some(sign) = sign
+                    && run > MAX_RUN
+                {
+                    for line in &body[i..i + RUN_HEAD] {
+                        text.push_str(&format!("{}\n", line));
+                    }
+                    let what = if sign == '+' { "added" } else { "removed" };
+                    text.push_str(&format!(
+                        "{}… {} more {} lines\n",
+                        sign,
+                        run - RUN_HEAD - RUN_TAIL,
+                        what
+                    ));
+                    for line in &body[i + run - RUN_TAIL..i + run] {
+                        text.push_str(&format!("{}\n", line));
+                    }
+                } else {
+                    for line in &body[i..i + run] {
+                        text.push_str(&format!("{}\n", line));
+                    }
+                }
+                i += run;
+            }
+        }
+    }
+    text
+}
+
+/// Line pairs compared at most by [`unified`] before it gives up on finding
+/// the common lines and replaces everything.
+const MAX_COMPARISONS: usize = 25_000_000;
+
+/// The hunks of a unified diff from `old` to `new` with `context` lines
+/// around each change, without file headers.
+pub fn unified(old: &str, new: &str, context: usize) -> String {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    // `ops` walks both texts: ' ' keeps a line, '-' removes, '+' adds.
+    let mut ops: Vec<(char, &str)> = Vec::new();
+    if a.len().saturating_mul(b.len()) > MAX_COMPARISONS {
+        ops.extend(a.iter().map(|l| ('-', *l)));
+        ops.extend(b.iter().map(|l| ('+', *l)));
+    } else {
+        // Longest common subsequence of the suffixes.
+        let mut lcs = vec![vec![0u32; b.len() + 1]; a.len() + 1];
+        for i in (0..a.len()).rev() {
+            for j in (0..b.len()).rev() {
+                lcs[i][j] = if a[i] == b[j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() || j < b.len() {
+            if i < a.len() && j < b.len() && a[i] == b[j] {
+                ops.push((' ', a[i]));
+                i += 1;
+                j += 1;
+            } else if i < a.len() && (j == b.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+                ops.push(('-', a[i]));
+                i += 1;
+            } else {
+                ops.push(('+', b[j]));
+                j += 1;
+            }
+        }
+    }
+
+    let changed: Vec<usize> = (0..ops.len()).filter(|&k| ops[k].0 != ' ').collect();
+    let mut text = String::new();
+    let mut k = 0;
+    while k < changed.len() {
+        // Changes closer than twice the context share a hunk.
+        let start = changed[k].saturating_sub(context);
+        let mut last = changed[k];
+        while k + 1 < changed.len() && changed[k + 1] - last <= 2 * context {
+            k += 1;
+            last = changed[k];
+        }
+        let end = (last + context + 1).min(ops.len());
+        let line_of = |side: char| ops[..start].iter().filter(|(op, _)| *op != side).count() + 1;
+        let count = |side: char| ops[start..end].iter().filter(|(op, _)| *op != side).count();
+        text.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            line_of('+'),
+            count('+'),
+            line_of('-'),
+            count('-')
+        ));
+        for (op, line) in &ops[start..end] {
+            text.push_str(&format!("{}{}\n", op, line));
+        }
+        k += 1;
+    }
+    text
+}
+
+/// A `git diff --stat` style overview: one `path | changes +++--` line per
+/// file and the totals.
+pub fn stat(files: &[FileDiff]) -> String {
+    const BAR: usize = 40;
+    let width = files
+        .iter()
+        .map(|f| f.path.chars().count())
+        .max()
+        .unwrap_or(0);
+    let most = files.iter().map(|f| f.added + f.removed).max().unwrap_or(0);
+    let digits = most.to_string().len();
+    let mut lines: Vec<String> = files
+        .iter()
+        .map(|f| {
+            let changes = f.added + f.removed;
+            // Scaled like git so the largest file fills the bar.
+            let scale = |n: usize| match most > BAR {
+                true => (n * BAR).div_ceil(most),
+                false => n,
+            };
+            format!(
+                " {:width$} | {:>digits$} {}{}",
+                f.path,
+                changes,
+                "+".repeat(scale(f.added)),
+                "-".repeat(scale(f.removed)),
+                width = width,
+                digits = digits
+            )
+        })
+        .collect();
+    let plural =
+        |n: usize, one: &str, many: &str| format!("{} {}", n, if n == 1 { one } else { many });
+    lines.push(format!(
+        " {}, {}, {}",
+        plural(files.len(), "file changed", "files changed"),
+        plural(
+            files.iter().map(|f| f.added).sum(),
+            "insertion(+)",
+            "insertions(+)"
+        ),
+        plural(
+            files.iter().map(|f| f.removed).sum(),
+            "deletion(-)",
+            "deletions(-)"
+        )
+    ));
+    lines.join("\n")
+}
+
+/// The first line of a Git LFS pointer file.
+const LFS_VERSION: &str = "version https://git-lfs.github.com/spec/";
+
+/// `bytes` as `512 B`, `1.2 KB` or `3.4 MB`.
+pub fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// One line describing the change of a Git LFS pointer file, or `None` when
+/// `file` is not one.
+fn describe_lfs_file(file: &FileDiff) -> Option<String> {
+    let (mut pointer, mut old, mut new) = (false, None, None);
+    for line in file.text.lines() {
+        let (Some(sign), Some(content)) = (line.chars().next(), line.get(1..)) else {
+            continue;
+        };
+        if !matches!(sign, '+' | '-' | ' ') || line.starts_with("+++") || line.starts_with("---") {
+            continue;
+        }
+        pointer |= content.starts_with(LFS_VERSION);
+        if let Some(size) = content
+            .strip_prefix("size ")
+            .and_then(|s| s.trim().parse().ok())
+        {
+            if sign != '+' {
+                old = Some(size);
+            }
+            if sign != '-' {
+                new = Some(size);
+            }
+        }
+    }
+    if !pointer {
+        return None;
+    }
+    let deleted = file
+        .text
+        .lines()
+        .any(|l| l.starts_with("deleted file mode"));
+    let added = file.text.lines().any(|l| l.starts_with("new file mode"));
+    Some(match (old, new) {
+        (_, Some(new)) if added => {
+            format!("added LFS asset {} (size {})", file.path, human_size(new))
+        }
+        (Some(old), _) if deleted => {
+            format!("deleted LFS asset {} (size {})", file.path, human_size(old))
+        }
+        (Some(old), Some(new)) => format!(
+            "updated LFS asset {} (size {} → {})",
+            file.path,
+            human_size(old),
+            human_size(new)
+        ),
+        _ => format!("updated LFS asset {}", file.path),
+    })
+}
+
+/// Replaces the diff of every Git LFS pointer file with one line saying what
+/// happened to the asset, as the pointer's hashes tell the model nothing.
+/// Returns `None` when `diff` has no pointer files.
+pub fn describe_lfs(diff: &str) -> Option<String> {
+    let files = split_files(diff);
+    if !files.iter().any(|f| describe_lfs_file(f).is_some()) {
+        return None;
+    }
+    let mut text = String::new();
+    for file in &files {
+        match describe_lfs_file(file) {
+            Some(description) => {
+                let header = file.text.lines().next().unwrap_or_default();
+                text.push_str(&format!("{}\n{}\n", header, description));
+            }
+            None => text.push_str(&file.text),
+        }
+    }
+    Some(text)
+}
+
+/// How much a file tells about the intent of a change, from source code (0)
+/// through configuration and documentation to assets and snapshots (3).
+pub fn rank(path: &str) -> u8 {
+    let lower = path.to_lowercase();
+    let name = lower.rsplit('/').next().unwrap_or(&lower);
+    let extension = name.rsplit_once('.').map_or("", |(_, ext)| ext);
+    const ASSETS: &[&str] = &[
+        "png", "jpg", "jpeg", "gif", "webp", "ico", "svg", "bmp", "pdf", "woff", "woff2", "ttf",
+        "otf", "eot", "mp3", "mp4", "wav", "zip", "gz", "snap", "map", "lock", "min",
+    ];
+    const LOCKFILES: &[&str] = &["package-lock.json", "yarn.lock", "pnpm-lock.yaml", "go.sum"];
+    const CONFIG: &[&str] = &[
+        "toml",
+        "json",
+        "yaml",
+        "yml",
+        "ini",
+        "cfg",
+        "conf",
+        "xml",
+        "gradle",
+        "properties",
+        "env",
+    ];
+    const DOCS: &[&str] = &["md", "markdown", "rst", "txt", "adoc", "org"];
+    if ASSETS.contains(&extension)
+        || LOCKFILES.contains(&name)
+        || name.contains(".min.")
+        || lower.contains("__snapshots__/")
+        || lower.starts_with("vendor/")
+        || lower.contains("/vendor/")
+    {
+        3
+    } else if DOCS.contains(&extension) || lower.starts_with("docs/") || lower.contains("/docs/") {
+        2
+    } else if CONFIG.contains(&extension) || name.starts_with('.') || name == "makefile" {
+        1
+    } else {
+        0
+    }
+}
+
+/// Files listed as the largest in a [`TrimReport`].
+const MAX_REPORTED: usize = 5;
+
+/// What [`trim`] does to a diff over the limit, to tell before sending it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrimReport {
+    /// The size of the whole diff.
+    pub bytes: usize,
+    pub max_bytes: usize,
+    /// The largest files and their sizes, largest first.
+    pub largest: Vec<(String, usize)>,
+    /// The file sent only in part, if any.
+    pub cut: Option<String>,
+    /// The files left out entirely.
+    pub omitted: Vec<String>,
+}
+
+/// Shortens `diff` to about `max_bytes`, keeping the files that say most
+/// about the change: files are ordered by [`rank`] and cut from the bottom,
+/// the last one that fits only in part. Returns `None` when `diff` fits.
+pub fn trim(diff: &str, max_bytes: usize) -> Option<String> {
+    trim_with_report(diff, max_bytes).map(|(text, _)| text)
+}
+
+/// What [`trim`] would do to `diff`, or `None` when it fits.
+pub fn trim_report(diff: &str, max_bytes: usize) -> Option<TrimReport> {
+    trim_with_report(diff, max_bytes).map(|(_, report)| report)
+}
+
+/// How a [`TrimReport`] names a diff without `diff --git` headers.
+pub const HEADERLESS_DIFF: &str = "(diff without file headers)";
+
+/// `diff` without `diff --git` headers, such as a pasted patch, cut at the
+/// last character boundary within `max_bytes`. The report names the whole
+/// input as the one item cut.
+fn cut_text(diff: &str, max_bytes: usize) -> (String, TrimReport) {
+    let mut end = max_bytes;
+    while !diff.is_char_boundary(end) {
+        end -= 1;
+    }
+    let text = format!(
+        "{}\n[... {} more bytes cut]\n",
+        &diff[..end],
+        diff.len() - end
+    );
+    let report = TrimReport {
+        bytes: diff.len(),
+        max_bytes,
+        largest: vec![(HEADERLESS_DIFF.to_string(), diff.len())],
+        cut: Some(HEADERLESS_DIFF.to_string()),
+        omitted: Vec::new(),
+    };
+    (text, report)
+}
+
+fn trim_with_report(diff: &str, max_bytes: usize) -> Option<(String, TrimReport)> {
+    if diff.len() <= max_bytes {
+        return None;
+    }
+    let mut files = split_files(diff);
+    if files.is_empty() {
+        return Some(cut_text(diff, max_bytes));
+    }
+    let mut largest: Vec<(String, usize)> = files
+        .iter()
+        .map(|file| (file.path.clone(), file.text.len()))
+        .collect();
+    largest.sort_by_key(|(_, bytes)| std::cmp::Reverse(*bytes));
+    largest.truncate(MAX_REPORTED);
+    let mut report = TrimReport {
+        bytes: diff.len(),
+        max_bytes,
+        largest,
+        cut: None,
+        omitted: Vec::new(),
+    };
+    files.sort_by_key(|f| rank(&f.path));
+    let mut text = String::new();
+    let mut omitted = Vec::new();
+    for file in &files {
+        let left = max_bytes.saturating_sub(text.len());
+        if file.text.len() <= left {
+            text.push_str(&file.text);
+            continue;
+        }
+        // Part of a file is only worth sending if its header and a hunk fit.
+        if omitted.is_empty() && left > 1024 {
+            let mut lines = file.text.split_inclusive('\n');
+            let mut kept = 0;
+            for line in lines.by_ref() {
+                if kept + line.len() > left {
+                    break;
+                }
+                text.push_str(line);
+                kept += line.len();
+            }
+            let cut = lines.count() + 1;
+            text.push_str(&format!("[... {} more lines of {} cut]\n", cut, file.path));
+            report.cut = Some(file.path.clone());
+        } else {
+            report.omitted.push(file.path.clone());
+        }
+        omitted.push(format!("{} (+{} -{})", file.path, file.added, file.removed));
+    }
+    if !omitted.is_empty() {
+        text.push_str(&format!(
+            "[left out to keep the prompt short: {}]\n",
+            omitted.join(", ")
+        ));
+    }
+    Some((text, report))
+}
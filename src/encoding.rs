@@ -0,0 +1,108 @@
+//! Decoding git output that is not UTF-8: Shift_JIS or Latin-1 file
+//! content, or paths in those encodings. Each file of a diff is decoded on
+//! its own, since one legacy file must not garble the others.
+
+mod cp932;
+
+use crate::log;
+use std::sync::LazyLock;
+
+/// The characters of [`cp932::ROWS`], indexed by row and trail byte.
+static CP932: LazyLock<Vec<Vec<char>>> = LazyLock::new(|| {
+    cp932::ROWS
+        .iter()
+        .map(|row| row.chars().collect())
+        .collect()
+});
+
+/// Windows-1252 for 0x80–0x9F, where Latin-1 has control characters;
+/// unassigned bytes stay as those controls.
+const CP1252: [char; 32] = [
+    '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8D}', 'Ž', '\u{8F}',
+    '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9D}', 'ž', 'Ÿ',
+];
+
+/// The double-byte character for `lead` and `trail`, if assigned.
+fn cp932_pair(lead: u8, trail: u8) -> Option<char> {
+    let row = match lead {
+        0x81..=0x9F => lead - 0x81,
+        0xE0..=0xFC => lead - 0xE0 + 31,
+        _ => return None,
+    };
+    let column = match trail {
+        0x40..=0x7E => trail - 0x40,
+        0x80..=0xFC => trail - 0x41,
+        _ => return None,
+    };
+    CP932[row as usize]
+        .get(column as usize)
+        .copied()
+        .filter(|&c| c != char::REPLACEMENT_CHARACTER)
+}
+
+/// `bytes` as Shift_JIS, or `None` if they are not valid Shift_JIS.
+fn shift_jis(bytes: &[u8]) -> Option<String> {
+    let mut text = String::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let byte = bytes[i];
+        match byte {
+            0x00..=0x7F => text.push(byte as char),
+            // Half-width katakana.
+            0xA1..=0xDF => text.push(char::from_u32(0xFF61 + u32::from(byte - 0xA1))?),
+            _ => {
+                text.push(cp932_pair(byte, *bytes.get(i + 1)?)?);
+                i += 1;
+            }
+        }
+        i += 1;
+    }
+    Some(text)
+}
+
+/// `bytes` as Windows-1252, which never fails.
+fn latin1(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|&b| match b {
+            0x80..=0x9F => CP1252[usize::from(b - 0x80)],
+            _ => char::from(b),
+        })
+        .collect()
+}
+
+/// Decodes one part: UTF-8 if valid, else Shift_JIS if valid, else Latin-1.
+fn decode_part(bytes: &[u8]) -> String {
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return text.to_string();
+    }
+    let (text, encoding) = match shift_jis(bytes) {
+        Some(text) => (text, "Shift_JIS"),
+        None => (latin1(bytes), "Latin-1"),
+    };
+    let first = text.lines().next().unwrap_or_default();
+    log::debug(&format!("decoded as {}: {}", encoding, first));
+    text
+}
+
+/// Decodes output that is not UTF-8, one `diff --git` section at a time so
+/// that each file gets its own encoding.
+pub fn decode(bytes: &[u8]) -> String {
+    const HEADER: &[u8] = b"\ndiff --git ";
+    let mut text = String::with_capacity(bytes.len());
+    let mut start = 0;
+    while start < bytes.len() {
+        let end = bytes[start + 1..]
+            .windows(HEADER.len())
+            .position(|window| window == HEADER)
+            .map_or(bytes.len(), |at| start + 1 + at + 1);
+        text.push_str(&decode_part(&bytes[start..end]));
+        start = end;
+    }
+    text
+}
+
+/// `bytes` as a string, decoding them with [`decode`] unless they are UTF-8.
+pub fn to_string(bytes: Vec<u8>) -> String {
+    String::from_utf8(bytes).unwrap_or_else(|e| decode(e.as_bytes()))
+}
@@ -0,0 +1,29 @@
+use crate::i18n::{Message, tr};
+use std::fmt;
+
+/// Failures that callers may want to tell apart, e.g. to pick an exit code.
+/// Everything else stays a plain boxed error.
+#[derive(Debug)]
+pub enum Error {
+    /// There are no changes to describe.
+    NothingToCommit,
+    /// Missing key, unsupported provider or conflicting options.
+    Config(String),
+    /// The request to the model failed or returned nothing usable.
+    Api(String),
+    /// The generated message does not follow the expected format.
+    Validation(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NothingToCommit => f.write_str(&tr(Message::NothingToCommit, &[])),
+            Error::Config(message) | Error::Api(message) | Error::Validation(message) => {
+                f.write_str(message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
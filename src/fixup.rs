@@ -0,0 +1,144 @@
+//! Detects staged changes that rework a commit which has not been pushed
+//! yet, so they can become a `fixup!` commit for `git rebase --autosquash`
+//! instead of a commit of their own.
+
+use crate::diff::{self, FileDiff};
+use crate::git;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// Unpushed commits looked at, newest first.
+const MAX_COMMITS: usize = 20;
+
+/// The commit the staged changes belong to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Target {
+    pub commit: String,
+    pub subject: String,
+}
+
+impl Target {
+    /// The message `git commit --fixup` would write.
+    pub fn message(&self) -> String {
+        format!("fixup! {}", self.subject)
+    }
+}
+
+/// The `start,count` of the old side of a `@@ -start,count +... @@` line.
+fn old_range(line: &str) -> Option<(usize, usize)> {
+    let range = line.strip_prefix("@@ -")?.split(' ').next()?;
+    Some(match range.split_once(',') {
+        Some((start, count)) => (start.parse().ok()?, count.parse().ok()?),
+        None => (range.parse().ok()?, 1),
+    })
+}
+
+/// The path of the old version of `file`, or `None` when it was added.
+fn old_path(file: &FileDiff) -> Option<&str> {
+    let line = file.text.lines().find(|l| l.starts_with("--- "))?;
+    let path = line["--- ".len()..].trim_end_matches('\t');
+    path.strip_prefix("a/")
+}
+
+/// The lines of the old version that `file` changes: the removed ones, or
+/// for a hunk that only adds, the lines it is inserted between.
+pub fn touched_lines(file: &FileDiff) -> Vec<usize> {
+    let mut lines = Vec::new();
+    for hunk in file.text.split("\n@@").skip(1) {
+        let Some((start, count)) = old_range(&format!("@@{}", hunk)) else {
+            continue;
+        };
+        let mut old = start;
+        let mut removed = Vec::new();
+        let mut inserted = Vec::new();
+        for line in hunk.lines().skip(1) {
+            if line.starts_with('-') {
+                removed.push(old);
+                old += 1;
+            } else if line.starts_with('+') {
+                inserted.extend([old.saturating_sub(1), old]);
+            } else if line.starts_with(' ') {
+                old += 1;
+            }
+        }
+        if removed.is_empty() {
+            // Only the hunk's context lines exist around an insertion.
+            lines.extend(
+                inserted
+                    .into_iter()
+                    .filter(|line| (start..start + count).contains(line)),
+            );
+        } else {
+            lines.extend(removed);
+        }
+    }
+    lines.sort_unstable();
+    lines.dedup();
+    lines
+}
+
+/// Adds the commits that last touched `lines` of `path` to `commits`.
+fn blame(
+    repo: Option<&Path>,
+    path: &str,
+    lines: &[usize],
+    commits: &mut BTreeSet<String>,
+) -> Option<()> {
+    let mut args = vec!["blame".to_string(), "--porcelain".to_string()];
+    for line in lines {
+        args.push(format!("-L{},{}", line, line));
+    }
+    args.extend(["HEAD".to_string(), "--".to_string(), path.to_string()]);
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let output = git::output(repo, &args).ok()?;
+    for line in output.lines() {
+        let mut words = line.split(' ');
+        let Some(commit) = words.next() else {
+            continue;
+        };
+        // Every blamed line starts with `<commit> <old line> <new line>`.
+        let is_header = commit.len() >= 40
+            && commit.bytes().all(|b| b.is_ascii_hexdigit())
+            && words.next().is_some_and(|w| w.parse::<usize>().is_ok());
+        if is_header {
+            commits.insert(commit.to_string());
+        }
+    }
+    Some(())
+}
+
+/// The recent commits on the current branch that no remote branch has.
+fn unpushed(repo: Option<&Path>) -> BTreeSet<String> {
+    let max = format!("--max-count={}", MAX_COMMITS);
+    git::output(repo, &["rev-list", &max, "HEAD", "--not", "--remotes"])
+        .map(|text| text.lines().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// The unpushed commit that last touched every line `diff` changes, if it
+/// is a single one; added files and changes to older code have none.
+pub fn target(repo: Option<&Path>, diff: &str) -> Option<Target> {
+    let unpushed = unpushed(repo);
+    if unpushed.is_empty() {
+        return None;
+    }
+    let mut commits = BTreeSet::new();
+    for file in diff::split_files(diff) {
+        let path = old_path(&file)?;
+        let lines = touched_lines(&file);
+        if lines.is_empty() {
+            return None;
+        }
+        blame(repo, path, &lines, &mut commits)?;
+    }
+    let mut commits = commits.into_iter();
+    let commit = commits.next()?;
+    if commits.next().is_some() || !unpushed.contains(&commit) {
+        return None;
+    }
+    let subject = git::output(repo, &["log", "-1", "--format=%s", &commit]).ok()?;
+    Some(Target {
+        commit,
+        subject: subject.trim().to_string(),
+    })
+}
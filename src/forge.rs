@@ -0,0 +1,269 @@
+//! Minimal GitHub / GitLab REST clients for pull and merge requests.
+
+use serde_json::{Value, json};
+use std::env;
+
+/// Around the generated part of a description, so that updating it replaces
+/// the previous text and keeps what people wrote outside of it.
+pub const MARKER_START: &str = "<!-- gemini-commit-message:start -->";
+pub const MARKER_END: &str = "<!-- gemini-commit-message:end -->";
+
+/// `generated` between the markers.
+pub fn marked(generated: &str) -> String {
+    format!("{}\n{}\n{}", MARKER_START, generated.trim(), MARKER_END)
+}
+
+/// `existing` with its marked section replaced by `generated`, or with
+/// `generated` appended when it has none.
+pub fn replace_marked(existing: &str, generated: &str) -> String {
+    if let Some(start) = existing.find(MARKER_START)
+        && let Some(end) = existing[start..].find(MARKER_END)
+    {
+        let end = start + end + MARKER_END.len();
+        return format!(
+            "{}{}{}",
+            &existing[..start],
+            marked(generated),
+            &existing[end..]
+        );
+    }
+    match existing.trim() {
+        "" => marked(generated),
+        existing => format!("{}\n\n{}", existing, marked(generated)),
+    }
+}
+
+/// An open pull (merge) request.
+#[derive(Debug, Clone)]
+pub struct PullRequest {
+    /// The number on GitHub, the `iid` on GitLab.
+    pub number: u64,
+    pub body: String,
+    pub url: String,
+}
+
+/// A hosting service detected from a remote URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Forge {
+    GitHub {
+        api: String,
+        owner: String,
+        repo: String,
+    },
+    GitLab {
+        api: String,
+        project: String,
+    },
+}
+
+/// Splits `git@host:path`, `ssh://git@host/path` or `https://host/path`
+/// into host and repository path without `.git`.
+fn parse_remote(url: &str) -> Option<(String, String)> {
+    let url = url.trim();
+    let (host, path) = if let Some(rest) = url.split_once("://").map(|(_, rest)| rest) {
+        let rest = rest.rsplit_once('@').map_or(rest, |(_, rest)| rest);
+        rest.split_once('/')?
+    } else {
+        let rest = url.rsplit_once('@').map_or(url, |(_, rest)| rest);
+        rest.split_once(':')?
+    };
+    // Drop an explicit port such as `host:2222`.
+    let host = host.split(':').next()?;
+    let path = path.trim_matches('/').trim_end_matches(".git");
+    if host.is_empty() || !path.contains('/') {
+        return None;
+    }
+    Some((host.to_string(), path.to_string()))
+}
+
+fn encode_path(path: &str) -> String {
+    path.replace('%', "%25").replace('/', "%2F")
+}
+
+/// `value` for a query string: everything but unreserved characters is
+/// percent-encoded, so branches such as `fix/c++-build` or `issue#12` stay
+/// whole.
+fn encode_query(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                char::from(b).to_string()
+            }
+            b => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+impl Forge {
+    pub fn detect(remote_url: &str) -> Option<Self> {
+        let (host, path) = parse_remote(remote_url)?;
+        if host == "github.com" || host.contains("github") {
+            let (owner, repo) = path.rsplit_once('/')?;
+            let api = if host == "github.com" {
+                "https://api.github.com".to_string()
+            } else {
+                format!("https://{}/api/v3", host)
+            };
+            Some(Forge::GitHub {
+                api,
+                owner: owner.to_string(),
+                repo: repo.to_string(),
+            })
+        } else if host.contains("gitlab") {
+            Some(Forge::GitLab {
+                api: format!("https://{}/api/v4", host),
+                project: path,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Reads the token from `GITHUB_TOKEN`/`GH_TOKEN` or `GITLAB_TOKEN`.
+    pub fn token_from_env(&self) -> Option<String> {
+        let names: &[&str] = match self {
+            Forge::GitHub { .. } => &["GITHUB_TOKEN", "GH_TOKEN"],
+            Forge::GitLab { .. } => &["GITLAB_TOKEN"],
+        };
+        names
+            .iter()
+            .find_map(|name| env::var(name).ok().filter(|v| !v.is_empty()))
+    }
+
+    /// Adds the authentication headers of this forge.
+    fn authorize<B>(
+        &self,
+        request: ureq::RequestBuilder<B>,
+        token: &str,
+    ) -> ureq::RequestBuilder<B> {
+        let request = request.header("User-Agent", env!("CARGO_PKG_NAME"));
+        match self {
+            Forge::GitHub { .. } => request
+                .header("Accept", "application/vnd.github+json")
+                .header("Authorization", &format!("Bearer {}", token)),
+            Forge::GitLab { .. } => request.header("PRIVATE-TOKEN", token),
+        }
+    }
+
+    /// Sends `payload` with the authentication headers of this forge.
+    fn send(
+        &self,
+        request: ureq::RequestBuilder<ureq::typestate::WithBody>,
+        token: &str,
+        payload: Value,
+    ) -> Result<Value, Box<dyn std::error::Error>> {
+        Ok(self
+            .authorize(request, token)
+            .send_json(payload)?
+            .body_mut()
+            .read_json::<Value>()?)
+    }
+
+    /// The URL listing the open pull (merge) requests from the branch `head`.
+    pub fn pull_requests_url(&self, head: &str) -> String {
+        match self {
+            Forge::GitHub { api, owner, repo } => format!(
+                "{}/repos/{}/{}/pulls?state=open&head={}:{}",
+                api,
+                owner,
+                repo,
+                encode_query(owner),
+                encode_query(head)
+            ),
+            Forge::GitLab { api, project } => format!(
+                "{}/projects/{}/merge_requests?state=opened&source_branch={}",
+                api,
+                encode_path(project),
+                encode_query(head)
+            ),
+        }
+    }
+
+    /// The open pull (merge) request from the branch `head`, if any.
+    pub fn find_pull_request(
+        &self,
+        token: &str,
+        head: &str,
+    ) -> Result<Option<PullRequest>, Box<dyn std::error::Error>> {
+        let url = self.pull_requests_url(head);
+        let (number_field, body_field, url_field) = match self {
+            Forge::GitHub { .. } => ("number", "body", "html_url"),
+            Forge::GitLab { .. } => ("iid", "description", "web_url"),
+        };
+        let response: Value = self
+            .authorize(ureq::get(&url), token)
+            .call()?
+            .body_mut()
+            .read_json()?;
+        let Some(request) = response.as_array().and_then(|list| list.first()) else {
+            return Ok(None);
+        };
+        Ok(Some(PullRequest {
+            number: request[number_field]
+                .as_u64()
+                .ok_or("no pull request number")?,
+            body: request[body_field].as_str().unwrap_or_default().to_string(),
+            url: request[url_field].as_str().unwrap_or_default().to_string(),
+        }))
+    }
+
+    /// Replaces the description of pull (merge) request `number`.
+    pub fn update_pull_request(
+        &self,
+        token: &str,
+        number: u64,
+        body: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        match self {
+            Forge::GitHub { api, owner, repo } => {
+                let url = format!("{}/repos/{}/{}/pulls/{}", api, owner, repo, number);
+                self.send(ureq::patch(&url), token, json!({ "body": body }))?;
+            }
+            Forge::GitLab { api, project } => {
+                let url = format!(
+                    "{}/projects/{}/merge_requests/{}",
+                    api,
+                    encode_path(project),
+                    number
+                );
+                self.send(ureq::put(&url), token, json!({ "description": body }))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Opens a pull (merge) request from `head` into `base` and returns its URL.
+    pub fn create_pull_request(
+        &self,
+        token: &str,
+        head: &str,
+        base: &str,
+        title: &str,
+        body: &str,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let (url, payload, url_field) = match self {
+            Forge::GitHub { api, owner, repo } => (
+                format!("{}/repos/{}/{}/pulls", api, owner, repo),
+                json!({ "title": title, "body": body, "head": head, "base": base }),
+                "html_url",
+            ),
+            Forge::GitLab { api, project } => (
+                format!("{}/projects/{}/merge_requests", api, encode_path(project)),
+                json!({
+                    "title": title,
+                    "description": body,
+                    "source_branch": head,
+                    "target_branch": base
+                }),
+                "web_url",
+            ),
+        };
+        let response = self.send(ureq::post(&url), token, payload)?;
+        Ok(response
+            .get(url_field)
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string())
+    }
+}
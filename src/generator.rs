@@ -0,0 +1,455 @@
+use crate::cache::{self, Cache};
+use crate::diff::{self, DiffSource, FileDiff};
+use crate::error::Error;
+use crate::i18n::{Message, tr};
+use crate::learn::{self, Corrections};
+use crate::message::{self, CommitMessage, Footer};
+use crate::prompt::{
+    self, FILE_SUMMARY_GUIDELINE, Image, Prompt, PromptBuilder, SUBJECT_GUIDELINE,
+};
+use crate::provider::Provider;
+use crate::{git, log, notebook, repository, timing};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+
+/// Summary requests in flight at once in the chunked pipeline by default.
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// Subjects of recent commits sent along with the diff by default.
+pub const DEFAULT_RECENT_COMMITS: usize = 10;
+
+/// Images sent at most, and their size each; Gemini takes up to 20 MB of
+/// inline data per request.
+pub const MAX_IMAGES: usize = 4;
+const MAX_IMAGE_BYTES: usize = 4 * 1024 * 1024;
+
+/// Fails with [`Error::Validation`] when `message` is not a commit message,
+/// rather than letting an answer to text in the diff be committed.
+fn plausible(message: CommitMessage) -> Result<CommitMessage, Box<dyn std::error::Error>> {
+    match message.implausibility() {
+        Some(reason) => {
+            log::info(&format!("rejected answer:\n{}", message));
+            Err(Error::Validation(tr(Message::NotACommitMessage, &[&reason])).into())
+        }
+        None => Ok(message),
+    }
+}
+
+/// Reads a diff, builds the prompt and asks a provider for the message.
+///
+/// ```no_run
+/// use gemini_commit_message::{Gemini, Generator};
+///
+/// let generator = Generator::new(Gemini::new("API_KEY").model("gemini-2.5-flash"));
+/// if let Some(message) = generator.generate()? {
+///     println!("{}", message);
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct Generator {
+    provider: Box<dyn Provider>,
+    diff_source: DiffSource,
+    prompt_builder: PromptBuilder,
+    repo: Option<PathBuf>,
+    chunked: bool,
+    concurrency: usize,
+    trailers: Vec<Footer>,
+    recent_commits: usize,
+    repository_context: bool,
+    corrections: usize,
+    images: bool,
+    minify: bool,
+    subject: Option<String>,
+    /// Where file summaries are kept, and the model that wrote them.
+    summary_cache: Option<(Cache, String)>,
+    /// The prompt builder with the repository context, read on first use.
+    with_context: OnceLock<PromptBuilder>,
+}
+
+impl Generator {
+    pub fn new(provider: impl Provider + 'static) -> Self {
+        Self {
+            provider: Box::new(provider),
+            diff_source: DiffSource::default(),
+            prompt_builder: PromptBuilder::default(),
+            repo: None,
+            chunked: false,
+            concurrency: DEFAULT_CONCURRENCY,
+            trailers: Vec::new(),
+            recent_commits: DEFAULT_RECENT_COMMITS,
+            repository_context: false,
+            corrections: 0,
+            images: false,
+            minify: false,
+            subject: None,
+            summary_cache: None,
+            with_context: OnceLock::new(),
+        }
+    }
+
+    pub fn diff_source(mut self, diff_source: DiffSource) -> Self {
+        self.diff_source = diff_source;
+        self
+    }
+
+    /// Runs git in `repo` instead of the current directory.
+    pub fn repo(mut self, repo: impl Into<PathBuf>) -> Self {
+        self.repo = Some(repo.into());
+        self.with_context = OnceLock::new();
+        self
+    }
+
+    pub fn prompt_builder(mut self, prompt_builder: PromptBuilder) -> Self {
+        self.prompt_builder = prompt_builder;
+        self.with_context = OnceLock::new();
+        self
+    }
+
+    /// Summarizes a multi-file diff file by file and writes the message from
+    /// the summaries, for diffs too large to send whole.
+    pub fn chunked(mut self, chunked: bool) -> Self {
+        self.chunked = chunked;
+        self
+    }
+
+    pub fn is_chunked(&self) -> bool {
+        self.chunked
+    }
+
+    /// Keeps the chunked pipeline's file summaries in `cache`, so that
+    /// regenerating a message only summarizes files whose blobs changed since
+    /// `model` last summarized them.
+    pub fn summary_cache(mut self, cache: Cache, model: impl Into<String>) -> Self {
+        self.summary_cache = Some((cache, model.into()));
+        self
+    }
+
+    /// How many file summaries the chunked pipeline requests at once.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// How many recent commit subjects to send so the model follows the
+    /// branch's narrative instead of repeating it; 0 sends none.
+    pub fn recent_commits(mut self, count: usize) -> Self {
+        self.recent_commits = count;
+        self.with_context = OnceLock::new();
+        self
+    }
+
+    /// Also sends the project name, main languages and top-level layout, so
+    /// scopes and terms match the project.
+    pub fn repository_context(mut self, enabled: bool) -> Self {
+        self.repository_context = enabled;
+        self.with_context = OnceLock::new();
+        self
+    }
+
+    /// Shows the model the author's latest `count` edits of generated
+    /// messages in this repository (see [`learn`](crate::learn)); 0 shows none.
+    pub fn corrections(mut self, count: usize) -> Self {
+        self.corrections = count;
+        self.with_context = OnceLock::new();
+        self
+    }
+
+    /// Also sends the new versions of added or changed images (up to
+    /// [`MAX_IMAGES`]), so the message can say what they show.
+    pub fn images(mut self, enabled: bool) -> Self {
+        self.images = enabled;
+        self
+    }
+
+    /// Leaves out context lines, long runs of added or removed lines and
+    /// repeated hunks, see [`diff::minify`].
+    pub fn minify(mut self, enabled: bool) -> Self {
+        self.minify = enabled;
+        self.with_context = OnceLock::new();
+        self
+    }
+
+    /// Keeps `subject` as the header and only asks for the body and footers.
+    pub fn subject(mut self, subject: Option<String>) -> Self {
+        self.subject = subject.map(|s| s.trim().to_string());
+        self.with_context = OnceLock::new();
+        self
+    }
+
+    /// The prompt builder plus what the repository tells about the change.
+    fn builder(&self) -> &PromptBuilder {
+        self.with_context.get_or_init(|| {
+            let mut builder = self.prompt_builder.clone().minify(self.minify);
+            if let Some(subject) = &self.subject {
+                builder = builder
+                    .instructions(&format!("{}\n{}", SUBJECT_GUIDELINE.trim(), subject))
+                    .schema(None);
+            }
+            if self.repository_context
+                && let Some(description) = repository::describe(self.repo.as_deref())
+            {
+                builder = builder.context("Repository", description);
+            }
+            if self.corrections > 0
+                && let Ok(root) = git::toplevel(self.repo.as_deref())
+                && let Some(corrections) = Corrections::open()
+            {
+                let recent = corrections.recent(&root.display().to_string(), self.corrections);
+                if !recent.is_empty() {
+                    builder = builder.context("Corrections", learn::prompt_text(&recent));
+                }
+            }
+            if self.recent_commits == 0 {
+                return builder;
+            }
+            match git::recent_subjects(self.repo.as_deref(), self.recent_commits) {
+                Ok(subjects) if !subjects.is_empty() => {
+                    let list: Vec<String> = subjects.iter().map(|s| format!("- {}", s)).collect();
+                    builder = builder.context(
+                        "Recent Commits",
+                        format!(
+                            "The latest commits on this branch, newest first. Do not repeat them; \
+                             when this change continues their work, say so.\n\n{}",
+                            list.join("\n")
+                        ),
+                    );
+                }
+                Ok(_) => {}
+                Err(e) => log::info(&format!("no recent commits: {}", e)),
+            }
+            builder
+        })
+    }
+
+    /// Trailers appended to every generated message, e.g. `Reviewed-by`.
+    pub fn trailers(mut self, trailers: Vec<Footer>) -> Self {
+        self.trailers = trailers;
+        self
+    }
+
+    pub fn configured_trailers(&self) -> &[Footer] {
+        &self.trailers
+    }
+
+    /// Returns `None` when there is nothing to describe.
+    pub fn generate(&self) -> Result<Option<CommitMessage>, Box<dyn std::error::Error>> {
+        let diff = self.diff_source.read(self.repo.as_deref())?;
+        if diff.trim().is_empty() {
+            return Ok(None);
+        }
+        self.generate_for_diff(&diff).map(Some)
+    }
+
+    /// The prompt that would be sent for `diff`.
+    pub fn prompt(&self, diff: &str) -> Prompt {
+        let notebooks = self.rewrite_notebooks(diff);
+        let diff = notebooks.as_deref().unwrap_or(diff);
+        let mut prompt = self.builder().build(diff);
+        self.attach_images(&mut prompt, &diff::split_files(diff));
+        prompt
+    }
+
+    /// What the prompt for `diff` leaves out to stay within the size limit,
+    /// or `None` when it is sent whole. In the chunked pipeline, a diff of
+    /// several files is summarized file by file instead.
+    pub fn trim_report(&self, diff: &str) -> Option<diff::TrimReport> {
+        let notebooks = self.rewrite_notebooks(diff);
+        self.builder()
+            .trim_report(notebooks.as_deref().unwrap_or(diff))
+    }
+
+    /// `diff` with notebooks reduced to their cell sources, see [`notebook`].
+    fn rewrite_notebooks(&self, diff: &str) -> Option<String> {
+        notebook::rewrite(diff, |blob| {
+            git::output(self.repo.as_deref(), &["cat-file", "blob", blob]).ok()
+        })
+    }
+
+    /// Reads the new versions of the images among `files` from the object
+    /// database; images that cannot be read or are too large are left out.
+    fn attach_images(&self, prompt: &mut Prompt, files: &[FileDiff]) {
+        if !self.images {
+            return;
+        }
+        let images = files
+            .iter()
+            .filter_map(|file| {
+                let mime_type = prompt::image_mime_type(&file.path)?;
+                let blob = diff::new_blob(file)?;
+                let data = git::output_bytes(self.repo.as_deref(), &["cat-file", "blob", blob])
+                    .map_err(|e| log::info(&format!("cannot read {}: {}", file.path, e)))
+                    .ok()?;
+                if data.len() > MAX_IMAGE_BYTES {
+                    log::info(&format!("{} is too large to send", file.path));
+                    return None;
+                }
+                Some(Image {
+                    path: file.path.clone(),
+                    mime_type,
+                    data,
+                })
+            })
+            .take(MAX_IMAGES)
+            .collect();
+        prompt.attach_images(images);
+    }
+
+    pub fn generate_for_diff(
+        &self,
+        diff: &str,
+    ) -> Result<CommitMessage, Box<dyn std::error::Error>> {
+        let notebooks = self.rewrite_notebooks(diff);
+        let simplified = notebooks.as_deref().unwrap_or(diff);
+        let lfs = diff::describe_lfs(simplified);
+        let files = diff::split_files(lfs.as_deref().unwrap_or(simplified));
+        let prompt = if self.chunked && files.len() > 1 {
+            let summaries = self.summarize(&files)?;
+            let mut prompt = self.builder().build_summaries(&files, &summaries);
+            self.attach_images(&mut prompt, &files);
+            prompt
+        } else {
+            timing::measure("prompt", || self.prompt(diff))
+        };
+        let text = self.provider.generate(&prompt)?;
+        if let Some(subject) = &self.subject {
+            let rest = message::strip_wrapping(&text);
+            let rest = rest.strip_prefix(subject.as_str()).unwrap_or(&rest);
+            let mut message = CommitMessage::parse(&format!("{}\n\n{}", subject, rest.trim()));
+            message.add_trailers(&self.trailers);
+            return plausible(message);
+        }
+        // Providers that cannot enforce the schema still answer in plain text.
+        let structured = prompt
+            .schema
+            .as_ref()
+            .and_then(|_| CommitMessage::from_structured(&message::strip_wrapping(&text)));
+        let mut message = structured.unwrap_or_else(|| CommitMessage::parse(&text));
+        message.add_trailers(&self.trailers);
+        plausible(message)
+    }
+
+    /// One `- path (+added -removed): summary` line per file, requesting up
+    /// to [`Generator::concurrency`] summaries at a time.
+    fn summarize(&self, files: &[FileDiff]) -> Result<String, Error> {
+        let builder = PromptBuilder::new().guideline(FILE_SUMMARY_GUIDELINE);
+        let next = AtomicUsize::new(0);
+        let summaries: Mutex<Vec<Option<Result<String, Error>>>> =
+            Mutex::new((0..files.len()).map(|_| None).collect());
+        let workers = self.concurrency.min(files.len());
+        log::info(&format!(
+            "summarizing {} files, {} at a time",
+            files.len(),
+            workers
+        ));
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| {
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(file) = files.get(i) else { break };
+                        let prompt = builder.build_section(
+                            "File Diff",
+                            &prompt::fenced("diff", file.text.trim_end()),
+                        );
+                        let cached = self.cached_summary(file, &prompt);
+                        if let Some((_, _, Some(summary))) = &cached {
+                            log::info(&format!("using the cached summary of {}", file.path));
+                            summaries.lock().unwrap_or_else(|e| e.into_inner())[i] =
+                                Some(Ok(summary.clone()));
+                            continue;
+                        }
+                        let summary = self
+                            .provider
+                            .generate(&prompt)
+                            .map(|text| message::strip_wrapping(&text))
+                            .map_err(|e| match e.downcast::<Error>() {
+                                Ok(e) => *e,
+                                Err(e) => Error::Api(e.to_string()),
+                            });
+                        if let (Some((cache, key, _)), Ok(summary)) = (&cached, &summary)
+                            && let Err(e) = cache.put(key, std::slice::from_ref(summary))
+                        {
+                            log::info(&format!("could not cache a summary: {}", e));
+                        }
+                        let failed = summary.is_err();
+                        summaries.lock().unwrap_or_else(|e| e.into_inner())[i] = Some(summary);
+                        if failed {
+                            // Let the other workers stop after their current file.
+                            next.store(files.len(), Ordering::Relaxed);
+                        }
+                    }
+                });
+            }
+        });
+        let mut lines = Vec::new();
+        let summaries = summaries.into_inner().unwrap_or_else(|e| e.into_inner());
+        for (file, summary) in files.iter().zip(summaries) {
+            if let Some(summary) = summary {
+                lines.push(format!(
+                    "- {} (+{} -{}): {}",
+                    file.path, file.added, file.removed, summary?
+                ));
+            }
+        }
+        Ok(lines.join("\n"))
+    }
+
+    /// The summary cache with the key of `file`'s summary and the summary
+    /// stored under it, if any. Files are told apart by their old and new
+    /// blobs, so a diff without `index` lines is never cached.
+    fn cached_summary(
+        &self,
+        file: &FileDiff,
+        prompt: &Prompt,
+    ) -> Option<(&Cache, String, Option<String>)> {
+        let (cache, model) = self.summary_cache.as_ref()?;
+        let (old, new) = (diff::old_blob(file), diff::new_blob(file));
+        if old.is_none() && new.is_none() {
+            return None;
+        }
+        let key = cache::key(&[
+            "summary",
+            model,
+            &prompt.system,
+            &file.path,
+            old.unwrap_or(""),
+            new.unwrap_or(""),
+        ]);
+        let summary = cache
+            .get(&key)
+            .and_then(|summaries| summaries.into_iter().next());
+        Some((cache, key, summary))
+    }
+
+    /// Asks for `count` independent suggestions for the same diff.
+    pub fn generate_candidates(
+        &self,
+        diff: &str,
+        count: usize,
+    ) -> Result<Vec<CommitMessage>, Box<dyn std::error::Error>> {
+        (0..count.max(1))
+            .map(|_| self.generate_for_diff(diff))
+            .collect()
+    }
+
+    /// Sends `diff` with the guideline and context like a commit message
+    /// prompt, and returns the raw reply, for outputs about a diff that are
+    /// not commit messages (reviews...).
+    pub fn generate_text_for_diff(&self, diff: &str) -> Result<String, Box<dyn std::error::Error>> {
+        let prompt = self.prompt(diff);
+        Ok(message::strip_wrapping(&self.provider.generate(&prompt)?))
+    }
+
+    /// Sends the guideline with arbitrary `content` and returns the raw reply,
+    /// for outputs that are not commit messages (release notes, summaries...).
+    pub fn generate_text(
+        &self,
+        title: &str,
+        content: &str,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let prompt = self.prompt_builder.build_section(title, content);
+        Ok(message::strip_wrapping(&self.provider.generate(&prompt)?))
+    }
+}
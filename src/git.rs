@@ -0,0 +1,214 @@
+use crate::i18n::{Message, tr};
+use crate::{encoding, log, timing};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// `git` running in `repo`, or in the current directory when `repo` is `None`.
+/// Paths are printed as they are instead of as quoted octal escapes.
+pub fn command(repo: Option<&Path>) -> Command {
+    let mut command = Command::new("git");
+    command.args(["-c", "core.quotePath=false"]);
+    if let Some(repo) = repo {
+        command.arg("-C").arg(repo);
+    }
+    command
+}
+
+/// Runs `git <args>` and returns its stdout, failing with git's stderr.
+pub fn output(repo: Option<&Path>, args: &[&str]) -> Result<String, Box<dyn std::error::Error>> {
+    Ok(encoding::to_string(output_bytes(repo, args)?))
+}
+
+/// Like [`output`], for binary output such as blobs.
+pub fn output_bytes(
+    repo: Option<&Path>,
+    args: &[&str],
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    log::debug(&format!("git {}", args.join(" ")));
+    let output = timing::measure("git", || command(repo).args(args).output())?;
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed ({}): {}",
+            args.join(" "),
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+    Ok(output.stdout)
+}
+
+/// Locates the repository for `repo` (or the current directory) the way git
+/// does: walking up from subdirectories, following the `.git` file of linked
+/// worktrees and honouring `GIT_DIR`. Returns the absolute git directory.
+pub fn git_dir(repo: Option<&Path>) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    match output(repo, &["rev-parse", "--absolute-git-dir"]) {
+        Ok(dir) => Ok(PathBuf::from(dir.trim())),
+        Err(_) => Err(tr(
+            Message::NotARepository,
+            &[&repo.unwrap_or(Path::new(".")).display()],
+        )
+        .into()),
+    }
+}
+
+/// The tree of `HEAD`, or git's empty tree when the branch has no commits yet
+/// so that the first commit diffs the index against nothing.
+pub fn head_tree(repo: Option<&Path>) -> Result<String, Box<dyn std::error::Error>> {
+    if let Ok(tree) = output(repo, &["rev-parse", "--verify", "--quiet", "HEAD^{tree}"]) {
+        return Ok(tree.trim().to_string());
+    }
+    empty_tree(repo)
+}
+
+/// git's empty tree, to diff a whole history against nothing.
+pub fn empty_tree(repo: Option<&Path>) -> Result<String, Box<dyn std::error::Error>> {
+    // Hashing empty input works for both SHA-1 and SHA-256 repositories.
+    let empty_tree = command(repo)
+        .args(["hash-object", "-t", "tree", "--stdin"])
+        .stdin(Stdio::null())
+        .output()?;
+    if !empty_tree.status.success() {
+        return Err(format!("git hash-object failed ({})", empty_tree.status).into());
+    }
+    Ok(String::from_utf8(empty_tree.stdout)?.trim().to_string())
+}
+
+/// The oldest commit of a shallow clone reachable from `HEAD`, or `None` in
+/// a complete clone. Its parents are not in the repository.
+pub fn shallow_boundary(repo: Option<&Path>) -> Option<String> {
+    let shallow = output(repo, &["rev-parse", "--is-shallow-repository"]).ok()?;
+    if shallow.trim() != "true" {
+        return None;
+    }
+    // The cut-off commits look like root commits; the last one is the oldest.
+    let roots = output(repo, &["rev-list", "--max-parents=0", "HEAD"]).ok()?;
+    roots.lines().last().map(str::to_string)
+}
+
+/// The other end of `from..to` or `from...to`, `HEAD` when it is left out.
+fn range_end(range: &str) -> &str {
+    match range.rsplit_once("..") {
+        Some((_, to)) => match to.trim_start_matches('.') {
+            "" => "HEAD",
+            to => to,
+        },
+        None => "HEAD",
+    }
+}
+
+/// `git diff <range>`. In a shallow clone that lacks the base of `range`,
+/// the diff starts at the shallow boundary instead, or at the empty tree when
+/// the boundary is the end of the range itself, with a warning.
+pub fn diff_range(repo: Option<&Path>, range: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let error = match output(repo, &["diff", range]) {
+        Ok(diff) => return Ok(diff),
+        Err(e) => e,
+    };
+    let Some(boundary) = shallow_boundary(repo) else {
+        return Err(error);
+    };
+    let to = range_end(range);
+    let end = output(repo, &["rev-parse", "--verify", "--quiet", to])?;
+    let (base, described) = if end.trim() == boundary {
+        (empty_tree(repo)?, "the whole tree".to_string())
+    } else {
+        let short = &boundary[..boundary.len().min(7)];
+        (boundary.clone(), format!("the changes since {}", short))
+    };
+    eprintln!(
+        "warning: {} is incomplete in this shallow clone; describing {} instead",
+        range, described
+    );
+    output(repo, &["diff", &base, to])
+}
+
+/// The top-level directory of the working tree.
+pub fn toplevel(repo: Option<&Path>) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(PathBuf::from(
+        output(repo, &["rev-parse", "--show-toplevel"])?.trim(),
+    ))
+}
+
+/// Resolves `name` inside the git directory (`index`, `SQUASH_MSG`, ...);
+/// `--git-path` also picks the per-worktree file of linked worktrees.
+pub fn git_path(repo: Option<&Path>, name: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let path = PathBuf::from(output(repo, &["rev-parse", "--git-path", name])?.trim());
+    Ok(match repo {
+        Some(repo) if path.is_relative() => repo.join(path),
+        _ => path,
+    })
+}
+
+/// The text of the file `commit.template` names, if it is set and readable.
+/// Like git, a relative path is taken from the current directory.
+pub fn commit_template(repo: Option<&Path>) -> Option<String> {
+    let path = output(repo, &["config", "--path", "--get", "commit.template"]).ok()?;
+    let path = PathBuf::from(path.trim());
+    let path = match repo {
+        Some(repo) if path.is_relative() => repo.join(path),
+        _ => path,
+    };
+    std::fs::read_to_string(path)
+        .ok()
+        .filter(|text| !text.trim().is_empty())
+}
+
+/// How `git commit` signs commits, e.g. `ssh key ~/.ssh/id_ed25519.pub`, or
+/// `None` unless `commit.gpgSign` is on.
+pub fn commit_signing(repo: Option<&Path>) -> Option<String> {
+    let config = |key| {
+        output(repo, &["config", "--get", key])
+            .ok()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    let enabled = output(repo, &["config", "--bool", "--get", "commit.gpgSign"]).ok()?;
+    if enabled.trim() != "true" {
+        return None;
+    }
+    let format = config("gpg.format").unwrap_or_else(|| "openpgp".to_string());
+    Some(match config("user.signingKey") {
+        Some(key) => format!("{} key {}", format, key),
+        None => format!("{} default key", format),
+    })
+}
+
+/// The program `git commit` signs with under `commit.gpgSign`, from
+/// `gpg.program` or its SSH and X.509 counterparts.
+pub fn signing_program(repo: Option<&Path>) -> String {
+    let config = |key| output(repo, &["config", "--get", key]).ok();
+    let format = config("gpg.format").unwrap_or_default();
+    let (key, default) = match format.trim() {
+        "ssh" => ("gpg.ssh.program", "ssh-keygen"),
+        "x509" => ("gpg.x509.program", "gpgsm"),
+        _ => ("gpg.program", "gpg"),
+    };
+    config(key)
+        .map(|program| program.trim().to_string())
+        .filter(|program| !program.is_empty())
+        .unwrap_or_else(|| default.to_string())
+}
+
+/// Whether `commit` carries an OpenPGP, SSH or X.509 signature.
+pub fn is_signed(repo: Option<&Path>, commit: &str) -> bool {
+    output(repo, &["cat-file", "commit", commit]).is_ok_and(|text| {
+        text.lines()
+            .take_while(|line| !line.is_empty())
+            .any(|line| line.starts_with("gpgsig ") || line.starts_with("gpgsig-sha256 "))
+    })
+}
+
+/// The subjects of the last `count` commits on the current branch, newest
+/// first; empty on a branch without commits.
+pub fn recent_subjects(
+    repo: Option<&Path>,
+    count: usize,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    if output(repo, &["rev-parse", "--verify", "--quiet", "HEAD"]).is_err() {
+        return Ok(Vec::new());
+    }
+    let count = format!("--max-count={}", count);
+    let log = output(repo, &["log", &count, "--no-merges", "--format=%s"])?;
+    Ok(log.lines().map(str::to_string).collect())
+}
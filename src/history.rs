@@ -0,0 +1,140 @@
+//! A local log of generated messages, next to the cache, so that earlier
+//! generations can be found again after the terminal is gone.
+//!
+//! Each line of `history.jsonl` is one [`Entry`]; only the most recent
+//! [`MAX_ENTRIES`] are kept.
+
+use crate::cache;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const MAX_ENTRIES: usize = 1000;
+
+/// What became of a generated message.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    /// Printed or copied; the user went on outside the tool.
+    Shown,
+    /// Picked and committed.
+    Chosen,
+    /// A candidate passed over for another one.
+    Rejected,
+}
+
+impl Status {
+    pub fn name(self) -> &'static str {
+        match self {
+            Status::Shown => "shown",
+            Status::Chosen => "chosen",
+            Status::Rejected => "rejected",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Entry {
+    /// Seconds since the Unix epoch.
+    pub time: u64,
+    /// The top of the work tree, empty for a diff read from stdin.
+    pub repo: String,
+    /// Hash of the diff the message describes.
+    pub diff: String,
+    pub message: String,
+    pub status: Status,
+}
+
+impl Entry {
+    pub fn new(repo: &str, diff: &str, message: &str, status: Status) -> Self {
+        Self {
+            time: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+            repo: repo.to_string(),
+            diff: cache::key(&[&cache::normalize_diff(diff)]),
+            message: message.to_string(),
+            status,
+        }
+    }
+
+    /// `YYYY-MM-DD HH:MM` (UTC) of [`Entry::time`].
+    pub fn date(&self) -> String {
+        let days = (self.time / 86_400) as i64;
+        let seconds = self.time % 86_400;
+        // Howard Hinnant's days-to-civil algorithm.
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + i64::from(month <= 2);
+        format!(
+            "{:04}-{:02}-{:02} {:02}:{:02}",
+            year,
+            month,
+            day,
+            seconds / 3600,
+            seconds / 60 % 60
+        )
+    }
+}
+
+pub struct History {
+    path: PathBuf,
+}
+
+impl History {
+    /// The history in the user's cache directory, if one can be determined.
+    pub fn open() -> Option<Self> {
+        Some(Self::at(
+            cache::cache_home()?
+                .join(env!("CARGO_PKG_NAME"))
+                .join("history.jsonl"),
+        ))
+    }
+
+    pub fn at(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Every entry, oldest first; unreadable lines are skipped.
+    pub fn entries(&self) -> Vec<Entry> {
+        fs::read_to_string(&self.path)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect()
+    }
+
+    /// Appends `entries`, dropping the oldest beyond [`MAX_ENTRIES`].
+    pub fn record(&self, entries: &[Entry]) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut all = self.entries();
+        if all.len() + entries.len() <= MAX_ENTRIES {
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+            for entry in entries {
+                writeln!(file, "{}", serde_json::to_string(entry)?)?;
+            }
+            return Ok(());
+        }
+        all.extend_from_slice(entries);
+        let mut text = String::new();
+        for entry in &all[all.len().saturating_sub(MAX_ENTRIES)..] {
+            text.push_str(&serde_json::to_string(entry)?);
+            text.push('\n');
+        }
+        fs::write(&self.path, text)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,404 @@
+//! Translations of the messages the tool prints itself (not the generated
+//! commit messages). The language comes from `git config gemini.uiLanguage`
+//! or the usual `LC_ALL` / `LC_MESSAGES` / `LANG` variables.
+
+use std::env;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    English,
+    Japanese,
+}
+
+impl Language {
+    /// Understands locale names such as `ja_JP.UTF-8` as well as `ja` or
+    /// `japanese`; anything else is English.
+    pub fn parse(tag: &str) -> Self {
+        let tag = tag.trim().to_lowercase();
+        if tag.starts_with("ja") {
+            Language::Japanese
+        } else {
+            Language::English
+        }
+    }
+
+    /// The configured language, else the first locale variable that is set.
+    pub fn detect(configured: Option<&str>) -> Self {
+        if let Some(tag) = configured.filter(|tag| !tag.trim().is_empty()) {
+            return Self::parse(tag);
+        }
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .find_map(|name| env::var(name).ok().filter(|v| !v.is_empty()))
+            .map_or(Language::English, |tag| Self::parse(&tag))
+    }
+}
+
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+pub fn set_language(language: Language) {
+    CURRENT.store(language as u8, Ordering::Relaxed);
+}
+
+pub fn language() -> Language {
+    match CURRENT.load(Ordering::Relaxed) {
+        1 => Language::Japanese,
+        _ => Language::English,
+    }
+}
+
+/// Every translated message; `{}` in a template is replaced by the
+/// arguments passed to [`tr`], in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Message {
+    NothingToCommit,
+    NotARepository,
+    NoApiKey,
+    UnsupportedProvider,
+    InvalidThinkingBudget,
+    ModelsNeedGemini,
+    InvalidConcurrency,
+    InvalidCandidates,
+    InvalidMaxDiffBytes,
+    InvalidRateLimit,
+    MissingFlagValue,
+    UnreadableGuideline,
+    DiffOverLimit,
+    OverLimitChunked,
+    OverLimitTrimmed,
+    InvalidRecentCommits,
+    InvalidBoolean,
+    UnsupportedMood,
+    LintFailed,
+    UnsupportedStyle,
+    SubjectTooLong,
+    SubjectFullStop,
+    NotKernelStyle,
+    UnsupportedPreset,
+    InvalidTrailer,
+    NeedsConventional,
+    ScopeConflict,
+    TypeNotAllowed,
+    MissingPrefix,
+    ReadDiffFailed,
+    ClipboardFailed,
+    ApiStatus,
+    NoText,
+    OutputLimit,
+    NotACommitMessage,
+    UnknownFinishReason,
+    NoPromptFeedback,
+    PromptFeedback,
+    WatchWithStdin,
+    CommitWithStdin,
+    NotesWithoutCommit,
+    NotConventional,
+    ConfirmSend,
+    Aborted,
+    ChooseCandidate,
+    EnterNumber,
+    CommitGroup,
+    CommitGroupFailed,
+    SignedCommitFailed,
+    CommitNotSigned,
+    NotStaged,
+    LeftStaged,
+    FixupOffer,
+    FixupHint,
+    NoFixupTarget,
+    SelectHunks,
+    SelectNeedsTerminal,
+    InvalidUnstaged,
+    UnstagedOffer,
+    UnstagedCommitOffer,
+    StagedTracked,
+}
+
+fn template(language: Language, message: Message) -> &'static str {
+    use Language::*;
+    use Message::*;
+    match (language, message) {
+        (English, NothingToCommit) => "Nothing to commit",
+        (Japanese, NothingToCommit) => "コミットする変更がありません",
+        (English, NotARepository) => "not a git repository (or any of the parent directories): {}",
+        (Japanese, NotARepository) => "git リポジトリではありません (親ディレクトリも含む): {}",
+        (English, NoApiKey) => {
+            "No API key provided. Provide it via --api-key, -k, positional arg, set GEMINI_API_KEY in environment (.env is optional), set git config gemini.apikey, or run `auth set`."
+        }
+        (Japanese, NoApiKey) => {
+            "API キーがありません。--api-key、-k、位置引数、環境変数 GEMINI_API_KEY (.env も可)、git config gemini.apikey のいずれかで指定するか、`auth set` を実行してください。"
+        }
+        (English, UnsupportedProvider) => {
+            "Unsupported provider '{}' ({}). Supported providers: gemini, mock, exec:<command>"
+        }
+        (Japanese, UnsupportedProvider) => {
+            "未対応のプロバイダー '{}' です ({})。対応プロバイダー: gemini, mock, exec:<コマンド>"
+        }
+        (English, InvalidThinkingBudget) => {
+            "Invalid thinking budget '{}' ({}). Use a number of tokens, off or auto"
+        }
+        (Japanese, InvalidThinkingBudget) => {
+            "思考予算 '{}' が不正です ({})。トークン数、off、auto のいずれかを指定してください"
+        }
+        (English, ModelsNeedGemini) => "models lists Gemini models, but the provider is '{}' ({})",
+        (Japanese, ModelsNeedGemini) => {
+            "models は Gemini のモデルを一覧しますが、プロバイダーが '{}' です ({})"
+        }
+        (English, InvalidConcurrency) => {
+            "Invalid concurrency '{}' ({}). Use a positive number of requests"
+        }
+        (Japanese, InvalidConcurrency) => {
+            "同時実行数 '{}' が不正です ({})。1 以上の数を指定してください"
+        }
+        (English, InvalidCandidates) => {
+            "Invalid number of candidates '{}' ({}). Use a positive number"
+        }
+        (Japanese, InvalidCandidates) => {
+            "候補数 '{}' が不正です ({})。1 以上の数を指定してください"
+        }
+        (English, InvalidMaxDiffBytes) => {
+            "Invalid maxDiffBytes '{}' ({}). Use a positive size in bytes, e.g. 400000, 400k or 1M"
+        }
+        (Japanese, InvalidMaxDiffBytes) => {
+            "maxDiffBytes '{}' が不正です ({})。400000、400k、1M のような正のサイズを指定してください"
+        }
+        (English, MissingFlagValue) => "{} needs a value",
+        (Japanese, MissingFlagValue) => "{} には値が必要です",
+        (English, InvalidRateLimit) => {
+            "Invalid rate limit '{}' ({}). Use a positive number per minute, e.g. 15"
+        }
+        (Japanese, InvalidRateLimit) => {
+            "レート制限 '{}' が不正です ({})。15 のような 1 分あたりの正の数を指定してください"
+        }
+        (English, UnreadableGuideline) => "Cannot read the guideline file '{}' ({}): {}",
+        (Japanese, UnreadableGuideline) => "ガイドラインファイル '{}' を読み込めません ({}): {}",
+        (English, DiffOverLimit) => {
+            "The diff is {}, over the limit of {} (gemini.maxDiffBytes). Largest files:"
+        }
+        (Japanese, DiffOverLimit) => {
+            "差分が {} あり、上限の {} (gemini.maxDiffBytes) を超えています。大きいファイル:"
+        }
+        (English, OverLimitChunked) => {
+            "--chunked summarizes each file on its own, so every file is still described."
+        }
+        (Japanese, OverLimitChunked) => {
+            "--chunked によりファイルごとに要約するため、すべてのファイルが説明されます。"
+        }
+        (English, OverLimitTrimmed) => {
+            "Files are sent by importance (code, configuration, docs, assets). Cut short: {}. \
+             Left out: {}. Use --chunked to describe every file, or raise gemini.maxDiffBytes."
+        }
+        (Japanese, OverLimitTrimmed) => {
+            "重要度の順 (コード、設定、ドキュメント、アセット) に送信します。一部のみ: {}。除外: {}。\
+             すべてを説明するには --chunked を使うか、gemini.maxDiffBytes を増やしてください。"
+        }
+        (English, InvalidRecentCommits) => {
+            "Invalid number of recent commits '{}' ({}). Use 0 or more"
+        }
+        (Japanese, InvalidRecentCommits) => {
+            "最近のコミット数 '{}' が不正です ({})。0 以上の数を指定してください"
+        }
+        (English, InvalidBoolean) => "Invalid value '{}' ({}). Use true or false",
+        (Japanese, InvalidBoolean) => "値 '{}' が不正です ({})。true か false を指定してください",
+        (English, UnsupportedMood) => "Unsupported mood '{}' ({}). Use one of: {}",
+        (Japanese, UnsupportedMood) => {
+            "未対応の mood '{}' です ({})。次のいずれかを指定してください: {}"
+        }
+        (English, LintFailed) => "commit message has {} problem(s)",
+        (Japanese, LintFailed) => "コミットメッセージに {} 件の問題があります",
+        (English, UnsupportedStyle) => {
+            "Unsupported style '{}' ({}). Supported styles: conventional, plain, kernel"
+        }
+        (Japanese, UnsupportedStyle) => {
+            "未対応のスタイル '{}' です ({})。対応スタイル: conventional, plain, kernel"
+        }
+        (English, SubjectTooLong) => "generated subject is longer than {} characters: {}",
+        (Japanese, SubjectTooLong) => "生成された件名が {} 文字を超えています: {}",
+        (English, SubjectFullStop) => "generated subject ends with a period: {}",
+        (Japanese, SubjectFullStop) => "生成された件名がピリオドで終わっています: {}",
+        (English, NotKernelStyle) => "generated header does not follow \"subsystem: summary\": {}",
+        (Japanese, NotKernelStyle) => {
+            "生成されたヘッダーが \"subsystem: summary\" 形式に従っていません: {}"
+        }
+        (English, UnsupportedPreset) => {
+            "Unknown preset '{}' ({}). Built-in presets: {}; custom ones are read from {}"
+        }
+        (Japanese, UnsupportedPreset) => {
+            "不明なプリセット '{}' です ({})。組み込みプリセット: {}。カスタムプリセットは {} から読み込みます"
+        }
+        (English, InvalidTrailer) => "Invalid trailer '{}' ({}). Expected \"Token: value\"",
+        (Japanese, InvalidTrailer) => {
+            "トレーラー '{}' が不正です ({})。\"Token: value\" の形式で指定してください"
+        }
+        (English, NeedsConventional) => {
+            "{} only applies to Conventional Commits, not to the '{}' preset"
+        }
+        (Japanese, NeedsConventional) => {
+            "{} は Conventional Commits でのみ使えます ('{}' プリセットでは使えません)"
+        }
+        (English, ScopeConflict) => "--scope and --no-scope cannot be used together",
+        (Japanese, ScopeConflict) => "--scope と --no-scope は同時に指定できません",
+        (English, TypeNotAllowed) => "generated type '{}' is not one of {}",
+        (Japanese, TypeNotAllowed) => "生成された type '{}' が {} のいずれでもありません",
+        (English, MissingPrefix) => "generated header does not start as the {} preset requires: {}",
+        (Japanese, MissingPrefix) => {
+            "生成されたヘッダーが {} プリセットの求める形で始まっていません: {}"
+        }
+        (English, ReadDiffFailed) => "failed to read the diff: {}",
+        (Japanese, ReadDiffFailed) => "差分を読み込めませんでした: {}",
+        (English, ClipboardFailed) => "failed to copy to the clipboard: {}",
+        (Japanese, ClipboardFailed) => "クリップボードにコピーできませんでした: {}",
+        (English, ApiStatus) => "Gemini API returned {}: {}",
+        (Japanese, ApiStatus) => "Gemini API がエラーを返しました ({}): {}",
+        (English, NoText) => {
+            "Gemini API returned no usable text.\nReason: finish_reason='{}'\nDetails: {}"
+        }
+        (English, NotACommitMessage) => {
+            "The answer is not a commit message ({}); the diff may contain text that steers \
+             the model. Check it with --review or try again"
+        }
+        (Japanese, NotACommitMessage) => {
+            "回答がコミットメッセージになっていません ({})。差分にモデルを誘導する文章が含まれている可能性があります。--review で確認するか、もう一度実行してください"
+        }
+        (English, OutputLimit) => {
+            "The answer was still cut off by the output limit after {} continuations; \
+             try --chunked or a smaller diff"
+        }
+        (Japanese, OutputLimit) => {
+            "{} 回続きを求めても回答が出力上限で途切れました。--chunked を使うか、差分を小さくしてください"
+        }
+        (Japanese, NoText) => {
+            "Gemini APIは有効なテキストを返しませんでした。\n原因: finish_reason='{}'\n詳細: {}"
+        }
+        (English, UnknownFinishReason) => "unknown (no candidates or unexpected structure)",
+        (Japanese, UnknownFinishReason) => "不明 (candidatesが空か構造不正)",
+        (English, NoPromptFeedback) => "no prompt feedback",
+        (Japanese, NoPromptFeedback) => "プロンプトフィードバックなし",
+        (English, PromptFeedback) => "prompt feedback: {}",
+        (Japanese, PromptFeedback) => "プロンプトフィードバック: {}",
+        (English, WatchWithStdin) => {
+            "--watch follows the index and cannot be combined with --stdin"
+        }
+        (Japanese, WatchWithStdin) => {
+            "--watch はインデックスを監視するため --stdin と併用できません"
+        }
+        (English, CommitWithStdin) => {
+            "--commit and --split commit the index and cannot be combined with --stdin"
+        }
+        (Japanese, CommitWithStdin) => {
+            "--commit と --split はインデックスをコミットするため --stdin と併用できません"
+        }
+        (English, NotesWithoutCommit) => {
+            "--notes records the candidates of a new commit and needs --commit"
+        }
+        (Japanese, NotesWithoutCommit) => {
+            "--notes は新しいコミットに候補を記録するため --commit が必要です"
+        }
+        (English, NotConventional) => "generated header does not follow Conventional Commits: {}",
+        (Japanese, NotConventional) => {
+            "生成されたヘッダーが Conventional Commits に従っていません: {}"
+        }
+        (English, ConfirmSend) => "Send this to {}? [y]es, [N]o, show the full [p]rompt: ",
+        (Japanese, ConfirmSend) => {
+            "{} に送信しますか? [y] はい、[N] いいえ、[p] プロンプト全体を表示: "
+        }
+        (English, Aborted) => "aborted, nothing was sent",
+        (Japanese, Aborted) => "中止しました。何も送信していません",
+        (English, ChooseCandidate) => "Commit with candidate [1-{}]: ",
+        (Japanese, ChooseCandidate) => "コミットに使う候補 [1-{}]: ",
+        (English, EnterNumber) => "enter a number between 1 and {}",
+        (Japanese, EnterNumber) => "1 から {} までの番号を入力してください",
+        (English, CommitGroup) => "Commit this group? [Y]es, [e]dit, [s]kip, [q]uit: ",
+        (Japanese, CommitGroup) => {
+            "このグループをコミットしますか? [Y] はい、[e] 編集、[s] スキップ、[q] 終了: "
+        }
+        (English, SignedCommitFailed) => {
+            "git commit failed ({}) while signing with the {}; check the key and \
+             `git config gpg.program` (or gpg.ssh.program), or run `doctor`"
+        }
+        (Japanese, SignedCommitFailed) => {
+            "署名付きの git commit が失敗しました ({}、{})。鍵と `git config gpg.program` (または gpg.ssh.program) を確認するか、`doctor` を実行してください"
+        }
+        (English, CommitNotSigned) => {
+            "warning: commit.gpgSign is on but the new commit is not signed ({})"
+        }
+        (Japanese, CommitNotSigned) => {
+            "警告: commit.gpgSign が有効ですが、新しいコミットは署名されていません ({})"
+        }
+        (English, CommitGroupFailed) => "commit failed or aborted; group skipped",
+        (Japanese, CommitGroupFailed) => {
+            "コミットが失敗または中止されたため、このグループをスキップしました"
+        }
+        (English, NotStaged) => "ignoring {}: not a staged file",
+        (Japanese, NotStaged) => "{} を無視します: ステージされたファイルではありません",
+        (English, LeftStaged) => "Left staged:",
+        (Japanese, LeftStaged) => "ステージされたまま残したファイル:",
+        (English, FixupOffer) => {
+            "The staged changes rework {} \"{}\", which is not pushed yet. Commit them as a fixup! instead? [y/N]: "
+        }
+        (Japanese, FixupOffer) => {
+            "ステージされた変更は未プッシュのコミット {} \"{}\" の修正です。代わりに fixup! としてコミットしますか? [y/N]: "
+        }
+        (English, FixupHint) => {
+            "hint: the staged changes rework {} \"{}\", which is not pushed yet; --fixup makes them a fixup! commit"
+        }
+        (Japanese, FixupHint) => {
+            "ヒント: ステージされた変更は未プッシュのコミット {} \"{}\" の修正です。--fixup で fixup! コミットにできます"
+        }
+        (English, InvalidUnstaged) => "Invalid value '{}' ({}). Use ask, stage, describe or never",
+        (Japanese, InvalidUnstaged) => {
+            "値 '{}' が不正です ({})。ask、stage、describe、never のいずれかを指定してください"
+        }
+        (English, UnstagedOffer) => {
+            "Nothing is staged, but {} tracked file(s) have changes. [s]tage them (git add -u), [d]escribe them without staging, or [N]o: "
+        }
+        (Japanese, UnstagedOffer) => {
+            "ステージされた変更はありませんが、{} 個の追跡ファイルに変更があります。[s] ステージする (git add -u)、[d] ステージせずに説明する、[N] やめる: "
+        }
+        (English, UnstagedCommitOffer) => {
+            "Nothing is staged, but {} tracked file(s) have changes. Stage them (git add -u) and commit? [y/N]: "
+        }
+        (Japanese, UnstagedCommitOffer) => {
+            "ステージされた変更はありませんが、{} 個の追跡ファイルに変更があります。ステージ (git add -u) してコミットしますか? [y/N]: "
+        }
+        (English, StagedTracked) => "staged the changes of {} tracked file(s)",
+        (Japanese, StagedTracked) => "{} 個の追跡ファイルの変更をステージしました",
+        (English, SelectHunks) => {
+            "Toggle hunks to leave out of the prompt (e.g. 2 4-6, a for all, n for none), Enter to continue: "
+        }
+        (Japanese, SelectHunks) => {
+            "プロンプトから外すハンクを切り替えてください (例: 2 4-6、a で全て、n で無し)。Enter で続行: "
+        }
+        (English, SelectNeedsTerminal) => {
+            "--interactive-select asks on a terminal and cannot be combined with --stdin"
+        }
+        (Japanese, SelectNeedsTerminal) => {
+            "--interactive-select は端末で確認するため、--stdin とは併用できません"
+        }
+        (English, NoFixupTarget) => {
+            "--fixup: no single unpushed commit introduced the changed lines, writing a new message"
+        }
+        (Japanese, NoFixupTarget) => {
+            "--fixup: 変更された行を追加した未プッシュのコミットが一つに定まらないため、新しいメッセージを生成します"
+        }
+    }
+}
+
+/// The message in the current language with `args` filled in.
+pub fn tr(message: Message, args: &[&dyn Display]) -> String {
+    let mut text = String::new();
+    let mut args = args.iter();
+    let mut parts = template(language(), message).split("{}");
+    if let Some(first) = parts.next() {
+        text.push_str(first);
+    }
+    for part in parts {
+        if let Some(arg) = args.next() {
+            text.push_str(&arg.to_string());
+        }
+        text.push_str(part);
+    }
+    text
+}
@@ -0,0 +1,146 @@
+//! Corrections: generated messages next to what was actually committed
+//! after editing them. The latest ones of a repository are shown to the
+//! model so that its messages drift towards the author's own style.
+
+use crate::history::{Entry, History, Status};
+use crate::{cache, git};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Corrections the command line tool shows the model.
+pub const DEFAULT_CORRECTIONS: usize = 3;
+
+/// Corrections kept in the file, across repositories.
+const MAX_CORRECTIONS: usize = 200;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Correction {
+    /// Seconds since the Unix epoch.
+    pub time: u64,
+    /// The top of the work tree.
+    pub repo: String,
+    pub generated: String,
+    pub committed: String,
+}
+
+/// `message` without comments, sign-offs and whitespace differences, which
+/// say nothing about the author's style.
+fn normalize(message: &str) -> String {
+    message
+        .lines()
+        .filter(|line| !line.starts_with('#') && !line.starts_with("Signed-off-by:"))
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// Whether `committed` is an edit of `generated` rather than the same text.
+pub fn is_edit(generated: &str, committed: &str) -> bool {
+    let committed = normalize(committed);
+    !committed.is_empty() && normalize(generated) != committed
+}
+
+pub struct Corrections {
+    path: PathBuf,
+}
+
+impl Corrections {
+    /// The corrections in the user's cache directory, if one can be determined.
+    pub fn open() -> Option<Self> {
+        Some(Self::at(
+            cache::cache_home()?
+                .join(env!("CARGO_PKG_NAME"))
+                .join("corrections.json"),
+        ))
+    }
+
+    pub fn at(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Every correction, oldest first.
+    pub fn all(&self) -> Vec<Correction> {
+        fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn record(&self, correction: Correction) -> Result<(), Box<dyn std::error::Error>> {
+        let mut all = self.all();
+        all.retain(|c| c.repo != correction.repo || c.generated != correction.generated);
+        all.push(correction);
+        let keep = all.len().saturating_sub(MAX_CORRECTIONS);
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, serde_json::to_string(&all[keep..])?)?;
+        Ok(())
+    }
+
+    /// The latest `count` corrections made in `repo`, newest first.
+    pub fn recent(&self, repo: &str, count: usize) -> Vec<Correction> {
+        self.all()
+            .into_iter()
+            .rev()
+            .filter(|c| c.repo == repo)
+            .take(count)
+            .collect()
+    }
+}
+
+/// The corrections as a prompt section.
+pub fn prompt_text(corrections: &[Correction]) -> String {
+    let mut text = "The author edited these earlier messages before committing them. \
+                    Follow the author's version in wording, detail and format.\n"
+        .to_string();
+    for correction in corrections {
+        text.push_str(&format!(
+            "\nGenerated:\n{}\n\nCommitted instead:\n{}\n",
+            correction.generated.trim(),
+            correction.committed.trim()
+        ));
+    }
+    text
+}
+
+/// Compares the message of `HEAD` with the one generated for the same diff
+/// and records the pair when it was edited. Returns what was recorded.
+pub fn learn(repo: Option<&Path>) -> Result<Option<Correction>, Box<dyn std::error::Error>> {
+    let (Some(history), Some(corrections)) = (History::open(), Corrections::open()) else {
+        return Ok(None);
+    };
+    let toplevel = git::toplevel(repo)?.display().to_string();
+    // The same diff as the staged one it was generated for, even for a root commit.
+    let parent = git::output(repo, &["rev-parse", "--verify", "--quiet", "HEAD^"])
+        .map(|rev| rev.trim().to_string())
+        .or_else(|_| git::empty_tree(repo))?;
+    let diff = git::output(repo, &["diff", &parent, "HEAD"])?;
+    let hash = Entry::new(&toplevel, &diff, "", Status::Shown).diff;
+    let Some(entry) = history
+        .entries()
+        .into_iter()
+        .rev()
+        .find(|e| e.repo == toplevel && e.diff == hash && e.status != Status::Rejected)
+    else {
+        return Ok(None);
+    };
+    let committed = git::output(repo, &["log", "-1", "--format=%B"])?;
+    if !is_edit(&entry.message, &committed) {
+        return Ok(None);
+    }
+    let correction = Correction {
+        time: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs()),
+        repo: toplevel,
+        generated: entry.message,
+        committed: normalize(&committed),
+    };
+    corrections.record(correction.clone())?;
+    Ok(Some(correction))
+}
@@ -0,0 +1,44 @@
+//! Generate Conventional Commits messages for git changes with Gemini.
+//!
+//! The binary is a thin wrapper around [`Generator`]; other tools can embed
+//! the same pipeline without shelling out to it.
+
+pub mod auth;
+pub mod cache;
+pub mod changelog;
+pub mod commits;
+pub mod config;
+pub mod diff;
+pub mod encoding;
+pub mod error;
+pub mod fixup;
+pub mod forge;
+pub mod generator;
+pub mod git;
+pub mod history;
+pub mod i18n;
+pub mod learn;
+pub mod lint;
+pub mod log;
+pub mod message;
+pub mod notebook;
+pub mod preset;
+pub mod prompt;
+pub mod provider;
+pub mod rate;
+pub mod renames;
+pub mod repository;
+pub mod score;
+pub mod split;
+pub mod style;
+pub mod subject;
+pub mod timing;
+pub mod update;
+pub mod version;
+
+pub use diff::DiffSource;
+pub use error::Error;
+pub use generator::Generator;
+pub use message::CommitMessage;
+pub use prompt::{Prompt, PromptBuilder};
+pub use provider::{Exec, Gemini, Mock, Provider};
@@ -0,0 +1,312 @@
+//! Checks a written commit message against Conventional Commits and the
+//! rules of a commitlint configuration, as a `commit-msg` hook would.
+
+use crate::message::CommitMessage;
+use serde_json::{Value, json};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Files searched for a commitlint configuration, in order. JavaScript and
+/// YAML configurations cannot be read and are reported instead.
+const CONFIG_FILES: &[&str] = &[".commitlintrc", ".commitlintrc.json", "package.json"];
+const UNREADABLE_CONFIG_FILES: &[&str] = &[
+    "commitlint.config.js",
+    "commitlint.config.cjs",
+    "commitlint.config.mjs",
+    "commitlint.config.ts",
+    ".commitlintrc.js",
+    ".commitlintrc.cjs",
+    ".commitlintrc.yml",
+    ".commitlintrc.yaml",
+];
+
+/// Headers commitlint does not check by default.
+const IGNORED_PREFIXES: &[&str] = &[
+    "Merge ",
+    "Revert \"",
+    "fixup! ",
+    "squash! ",
+    "amend! ",
+    "Automatic merge",
+    "Auto-merged",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct Problem {
+    pub severity: Severity,
+    pub rule: String,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mark = match self.severity {
+            Severity::Error => "✖",
+            Severity::Warning => "⚠",
+        };
+        write!(f, "{}   {} [{}]", mark, self.message, self.rule)
+    }
+}
+
+/// One `"name": [level, "always" | "never", value]` entry.
+#[derive(Debug, Clone)]
+struct Rule {
+    name: String,
+    severity: Severity,
+    always: bool,
+    value: Value,
+}
+
+/// The rules of a commitlint configuration; without one, only the
+/// Conventional Commits header format is checked.
+#[derive(Debug, Clone, Default)]
+pub struct Rules {
+    rules: Vec<Rule>,
+    /// The file the rules were read from.
+    pub source: Option<PathBuf>,
+}
+
+/// The rules of `@commitlint/config-conventional`.
+fn conventional_rules() -> Value {
+    json!({
+        "body-max-line-length": [2, "always", 100],
+        "footer-max-line-length": [2, "always", 100],
+        "header-max-length": [2, "always", 100],
+        "subject-case": [2, "never", ["sentence-case", "start-case", "pascal-case", "upper-case"]],
+        "subject-empty": [2, "never"],
+        "subject-full-stop": [2, "never", "."],
+        "type-case": [2, "always", "lower-case"],
+        "type-empty": [2, "never"],
+        "type-enum": [2, "always", [
+            "build", "chore", "ci", "docs", "feat", "fix", "perf", "refactor", "revert", "style", "test"
+        ]]
+    })
+}
+
+impl Rules {
+    /// Reads the rules from a commitlint configuration object.
+    pub fn from_config(config: &Value) -> Self {
+        let mut rules = Self::default();
+        let extends = match &config["extends"] {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if extends
+            .iter()
+            .any(|name| name.contains("config-conventional"))
+        {
+            rules.add(&conventional_rules());
+        }
+        rules.add(&config["rules"]);
+        rules
+    }
+
+    fn add(&mut self, rules: &Value) {
+        let Some(rules) = rules.as_object() else {
+            return;
+        };
+        for (name, rule) in rules {
+            self.rules.retain(|r| &r.name != name);
+            let severity = match rule[0].as_u64() {
+                Some(2) => Severity::Error,
+                Some(1) => Severity::Warning,
+                _ => continue,
+            };
+            self.rules.push(Rule {
+                name: name.clone(),
+                severity,
+                always: rule[1].as_str() != Some("never"),
+                value: rule[2].clone(),
+            });
+        }
+    }
+
+    /// The first JSON commitlint configuration in `root`, if any.
+    pub fn load(root: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        for name in CONFIG_FILES {
+            let path = root.join(name);
+            let Ok(text) = fs::read_to_string(&path) else {
+                continue;
+            };
+            let value: Value = serde_json::from_str(&text)
+                .map_err(|e| format!("cannot parse {}: {}", path.display(), e))?;
+            let config = if *name == "package.json" {
+                match value.get("commitlint") {
+                    Some(config) => config.clone(),
+                    None => continue,
+                }
+            } else {
+                value
+            };
+            return Ok(Self {
+                source: Some(path),
+                ..Self::from_config(&config)
+            });
+        }
+        Ok(Self::default())
+    }
+
+    /// A configuration in `root` in a format that cannot be read.
+    pub fn unreadable_config(root: &Path) -> Option<PathBuf> {
+        UNREADABLE_CONFIG_FILES
+            .iter()
+            .map(|name| root.join(name))
+            .find(|path| path.exists())
+    }
+}
+
+/// The message as git will record it: without comment lines and without
+/// the diff below the scissors line of `git commit --verbose`.
+pub fn strip_comments(text: &str, comment_char: char) -> String {
+    let scissors = format!(
+        "{} ------------------------ >8 ------------------------",
+        comment_char
+    );
+    text.lines()
+        .take_while(|line| *line != scissors)
+        .filter(|line| !line.starts_with(comment_char))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// Whether `text` is in the commitlint case `case`.
+fn has_case(text: &str, case: &str) -> bool {
+    let first_upper = text.chars().next().is_some_and(char::is_uppercase);
+    let words = || text.split_whitespace();
+    match case {
+        "lower-case" | "lowercase" => text == text.to_lowercase(),
+        "upper-case" | "uppercase" => text == text.to_uppercase(),
+        "sentence-case" | "sentencecase" => {
+            let rest: String = text.chars().skip(1).collect();
+            first_upper && rest == rest.to_lowercase()
+        }
+        "start-case" | "startcase" => {
+            words().all(|word| word.chars().next().is_some_and(char::is_uppercase))
+        }
+        "pascal-case" | "pascalcase" => {
+            first_upper && !text.contains([' ', '-', '_']) && text != text.to_uppercase()
+        }
+        "camel-case" | "camelcase" => {
+            !first_upper && !text.contains([' ', '-', '_']) && text != text.to_lowercase()
+        }
+        "kebab-case" | "kebabcase" => text == text.to_lowercase() && !text.contains([' ', '_']),
+        "snake-case" | "snakecase" => text == text.to_lowercase() && !text.contains([' ', '-']),
+        _ => true,
+    }
+}
+
+fn strings(value: &Value) -> Vec<&str> {
+    match value {
+        Value::String(s) => vec![s.as_str()],
+        Value::Array(items) => items.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn longest_line(text: Option<&str>) -> usize {
+    text.map_or(0, |text| {
+        text.lines().map(|l| l.chars().count()).max().unwrap_or(0)
+    })
+}
+
+/// Every problem of `message` under `rules`; merges, reverts and fixups pass.
+pub fn lint(message: &CommitMessage, rules: &Rules) -> Vec<Problem> {
+    if IGNORED_PREFIXES
+        .iter()
+        .any(|p| message.header.starts_with(p))
+    {
+        return Vec::new();
+    }
+    let Some(header) = message.conventional() else {
+        return vec![Problem {
+            severity: Severity::Error,
+            rule: "header-format".to_string(),
+            message: format!(
+                "header must be \"type(scope): subject\": {}",
+                message.header
+            ),
+        }];
+    };
+    let footer = (!message.footers.is_empty()).then(|| {
+        message
+            .footers
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n")
+    });
+
+    let mut problems = Vec::new();
+    for rule in &rules.rules {
+        let values = strings(&rule.value);
+        let limit = rule.value.as_u64().unwrap_or(u64::MAX) as usize;
+        // `holds` is whether the "always" form of the rule is satisfied.
+        let (holds, what) = match rule.name.as_str() {
+            "type-enum" => (
+                values.contains(&header.kind),
+                format!("type must be one of [{}]", values.join(", ")),
+            ),
+            "type-case" => (
+                values.iter().all(|case| has_case(header.kind, case)),
+                format!("type must be {}", values.join(", ")),
+            ),
+            "type-empty" => (header.kind.is_empty(), "type must be empty".to_string()),
+            "scope-enum" => (
+                header.scope.is_none_or(|scope| {
+                    scope.split([',', '/']).all(|s| values.contains(&s.trim()))
+                }),
+                format!("scope must be one of [{}]", values.join(", ")),
+            ),
+            "scope-empty" => (header.scope.is_none(), "scope must be empty".to_string()),
+            "subject-empty" => (
+                header.description.trim().is_empty(),
+                "subject must be empty".to_string(),
+            ),
+            "subject-case" => (
+                values.iter().any(|case| has_case(header.description, case)),
+                format!("subject must be {}", values.join(", ")),
+            ),
+            "subject-full-stop" => (
+                values
+                    .first()
+                    .is_some_and(|stop| header.description.ends_with(stop)),
+                format!("subject must end with \"{}\"", values.join("")),
+            ),
+            "header-max-length" => (
+                message.header.chars().count() <= limit,
+                format!("header must not be longer than {} characters", limit),
+            ),
+            "body-max-line-length" => (
+                longest_line(message.body.as_deref()) <= limit,
+                format!("body lines must not be longer than {} characters", limit),
+            ),
+            "footer-max-line-length" => (
+                longest_line(footer.as_deref()) <= limit,
+                format!("footer lines must not be longer than {} characters", limit),
+            ),
+            _ => continue,
+        };
+        if holds != rule.always {
+            let message = match rule.always {
+                true => what,
+                false => what.replacen(" must ", " must not ", 1),
+            };
+            problems.push(Problem {
+                severity: rule.severity,
+                rule: rule.name.clone(),
+                message,
+            });
+        }
+    }
+    problems
+}
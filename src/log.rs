@@ -0,0 +1,63 @@
+//! Leveled diagnostics on stderr (`-v`, `-vv`) and a `--debug` dump file
+//! that also receives full request and response bodies.
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const INFO: u8 = 1;
+pub const DEBUG: u8 = 2;
+
+static LEVEL: AtomicU8 = AtomicU8::new(0);
+static DEBUG_FILE: Mutex<Option<File>> = Mutex::new(None);
+
+pub fn set_level(level: u8) {
+    LEVEL.store(level, Ordering::Relaxed);
+}
+
+pub fn enabled(level: u8) -> bool {
+    LEVEL.load(Ordering::Relaxed) >= level
+}
+
+/// Appends everything that is logged, at any level, to `path`.
+pub fn open_debug_file(path: &Path) -> std::io::Result<()> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    *DEBUG_FILE.lock().unwrap_or_else(|e| e.into_inner()) = Some(file);
+    Ok(())
+}
+
+fn write_debug_file(text: &str) {
+    let mut guard = DEBUG_FILE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(file) = guard.as_mut() {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        // Losing a log line is not worth failing the run over.
+        let _ = writeln!(file, "[{}] {}", seconds, text);
+    }
+}
+
+pub fn log(level: u8, message: &str) {
+    if enabled(level) {
+        let label = if level >= DEBUG { "debug" } else { "info" };
+        eprintln!("[{}] {}", label, message);
+    }
+    write_debug_file(message);
+}
+
+pub fn info(message: &str) {
+    log(INFO, message);
+}
+
+pub fn debug(message: &str) {
+    log(DEBUG, message);
+}
+
+/// Writes a multi-line body (request or response JSON) to the debug file
+/// only; it is too long for the terminal.
+pub fn dump(label: &str, body: &str) {
+    write_debug_file(&format!("{}\n{}\n", label, body));
+}
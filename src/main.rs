@@ -1,199 +1,372 @@
-use arboard::Clipboard;
-use core::str;
 use dotenvy::dotenv;
-use serde::Deserialize;
+use gemini_commit_message::cache::Cache;
+use gemini_commit_message::config::{Config, GitConfig};
+use gemini_commit_message::i18n::{self, Language, Message, tr};
+use gemini_commit_message::{DiffSource, Error, diff, fixup, git, log, score, timing};
 use std::env;
-use std::process::Command;
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
+use std::process::ExitCode;
 
-fn get_git_diff() -> Result<String, Box<dyn std::error::Error>> {
-    let diff = Command::new("git").arg("diff").arg("--cached").output()?;
+mod cli;
 
-    if !diff.status.success() {
-        println!("error get_git_diff {}", diff.status);
-        return Ok(String::new());
+fn load_dotenv() {
+    if let Err(e) = dotenv()
+        && !e.not_found()
+    {
+        eprintln!("ignoring unreadable .env file: {}", e);
     }
+}
+
+/// Switches the console to UTF-8 for the lifetime of the process, so that
+/// PowerShell and cmd decode piped output correctly instead of assuming the
+/// legacy OEM code page, and restores the previous code page afterwards.
+#[cfg(windows)]
+struct Utf8Console(u32);
+
+#[cfg(windows)]
+mod console {
+    unsafe extern "system" {
+        pub fn GetConsoleOutputCP() -> u32;
+        pub fn SetConsoleOutputCP(code_page: u32) -> i32;
+    }
+}
 
-    let diff_text_string = String::from_utf8(diff.stdout)?;
-    Ok(diff_text_string)
+#[cfg(windows)]
+impl Utf8Console {
+    fn enable() -> Option<Self> {
+        const CP_UTF8: u32 = 65001;
+        // SAFETY: neither call takes pointers; both fail harmlessly without a console.
+        unsafe {
+            let previous = console::GetConsoleOutputCP();
+            (previous != 0 && previous != CP_UTF8 && console::SetConsoleOutputCP(CP_UTF8) != 0)
+                .then_some(Self(previous))
+        }
+    }
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let _ = dotenv().ok();
-    let diff: String = match get_git_diff() {
-        Ok(message) => message,
-        Err(e) => {
-            println!("error get_git_diff {}", e);
-            return Ok(());
+#[cfg(windows)]
+impl Drop for Utf8Console {
+    fn drop(&mut self) {
+        // SAFETY: as in `enable`.
+        unsafe {
+            console::SetConsoleOutputCP(self.0);
         }
+    }
+}
+
+const EXIT_NOTHING_TO_COMMIT: u8 = 2;
+const EXIT_API: u8 = 3;
+const EXIT_CONFIG: u8 = 4;
+const EXIT_VALIDATION: u8 = 5;
+
+fn main() -> ExitCode {
+    #[cfg(windows)]
+    let _console = Utf8Console::enable();
+    let args: Vec<String> = env::args().collect();
+    if args.get(1).map(String::as_str) == Some(cli::clipboard::SERVE_ARG) {
+        return match cli::clipboard::serve() {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(_) => ExitCode::FAILURE,
+        };
+    }
+    // CI takes its key from the job's secrets, never from a checked-in file.
+    let ci = args.iter().any(|a| a == "--ci");
+    if !ci {
+        load_dotenv();
+    }
+    let quiet = args.iter().any(|a| a == "--quiet" || a == "-q");
+    let timings = args.iter().any(|a| a == "--timings");
+    let result = timing::measure("total", || run(&args));
+    if timings {
+        eprintln!("{}", timing::report());
+    }
+    let Err(e) = result else {
+        return ExitCode::SUCCESS;
     };
-    if diff.is_empty() {
-        println!("Nothing to commit");
+    let code = match e.downcast_ref::<Error>() {
+        Some(Error::NothingToCommit) => EXIT_NOTHING_TO_COMMIT,
+        Some(Error::Api(_)) => EXIT_API,
+        Some(Error::Config(_)) => EXIT_CONFIG,
+        Some(Error::Validation(_)) => EXIT_VALIDATION,
+        None => 1,
+    };
+    if ci {
+        println!(
+            "{}",
+            cli::ci::annotation("error", "gemini-commit-message", &e.to_string())
+        );
+    } else if !(quiet && code == EXIT_NOTHING_TO_COMMIT) {
+        eprintln!("{}", e);
+    }
+    ExitCode::from(code)
+}
+
+fn set_language(git_config: &GitConfig) {
+    let configured = git_config.get("gemini.uiLanguage");
+    i18n::set_language(Language::detect(
+        configured.map(|entry| entry.value.as_str()),
+    ));
+}
+
+fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    set_language(&GitConfig::load());
+    let options = |args: &[String]| {
+        let options = cli::parse_options(args)?;
+        cli::init_logging(&options);
+        Ok::<_, Error>(options)
+    };
+    match args.get(1).map(String::as_str) {
+        Some("auth") => return cli::auth::run(&args[2..]),
+        Some("config") => return cli::config::run(options(&args[2..])?),
+        Some("doctor") => return cli::doctor::run(options(&args[2..])?),
+        Some("history") => return cli::history::run(options(&args[2..])?),
+        Some("mcp") => return cli::mcp::run(options(&args[2..])?),
+        Some("models") => return cli::models::run(options(&args[2..])?),
+        Some("serve") => return cli::serve::run(options(&args[2..])?),
+        Some("stash") => return cli::stash::run(options(&args[2..])?),
+        Some("release") => return cli::release::run(options(&args[2..])?),
+        Some("changelog") => return cli::changelog::run(options(&args[2..])?),
+        Some("branch-name") => return cli::branch_name::run(options(&args[2..])?),
+        Some("next-version") => return cli::next_version::run(options(&args[2..])?),
+        Some("squash") => return cli::squash::run(options(&args[2..])?),
+        Some("pr") => return cli::pr::run(options(&args[2..])?),
+        Some("explain") => return cli::explain::run(options(&args[2..])?),
+        Some("revert") => return cli::revert::run(options(&args[2..])?),
+        Some("batch") => return cli::batch::run(options(&args[2..])?),
+        Some("lint") => return cli::lint::run(options(&args[2..])?),
+        Some("learn") => return cli::learn::run(options(&args[2..])?),
+        Some("hook") => return cli::hook::run(options(&args[2..])?),
+        Some("prompt") => return cli::prompt::run(options(&args[2..])?),
+        Some("self-update") => return cli::self_update::run(options(&args[2..])?),
+        Some("completions") => return cli::completions::run(&args[2..]),
+        _ => {}
+    }
+
+    let mut options = options(&args[1..])?;
+    if options.help {
+        cli::print_usage();
         return Ok(());
     }
+    if options.ci {
+        return cli::ci::run(options);
+    }
+    if options.rpc {
+        return cli::rpc::run(options);
+    }
+    if options.overrides.api_key.is_none() && !options.positional.is_empty() {
+        options.overrides.api_key = Some(options.positional.remove(0));
+    }
 
-    let args: Vec<String> = env::args().collect();
-    let mut api_key_arg: Option<String> = None;
-    let mut i = 1;
-    while i < args.len() {
-        let a = &args[i];
-        if a == "--help" || a == "-h" {
-            println!("Usage:");
-            println!("  Provide Gemini API key via one of:");
-            println!("    - As first positional argument: <program> <API_KEY>");
-            println!("    - Using --api-key=<KEY>");
-            println!("    - Using -k <KEY>");
-            println!("  Or set GEMINI_API_KEY in environment (or in a .env file).");
-            return Ok(());
-        } else if let Some(rest) = a.strip_prefix("--api-key=") {
-            api_key_arg = Some(rest.to_string());
-            break;
-        } else if a == "-k" {
-            if i + 1 < args.len() {
-                api_key_arg = Some(args[i + 1].clone());
-            }
-            break;
-        } else if !a.starts_with('-') {
-            api_key_arg = Some(a.clone());
-            break;
+    let repo = options.repo.as_deref();
+    let quiet = options.quiet;
+    if !options.stdin {
+        git::git_dir(repo)?;
+    }
+
+    let git_config = GitConfig::load_in(repo);
+    set_language(&git_config);
+    let config = Config::resolve(options.overrides.clone(), &git_config);
+    // Also part of the cache key, so mock messages never stand in for real ones.
+    let model = match config.provider.value.as_str() {
+        "mock" => "mock".to_string(),
+        provider if provider.starts_with("exec:") => provider.to_string(),
+        _ => config.model.value.clone(),
+    };
+    let preset = cli::adjust_preset(cli::preset(&config, repo)?, &options)?;
+    let learn = cli::learning(&config)?;
+    let unstaged = cli::unstaged::Mode::from_config(&config)?;
+    let mut generator = cli::build_generator(config, &preset)?
+        .chunked(options.chunked)
+        .subject(options.subject.clone());
+    if let Some(repo) = repo {
+        generator = generator.repo(repo);
+    }
+    if options.images {
+        generator = generator.images(true);
+    }
+    if options.minify {
+        generator = generator.minify(true);
+    }
+    if !options.no_cache
+        && let Some(cache) = Cache::open()
+    {
+        generator = generator.summary_cache(cache, model.clone());
+    }
+
+    if options.watch {
+        if options.stdin {
+            return Err(usage_error(Message::WatchWithStdin));
         }
-        i += 1;
+        return cli::watch::run(&generator, repo, |message| emit_message(message, quiet));
+    }
+
+    if (options.commit || options.split) && options.stdin {
+        return Err(usage_error(Message::CommitWithStdin));
+    }
+    if options.notes && !options.commit {
+        return Err(usage_error(Message::NotesWithoutCommit));
+    }
+    if options.interactive_select && (options.stdin || !io::stdin().is_terminal()) {
+        return Err(usage_error(Message::SelectNeedsTerminal));
     }
 
-    let api_key: String = if let Some(key) = api_key_arg {
-        key
+    let mut spinner = cli::spinner::Spinner::start(!quiet && options.verbose == 0);
+    spinner.phase("reading diff");
+    let source = if options.stdin {
+        DiffSource::Stdin
     } else {
-        match env::var("GEMINI_API_KEY") {
-            Ok(api_key) => api_key,
-            Err(_) => {
-                println!(
-                    "No API key provided. Provide it via --api-key, -k, positional arg, or set GEMINI_API_KEY in environment (.env is optional)."
-                );
+        DiffSource::Staged
+    };
+    let mut diff: String = source
+        .read(repo)
+        .map_err(|e| tr(Message::ReadDiffFailed, &[&e]))?;
+    if diff.trim().is_empty() && !options.stdin {
+        spinner.finish();
+        if let Some(fallback) =
+            cli::unstaged::fallback(repo, unstaged, options.commit || options.split)?
+        {
+            diff = fallback;
+        }
+        spinner = cli::spinner::Spinner::start(!quiet && options.verbose == 0);
+    }
+    if diff.trim().is_empty() {
+        return Err(Error::NothingToCommit.into());
+    }
+    if options.interactive_select {
+        spinner.finish();
+        diff = cli::select::run(&diff)?;
+        spinner = cli::spinner::Spinner::start(!quiet && options.verbose == 0);
+    }
+    log::info(&format!("read {} bytes of diff", diff.len()));
+    if !options.stdin && !options.split && !options.suggest_split {
+        spinner.finish();
+        if let Some(target) = fixup::target(repo, &diff) {
+            let short = &target.commit[..target.commit.len().min(7)];
+            let wanted = if options.fixup {
+                true
+            } else if options.commit && io::stdin().is_terminal() {
+                let answer = cli::read_line(&tr(Message::FixupOffer, &[&short, &target.subject]))?;
+                matches!(answer.to_lowercase().as_str(), "y" | "yes")
+            } else {
+                if !quiet {
+                    eprintln!("{}", tr(Message::FixupHint, &[&short, &target.subject]));
+                }
+                false
+            };
+            if wanted && options.commit {
+                return cli::commit::fixup(repo, &target.commit);
+            } else if wanted {
+                match &options.output {
+                    Some(path) => write_output(path, &target.message())?,
+                    None => emit_message(&target.message(), quiet),
+                }
                 return Ok(());
             }
+        } else if options.fixup && !quiet {
+            eprintln!("{}", tr(Message::NoFixupTarget, &[]));
         }
-    };
-
-    let prompto = create_prompt(&diff);
-    let message = generate_commit_message(&prompto, api_key)?;
-
-    println!("{}", message);
+        spinner = cli::spinner::Spinner::start(!quiet && options.verbose == 0);
+    }
+    if options.review {
+        spinner.finish();
+        cli::review::confirm(&diff, &generator.prompt(&diff).to_string(), &model)?;
+        spinner = cli::spinner::Spinner::start(!quiet && options.verbose == 0);
+    }
+    if !quiet
+        && !options.split
+        && !options.suggest_split
+        && let Some(report) = generator.trim_report(&diff)
+    {
+        spinner.finish();
+        let chunked = generator.is_chunked() && diff::split_files(&diff).len() > 1;
+        cli::review::over_limit(&report, chunked);
+        spinner = cli::spinner::Spinner::start(!quiet && options.verbose == 0);
+    }
+    // Roughly four characters per token for code and English.
+    spinner.phase(format!("calling {}… (~{} tokens)", model, diff.len() / 4));
 
-    match copy_to_clip(&message) {
-        Ok(_) => {}
-        Err(e) => eprintln!("fail to copy to clip {:?}", e),
+    if options.split || options.suggest_split {
+        let groups = cli::split::suggest(generator, &diff)?;
+        spinner.finish();
+        if options.split {
+            return cli::split::interactive(repo, &groups);
+        }
+        let files = if options.stdin {
+            None
+        } else {
+            Some(cli::split::staged_files(repo)?)
+        };
+        cli::split::print_groups(&groups, files.as_deref());
+        return Ok(());
     }
-    Ok(())
-}
 
-fn copy_to_clip(message: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let mut clipboard = Clipboard::new()?;
-    clipboard.set_text(message)?;
+    let mut candidates = cli::cached_candidates(
+        &generator,
+        &model,
+        &diff,
+        options.candidates,
+        !options.no_cache,
+    )?;
+    spinner.finish();
+    for candidate in &mut candidates {
+        preset.apply(candidate);
+    }
+    score::rank(&mut candidates, &preset, &diff);
+    if options.commit {
+        let message = cli::commit::choose(&candidates)?;
+        let chosen = candidates.iter().position(|c| std::ptr::eq(c, message));
+        cli::history::record(repo, &diff, &candidates, chosen);
+        cli::validate(message, &preset)?;
+        cli::commit::commit(repo, &message.to_string())?;
+        if options.notes {
+            cli::commit::add_note(repo, &candidates)?;
+        }
+        if learn {
+            cli::learn::after_commit(repo);
+        }
+        return Ok(());
+    }
+    cli::history::record(repo, &diff, &candidates, None);
+    if let Some(path) = &options.output {
+        write_output(path, &candidates[0].to_string())?;
+    } else if candidates.len() > 1 {
+        cli::commit::print_candidates(&candidates);
+        if let Err(e) = cli::clipboard::copy(&candidates[0].to_string())
+            && !quiet
+        {
+            eprintln!("{}", tr(Message::ClipboardFailed, &[&e]));
+        }
+    } else {
+        emit_message(&candidates[0].to_string(), quiet);
+    }
+    cli::validate(&candidates[0], &preset)?;
     Ok(())
 }
 
-const COMMIT_MESSAGE_GUIDELINE: &str = r#"
-Please generate a concise yet appropriate commit message based on the provided Git diff, following Conventional Commits.
-The key words “MUST”, “MUST NOT”, “REQUIRED”, “SHALL”, “SHALL NOT”, “SHOULD”, “SHOULD NOT”, “RECOMMENDED”, “MAY”, and “OPTIONAL” in this document are to be interpreted as described in RFC 2119.
-
-1. Commits MUST be prefixed with a type, which consists of a noun, feat, fix, etc., followed by the OPTIONAL scope, OPTIONAL !, and REQUIRED terminal colon and space.
-2. The type feat MUST be used when a commit adds a new feature to your application or library.
-3. The type fix MUST be used when a commit represents a bug fix for your application.
-4. A scope MAY be provided after a type. A scope MUST consist of a noun describing a section of the codebase surrounded by parenthesis, e.g., fix(parser):
-5. A description MUST immediately follow the colon and space after the type/scope prefix. The description is a short summary of the code changes, e.g., fix: array parsing issue when multiple spaces were contained in string.
-6. A longer commit body MAY be provided after the short description, providing additional contextual information about the code changes. The body MUST begin one blank line after the description.
-7. A commit body is free-form and MAY consist of any number of newline separated paragraphs.
-8. One or more footers MAY be provided one blank line after the body. Each footer MUST consist of a word token, followed by either a :<space> or <space># separator, followed by a string value (this is inspired by the git trailer convention).
-9. A footer’s token MUST use - in place of whitespace characters, e.g., Acked-by (this helps differentiate the footer section from a multi-paragraph body). An exception is made for BREAKING CHANGE, which MAY also be used as a token.
-10. A footer’s value MAY contain spaces and newlines, and parsing MUST terminate when the next valid footer token/separator pair is observed.
-11. Breaking changes MUST be indicated in the type/scope prefix of a commit, or as an entry in the footer.
-12. If included as a footer, a breaking change MUST consist of the uppercase text BREAKING CHANGE, followed by a colon, space, and description, e.g., BREAKING CHANGE: environment variables now take precedence over config files.
-13. If included in the type/scope prefix, breaking changes MUST be indicated by a ! immediately before the :. If ! is used, BREAKING CHANGE: MAY be omitted from the footer section, and the commit description SHALL be used to describe the breaking change.
-14. Types other than feat and fix MAY be used in your commit messages, e.g., docs: update ref docs.
-15. The units of information that make up Conventional Commits MUST NOT be treated as case sensitive by implementors, with the exception of BREAKING CHANGE which MUST be uppercase.
-16. BREAKING-CHANGE MUST be synonymous with BREAKING CHANGE, when used as a token in a footer.
-17. Do not include ``` in the generated commit message.
-    "#;
-
-fn create_prompt(diff: &str) -> String {
-    format!(
-        "{}\n\n---\n\n## Git Diff\n\n```diff\n{}\n```",
-        COMMIT_MESSAGE_GUIDELINE, diff
-    )
+fn usage_error(message: Message) -> Box<dyn std::error::Error> {
+    Error::Config(tr(message, &[])).into()
 }
 
-#[derive(Deserialize, Debug)]
-struct Part {
-    text: String,
-}
-
-#[derive(Deserialize, Debug)]
-struct Content {
-    parts: Vec<Part>,
-}
-
-#[derive(Deserialize, Debug)]
-struct Candidate {
-    content: Option<Content>,
-    finish_reason: Option<String>,
+/// Writes `message` to `path`, or stdout for `-`, ending in exactly one
+/// newline as `git commit -F` expects.
+fn write_output(path: &Path, message: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let text = format!("{}\n", message.trim_end());
+    if path == Path::new("-") {
+        io::stdout().write_all(text.as_bytes())?;
+    } else {
+        fs::write(path, text).map_err(|e| format!("cannot write {}: {}", path.display(), e))?;
+    }
+    Ok(())
 }
 
-#[derive(Deserialize, Debug)]
-struct GeminiResponse {
-    candidates: Vec<Candidate>,
-    prompt_feedback: Option<serde_json::Value>,
-}
+fn emit_message(message: &str, quiet: bool) {
+    println!("{}", message);
 
-fn generate_commit_message(
-    prompt: &str,
-    api_key: String,
-) -> Result<String, Box<dyn std::error::Error>> {
-    let url = "https://generativelanguage.googleapis.com/v1beta/models/gemini-flash-lite-latest:generateContent";
-
-    let payload = serde_json::json!({
-        "contents": [
-            {
-                "parts": [
-                    {"text": prompt}
-                ]
-            }
-        ],
-    });
-
-    let body = ureq::post(url)
-        .header("X-Goog-Api-Key", &api_key)
-        .send_json(payload)?
-        .body_mut()
-        .read_json::<GeminiResponse>()?;
-
-    let commit_message = body
-        .candidates
-        .first()
-        .and_then(|c| c.content.as_ref())
-        .and_then(|content| content.parts.first())
-        .map(|part| part.text.trim().to_string());
-
-    match commit_message {
-        Some(text) => Ok(text),
-        None => {
-            let reason = body
-                .candidates
-                .first()
-                .and_then(|c| c.finish_reason.as_ref())
-                .unwrap_or(&"不明 (candidatesが空か構造不正)".to_string())
-                .clone();
-
-            let feedback_info = body
-                .prompt_feedback
-                .map(|f| format!("Prompt Feedback: {:?}", f))
-                .unwrap_or_else(|| "No Prompt Feedback".to_string());
-
-            Err(format!(
-                "Gemini APIは有効なテキストを返しませんでした。\n\
-                 原因: finish_reason='{}'\n\
-                 詳細: {}",
-                reason, feedback_info
-            )
-            .into())
-        }
+    match cli::clipboard::copy(message) {
+        Ok(_) => {}
+        Err(e) if !quiet => eprintln!("{}", tr(Message::ClipboardFailed, &[&e])),
+        Err(_) => {}
     }
 }
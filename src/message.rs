@@ -0,0 +1,368 @@
+use std::fmt;
+
+/// A `token: value` (or `token #value`) trailer at the end of a message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Footer {
+    pub token: String,
+    pub separator: String,
+    pub value: String,
+}
+
+impl Footer {
+    /// Parses a single `Token: value` or `Token #value` line.
+    pub fn parse(line: &str) -> Option<Self> {
+        parse_footer_line(line.trim())
+    }
+}
+
+impl fmt::Display for Footer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}{}", self.token, self.separator, self.value)
+    }
+}
+
+/// The `type(scope)!: description` parts of a Conventional Commits header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConventionalHeader<'a> {
+    pub kind: &'a str,
+    pub scope: Option<&'a str>,
+    pub breaking: bool,
+    pub description: &'a str,
+}
+
+impl fmt::Display for ConventionalHeader<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind)?;
+        if let Some(scope) = self.scope {
+            write!(f, "({})", scope)?;
+        }
+        let bang = if self.breaking { "!" } else { "" };
+        write!(f, "{}: {}", bang, self.description)
+    }
+}
+
+/// A commit message split into header, body and footers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitMessage {
+    pub header: String,
+    pub body: Option<String>,
+    pub footers: Vec<Footer>,
+}
+
+/// Parses the start of a footer line: `Token: value`, `Token #value` or
+/// `BREAKING CHANGE: value`.
+fn parse_footer_line(line: &str) -> Option<Footer> {
+    let (token, separator, value) = if let Some(value) = line.strip_prefix("BREAKING CHANGE: ") {
+        ("BREAKING CHANGE", ": ", value)
+    } else {
+        let end = line
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+            .unwrap_or(line.len());
+        let (token, rest) = line.split_at(end);
+        if token.is_empty() || !token.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            return None;
+        }
+        if let Some(value) = rest.strip_prefix(": ") {
+            (token, ": ", value)
+        } else if let Some(value) = rest.strip_prefix(" #") {
+            (token, " #", value)
+        } else {
+            return None;
+        }
+    };
+    Some(Footer {
+        token: token.to_string(),
+        separator: separator.to_string(),
+        value: value.to_string(),
+    })
+}
+
+/// Splits the last paragraph into footers, or returns `None` if it is body text.
+fn parse_footers(paragraph: &str) -> Option<Vec<Footer>> {
+    let mut footers: Vec<Footer> = Vec::new();
+    for line in paragraph.lines() {
+        match (parse_footer_line(line), footers.last_mut()) {
+            (Some(footer), _) => footers.push(footer),
+            (None, Some(last)) => {
+                last.value.push('\n');
+                last.value.push_str(line);
+            }
+            (None, None) => return None,
+        }
+    }
+    Some(footers)
+}
+
+/// Openings of chatty lines such as "Here is your commit message:".
+const PREAMBLES: &[&str] = &[
+    "here is",
+    "here's",
+    "here are",
+    "sure",
+    "certainly",
+    "okay",
+    "commit message",
+];
+
+fn is_preamble(line: &str) -> bool {
+    let lower = line.trim().to_lowercase();
+    PREAMBLES.iter().any(|p| lower.starts_with(p)) && lower.ends_with([':', '.', '!'])
+}
+
+/// Openings of replies that talk to the user instead of describing a change,
+/// as models steered by instructions hidden in a diff tend to write.
+const REPLIES: &[&str] = &[
+    "i ",
+    "i'm",
+    "i am",
+    "i can",
+    "i will",
+    "as an ai",
+    "as requested",
+    "sorry",
+    "ignoring",
+];
+
+/// A first line longer than this is not a summary.
+const MAX_PLAUSIBLE_HEADER: usize = 200;
+
+/// Removes what models wrap around an answer despite being told not to:
+/// preamble lines, a surrounding ``` fence (and anything after it), a
+/// `Commit message:` label and enclosing quotes.
+pub fn strip_wrapping(text: &str) -> String {
+    let text = text.replace("\r\n", "\n");
+    let mut lines: Vec<&str> = text.trim().lines().collect();
+    while lines.first().is_some_and(|line| is_preamble(line)) {
+        lines.remove(0);
+        while lines.first().is_some_and(|line| line.trim().is_empty()) {
+            lines.remove(0);
+        }
+    }
+    if lines
+        .first()
+        .is_some_and(|line| line.trim_start().starts_with("```"))
+    {
+        lines.remove(0);
+        if let Some(end) = lines.iter().position(|line| line.trim() == "```") {
+            lines.truncate(end);
+        }
+    }
+    let mut text = lines.join("\n").trim().to_string();
+    if let Some(label) = text.get(..15)
+        && label.eq_ignore_ascii_case("commit message:")
+    {
+        text = text[15..].trim_start().to_string();
+    }
+    for quote in ['"', '\'', '`'] {
+        if text.len() > 1
+            && text.starts_with(quote)
+            && text.ends_with(quote)
+            && !text[1..text.len() - 1].contains(quote)
+        {
+            text = text[1..text.len() - 1].trim().to_string();
+        }
+    }
+    text
+}
+
+impl CommitMessage {
+    /// Why the message is not a commit message at all, e.g. because the
+    /// model followed instructions in the diff, or `None` if it may be one.
+    pub fn implausibility(&self) -> Option<&'static str> {
+        let header = self.header.to_lowercase();
+        if header.is_empty() {
+            Some("the first line is empty")
+        } else if header.chars().count() > MAX_PLAUSIBLE_HEADER {
+            Some("the first line is too long to be a summary")
+        } else if REPLIES.iter().any(|reply| header.starts_with(reply)) {
+            Some("it talks to the reader instead of describing the change")
+        } else if self.to_string().contains("```") {
+            Some("it contains a code block")
+        } else {
+            None
+        }
+    }
+
+    /// Parses a plain-text message, after [`strip_wrapping`].
+    pub fn parse(text: &str) -> Self {
+        let text = strip_wrapping(text);
+        let text = text.as_str();
+        let (header, rest) = text.split_once('\n').unwrap_or((text, ""));
+        let mut paragraphs: Vec<String> = rest
+            .trim()
+            .split("\n\n")
+            .map(|p| p.trim_matches('\n').to_string())
+            .filter(|p| !p.is_empty())
+            .collect();
+
+        let footers = match paragraphs.last().and_then(|p| parse_footers(p)) {
+            Some(footers) => {
+                paragraphs.pop();
+                footers
+            }
+            None => Vec::new(),
+        };
+        let body = if paragraphs.is_empty() {
+            None
+        } else {
+            Some(paragraphs.join("\n\n"))
+        };
+
+        Self {
+            header: header.trim().to_string(),
+            body,
+            footers,
+        }
+    }
+
+    /// Assembles a message from a reply following
+    /// [`crate::prompt::commit_message_schema`], or returns `None` if `text`
+    /// is not such a reply.
+    pub fn from_structured(text: &str) -> Option<Self> {
+        let value: serde_json::Value = serde_json::from_str(text.trim()).ok()?;
+        let field = |name: &str| {
+            value[name]
+                .as_str()
+                .map(|s| s.trim().replace("\r\n", "\n"))
+                .filter(|s| !s.is_empty())
+        };
+        let kind = field("type")?;
+        let description = field("description")?;
+        let scope = field("scope")
+            .map(|scope| format!("({})", scope))
+            .unwrap_or_default();
+        let bang = if value["breaking"].as_bool() == Some(true) {
+            "!"
+        } else {
+            ""
+        };
+        let footers = value["footers"]
+            .as_array()
+            .map(|footers| {
+                footers
+                    .iter()
+                    .filter_map(|footer| {
+                        Some(Footer {
+                            token: footer["token"].as_str()?.trim().to_string(),
+                            separator: ": ".to_string(),
+                            value: footer["value"].as_str()?.trim().replace("\r\n", "\n"),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Some(Self {
+            header: format!("{}{}{}: {}", kind, scope, bang, description),
+            body: field("body"),
+            footers,
+        })
+    }
+
+    /// The header split into its Conventional Commits parts, if it follows the format.
+    pub fn conventional(&self) -> Option<ConventionalHeader<'_>> {
+        let (prefix, description) = self.header.split_once(": ")?;
+        let (prefix, breaking) = match prefix.strip_suffix('!') {
+            Some(prefix) => (prefix, true),
+            None => (prefix, false),
+        };
+        let (kind, scope) = match prefix.split_once('(') {
+            Some((kind, scope)) => (kind, Some(scope.strip_suffix(')')?)),
+            None => (prefix, None),
+        };
+        let is_word = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_alphanumeric());
+        if !is_word(kind) || scope.is_some_and(|s| s.is_empty() || s.contains(['(', ')'])) {
+            return None;
+        }
+        Some(ConventionalHeader {
+            kind,
+            scope,
+            breaking,
+            description,
+        })
+    }
+
+    /// Replaces the scope of a Conventional Commits header, or removes it
+    /// with `None`; other headers are left alone.
+    pub fn set_scope(&mut self, scope: Option<&str>) {
+        if let Some(header) = self.conventional() {
+            self.header = ConventionalHeader { scope, ..header }.to_string();
+        }
+    }
+
+    /// Marks a Conventional Commit as breaking: `!` after the type and a
+    /// `BREAKING CHANGE` footer, which repeats the description if the
+    /// message has none.
+    pub fn mark_breaking(&mut self) {
+        let Some(header) = self.conventional() else {
+            return;
+        };
+        let description = header.description.to_string();
+        self.header = ConventionalHeader {
+            breaking: true,
+            ..header
+        }
+        .to_string();
+        if !self
+            .footers
+            .iter()
+            .any(|f| f.token == "BREAKING CHANGE" || f.token == "BREAKING-CHANGE")
+        {
+            self.footers.insert(
+                0,
+                Footer {
+                    token: "BREAKING CHANGE".to_string(),
+                    separator: ": ".to_string(),
+                    value: description,
+                },
+            );
+        }
+    }
+
+    /// Adds `trailers` as `git interpret-trailers` would: after the existing
+    /// ones but before the closing `Signed-off-by` lines, skipping any that
+    /// are already present with the same value.
+    pub fn add_trailers(&mut self, trailers: &[Footer]) {
+        let mut at = self.footers.len();
+        while at > 0
+            && self.footers[at - 1]
+                .token
+                .eq_ignore_ascii_case("Signed-off-by")
+        {
+            at -= 1;
+        }
+        for trailer in trailers {
+            let present = self.footers.iter().any(|footer| {
+                footer.token.eq_ignore_ascii_case(&trailer.token) && footer.value == trailer.value
+            });
+            if !present {
+                self.footers.insert(at, trailer.clone());
+                at += 1;
+            }
+        }
+    }
+
+    /// Whether the header has `!` or a `BREAKING CHANGE` footer.
+    pub fn is_breaking(&self) -> bool {
+        self.conventional().is_some_and(|h| h.breaking)
+            || self
+                .footers
+                .iter()
+                .any(|f| f.token == "BREAKING CHANGE" || f.token == "BREAKING-CHANGE")
+    }
+}
+
+impl fmt::Display for CommitMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.header)?;
+        if let Some(body) = &self.body {
+            write!(f, "\n\n{}", body)?;
+        }
+        if !self.footers.is_empty() {
+            writeln!(f)?;
+            for footer in &self.footers {
+                write!(f, "\n{}", footer)?;
+            }
+        }
+        Ok(())
+    }
+}
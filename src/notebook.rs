@@ -0,0 +1,73 @@
+//! Jupyter notebooks in diffs. Their JSON mixes the cell sources with
+//! outputs, execution counts and metadata, so a notebook's diff is rebuilt
+//! from the sources of its cells alone.
+
+use crate::diff::{self, FileDiff};
+use serde_json::Value;
+
+/// Lines of context around each change, as `git diff` shows.
+const CONTEXT: usize = 3;
+
+/// The cells of a notebook as text: a `# %% [kind]` line before the source
+/// of each cell, like Jupytext's percent format. `None` if `json` is not a
+/// notebook.
+pub fn cell_sources(json: &str) -> Option<String> {
+    let notebook: Value = serde_json::from_str(json).ok()?;
+    let mut text = String::new();
+    for cell in notebook["cells"].as_array()? {
+        let kind = cell["cell_type"].as_str().unwrap_or("code");
+        // The source is a list of lines or, less often, one string.
+        let source = match &cell["source"] {
+            Value::Array(lines) => lines.iter().filter_map(Value::as_str).collect(),
+            Value::String(source) => source.clone(),
+            _ => String::new(),
+        };
+        text.push_str(&format!("# %% [{}]\n{}\n", kind, source.trim_end()));
+    }
+    Some(text)
+}
+
+/// The diff of the cell sources of `file`, reading both versions with
+/// `read_blob`; `None` when either cannot be read.
+fn rewrite_file(file: &FileDiff, read_blob: &impl Fn(&str) -> Option<String>) -> Option<String> {
+    let read = |id: Option<&str>| match id {
+        Some(id) => cell_sources(&read_blob(id)?),
+        None => Some(String::new()),
+    };
+    let old = read(diff::old_blob(file))?;
+    let new = read(diff::new_blob(file))?;
+    let header: String = file
+        .text
+        .lines()
+        .take_while(|line| !line.starts_with("@@") && !line.starts_with("Binary files"))
+        .map(|line| format!("{}\n", line))
+        .collect();
+    Some(format!("{}{}", header, diff::unified(&old, &new, CONTEXT)))
+}
+
+/// Replaces the diff of every notebook in `diff` with the diff of its cell
+/// sources, without outputs, execution counts or metadata. `read_blob`
+/// returns the content of a blob by id. Returns `None` when nothing changed.
+pub fn rewrite(diff: &str, read_blob: impl Fn(&str) -> Option<String>) -> Option<String> {
+    let files = diff::split_files(diff);
+    if !files.iter().any(|f| f.path.ends_with(".ipynb")) {
+        return None;
+    }
+    let mut text = String::new();
+    let mut rewritten = false;
+    for file in &files {
+        match file
+            .path
+            .ends_with(".ipynb")
+            .then(|| rewrite_file(file, &read_blob))
+            .flatten()
+        {
+            Some(cells) => {
+                text.push_str(&cells);
+                rewritten = true;
+            }
+            None => text.push_str(&file.text),
+        }
+    }
+    rewritten.then_some(text)
+}
@@ -0,0 +1,426 @@
+//! Presets bundle what makes up a commit convention: the guideline sent to
+//! the model, example messages and the rules the result is checked against.
+//!
+//! Besides the built-in presets, repositories and users can define their own
+//! in TOML, in `.gemini-commit-message.toml` at the top of the work tree or
+//! `presets.toml` in the user's config directory:
+//!
+//! ```toml
+//! [preset.team]
+//! extends = "angular"
+//! instructions = "Reference the ticket as `Refs: ABC-123` when the branch names one."
+//! types = ["feat", "fix", "docs", "chore"]
+//! max_header_length = 72
+//! mood = "imperative"
+//! capitalize = false
+//! period = false
+//! examples = ["feat(api): add cursor pagination to /orders"]
+//! ```
+
+use crate::i18n::{Message, tr};
+use crate::message::CommitMessage;
+use crate::prompt::{
+    ANGULAR_GUIDELINE, COMMIT_MESSAGE_GUIDELINE, GITMOJI_GUIDELINE, PromptBuilder,
+    commit_message_schema,
+};
+use crate::style::Style;
+use crate::subject::{Mood, SubjectStyle};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const BUILTIN: [&str; 5] = ["conventional", "angular", "gitmoji", "plain", "kernel"];
+
+/// The presets file in a work tree.
+pub const REPO_FILE: &str = ".gemini-commit-message.toml";
+
+const ANGULAR_TYPES: [&str; 8] = [
+    "build", "ci", "docs", "feat", "fix", "perf", "refactor", "test",
+];
+
+/// The gitmoji a header may start with, as emoji and as `:code:`.
+const GITMOJI: [(&str, &str); 24] = [
+    ("✨", ":sparkles:"),
+    ("🐛", ":bug:"),
+    ("📝", ":memo:"),
+    ("♻️", ":recycle:"),
+    ("⚡️", ":zap:"),
+    ("🔥", ":fire:"),
+    ("✅", ":white_check_mark:"),
+    ("🎨", ":art:"),
+    ("🚀", ":rocket:"),
+    ("🔧", ":wrench:"),
+    ("⬆️", ":arrow_up:"),
+    ("⬇️", ":arrow_down:"),
+    ("🚑️", ":ambulance:"),
+    ("💄", ":lipstick:"),
+    ("🔒️", ":lock:"),
+    ("🚧", ":construction:"),
+    ("👷", ":construction_worker:"),
+    ("💚", ":green_heart:"),
+    ("🏷️", ":label:"),
+    ("🌐", ":globe_with_meridians:"),
+    ("➕", ":heavy_plus_sign:"),
+    ("➖", ":heavy_minus_sign:"),
+    ("🗑️", ":wastebasket:"),
+    ("🎉", ":tada:"),
+];
+
+/// What the scope of a Conventional Commits header may be.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Scope {
+    /// Whatever the model picks.
+    #[default]
+    Any,
+    /// Always this one.
+    Pinned(String),
+    /// No scope at all.
+    Without,
+}
+
+#[derive(Debug, Clone)]
+pub struct Preset {
+    pub name: String,
+    /// The format the header is checked against.
+    pub style: Style,
+    pub guideline: String,
+    /// Messages shown to the model as examples of the convention.
+    pub examples: Vec<String>,
+    /// The allowed Conventional Commits types; empty allows any.
+    pub types: Vec<String>,
+    pub max_header_length: Option<usize>,
+    /// What the header must start with, e.g. a gitmoji; empty allows any.
+    pub prefixes: Vec<String>,
+    pub scope: Scope,
+    /// Whether every message is marked as a breaking change.
+    pub breaking: bool,
+    /// Mood, capitalization and trailing period of the summary.
+    pub subject: SubjectStyle,
+}
+
+/// One `[preset.<name>]` table.
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+struct PresetTable {
+    extends: Option<String>,
+    guideline: Option<String>,
+    instructions: Option<String>,
+    examples: Option<Vec<String>>,
+    types: Option<Vec<String>>,
+    max_header_length: Option<usize>,
+    prefixes: Option<Vec<String>>,
+    mood: Option<Mood>,
+    capitalize: Option<bool>,
+    period: Option<bool>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct PresetFile {
+    #[serde(default)]
+    preset: BTreeMap<String, PresetTable>,
+}
+
+/// Emoji are compared without the variation selector models often drop.
+fn without_variation(text: &str) -> String {
+    text.replace('\u{FE0F}', "")
+}
+
+impl Preset {
+    /// The preset that only applies `style`.
+    pub fn from_style(style: Style) -> Self {
+        Self {
+            name: style.name().to_string(),
+            style,
+            guideline: style.guideline().to_string(),
+            examples: Vec::new(),
+            types: Vec::new(),
+            max_header_length: None,
+            prefixes: Vec::new(),
+            scope: Scope::Any,
+            breaking: false,
+            subject: SubjectStyle::default(),
+        }
+    }
+
+    pub fn builtin(name: &str) -> Option<Self> {
+        let preset = match name {
+            "angular" => Self {
+                name: name.to_string(),
+                guideline: format!("{}\n{}", COMMIT_MESSAGE_GUIDELINE, ANGULAR_GUIDELINE),
+                types: ANGULAR_TYPES.iter().map(|t| t.to_string()).collect(),
+                max_header_length: Some(100),
+                ..Self::from_style(Style::Conventional)
+            },
+            "gitmoji" => Self {
+                name: name.to_string(),
+                guideline: GITMOJI_GUIDELINE.to_string(),
+                prefixes: GITMOJI
+                    .iter()
+                    .flat_map(|(emoji, code)| [emoji.to_string(), code.to_string()])
+                    .collect(),
+                ..Self::from_style(Style::Plain)
+            },
+            name => Self::from_style(Style::parse(name)?),
+        };
+        Some(preset)
+    }
+
+    /// `presets.toml` in the user's config directory.
+    pub fn user_file() -> Option<PathBuf> {
+        let var = |name| {
+            env::var_os(name)
+                .filter(|v| !v.is_empty())
+                .map(PathBuf::from)
+        };
+        let dir = var("XDG_CONFIG_HOME")
+            .or_else(|| if cfg!(windows) { var("APPDATA") } else { None })
+            .or_else(|| var("HOME").map(|home| home.join(".config")))?;
+        Some(dir.join("gemini-commit-message").join("presets.toml"))
+    }
+
+    /// The guideline saved by `prompt import` next to [`Preset::user_file`],
+    /// which replaces the guideline of every preset while it exists.
+    pub fn imported_guideline_file() -> Option<PathBuf> {
+        Some(Self::user_file()?.with_file_name("guideline.md"))
+    }
+
+    /// Looks `name` up in `files`, in order, and then among the built-in
+    /// presets. A custom preset builds on the one it `extends`
+    /// (`conventional` by default).
+    pub fn find(name: &str, files: &[PathBuf]) -> Result<Option<Self>, Box<dyn std::error::Error>> {
+        Self::find_nested(name, files, 0)
+    }
+
+    fn find_nested(
+        name: &str,
+        files: &[PathBuf],
+        depth: usize,
+    ) -> Result<Option<Self>, Box<dyn std::error::Error>> {
+        if depth > 8 {
+            return Err(format!("preset '{}' extends itself", name).into());
+        }
+        for path in files {
+            let Ok(text) = fs::read_to_string(path) else {
+                continue;
+            };
+            let mut file: PresetFile = toml::from_str(&text)
+                .map_err(|e| format!("cannot parse {}: {}", path.display(), e))?;
+            let Some(table) = file.preset.remove(name) else {
+                continue;
+            };
+            let base_name = table.extends.as_deref().unwrap_or("conventional");
+            let Some(base) = Self::find_nested(base_name, files, depth + 1)? else {
+                return Err(format!(
+                    "preset '{}' in {} extends unknown preset '{}'",
+                    name,
+                    path.display(),
+                    base_name
+                )
+                .into());
+            };
+            return Ok(Some(base.customize(name, table)));
+        }
+        Ok(Self::builtin(name))
+    }
+
+    fn customize(self, name: &str, table: PresetTable) -> Self {
+        let mut guideline = table.guideline.unwrap_or(self.guideline);
+        if let Some(instructions) = table.instructions {
+            guideline = format!("{}\n{}", guideline.trim_end(), instructions);
+        }
+        Self {
+            name: name.to_string(),
+            style: self.style,
+            guideline,
+            examples: table.examples.unwrap_or(self.examples),
+            types: table.types.unwrap_or(self.types),
+            max_header_length: table.max_header_length.or(self.max_header_length),
+            prefixes: table.prefixes.unwrap_or(self.prefixes),
+            scope: self.scope,
+            breaking: self.breaking,
+            subject: SubjectStyle {
+                mood: table.mood.or(self.subject.mood),
+                capitalize: table.capitalize.or(self.subject.capitalize),
+                period: table.period.or(self.subject.period),
+            },
+        }
+    }
+
+    /// Only allows `kind` as the type, in the prompt and when checking.
+    pub fn force_type(mut self, kind: &str) -> Self {
+        self.types = vec![kind.to_string()];
+        self.guideline = format!(
+            "{}\nThe type of this commit MUST be {}.",
+            self.guideline.trim_end(),
+            kind
+        );
+        self
+    }
+
+    /// Pins the scope to `scope`, or leaves it out with `None`, whatever
+    /// the model prefers.
+    pub fn force_scope(mut self, scope: Option<&str>) -> Self {
+        let rule = match scope {
+            Some(scope) => format!("The scope of this commit MUST be ({}).", scope),
+            None => "This commit MUST NOT have a scope.".to_string(),
+        };
+        self.guideline = format!("{}\n{}", self.guideline.trim_end(), rule);
+        self.scope = match scope {
+            Some(scope) => Scope::Pinned(scope.to_string()),
+            None => Scope::Without,
+        };
+        self
+    }
+
+    /// Asks for a breaking change described from the diff, and marks the
+    /// message as one whatever the model answers.
+    pub fn force_breaking(mut self) -> Self {
+        self.guideline = format!(
+            "{}\nThis commit is a breaking change: the type MUST be followed by ! and a BREAKING CHANGE footer MUST explain, based on the diff, what breaks for users and how to migrate.",
+            self.guideline.trim_end()
+        );
+        self.breaking = true;
+        self
+    }
+
+    /// Adds a one-off instruction from the user, which takes precedence
+    /// over the guideline.
+    pub fn instruct(mut self, instruction: &str) -> Self {
+        self.guideline = format!(
+            "{}\n\nAdditional instruction from the author for this commit, which takes precedence: {}",
+            self.guideline.trim_end(),
+            instruction.trim()
+        );
+        self
+    }
+
+    /// Asks for messages that fill in the team's `commit.template`.
+    pub fn template(mut self, template: &str) -> Self {
+        self.guideline = format!(
+            "{}\n\nThe repository mandates the commit template below. The message MUST fill it in: keep its structure, section headings and their order, and replace placeholders with what the diff shows. Lines starting with # are instructions for the author and MUST NOT appear in the message; leave out optional sections that do not apply.\n\n{}",
+            self.guideline.trim_end(),
+            template.trim_end()
+        );
+        self
+    }
+
+    /// Where the summary starts in `header`: after the Conventional Commits
+    /// prefix, the kernel subsystem or one of the preset's prefixes.
+    fn summary_start(&self, header: &str) -> usize {
+        let after_colon = || header.find(": ").map_or(0, |at| at + 2);
+        match self.style {
+            Style::Conventional | Style::Kernel => after_colon(),
+            Style::Plain => self
+                .prefixes
+                .iter()
+                .filter(|prefix| header.starts_with(prefix.as_str()))
+                .map(|prefix| prefix.len())
+                .max()
+                .map_or(0, |end| {
+                    end + header[end..].len() - header[end..].trim_start().len()
+                }),
+        }
+    }
+
+    /// Applies what the preset pins regardless of the model's answer.
+    pub fn apply(&self, message: &mut CommitMessage) {
+        match &self.scope {
+            Scope::Any => {}
+            Scope::Pinned(scope) => message.set_scope(Some(scope)),
+            Scope::Without => message.set_scope(None),
+        }
+        if self.breaking {
+            message.mark_breaking();
+        }
+        if !self.subject.is_empty() {
+            let start = self.summary_start(&message.header);
+            let summary = self.subject.apply(&message.header[start..]);
+            message.header = format!("{}{}", &message.header[..start], summary);
+        }
+    }
+
+    /// The guideline with the examples; Conventional Commits are requested
+    /// as JSON, limited to the allowed types.
+    pub fn prompt_builder(&self) -> PromptBuilder {
+        let mut guideline = self.guideline.trim_end().to_string();
+        if !self.examples.is_empty() {
+            guideline.push_str("\n\nExamples of commit messages following this convention:\n");
+            for example in &self.examples {
+                guideline.push_str(&format!("\n{}\n", example.trim()));
+            }
+        }
+        if let Some(rules) = self.subject.guideline() {
+            guideline.push_str(&format!("\n\n{}", rules));
+        }
+        let builder = PromptBuilder::new().guideline(guideline);
+        if self.style != Style::Conventional {
+            return builder;
+        }
+        let mut schema = commit_message_schema();
+        if !self.types.is_empty() {
+            schema["properties"]["type"]["enum"] = self.types.clone().into();
+        }
+        match &self.scope {
+            Scope::Any => {}
+            Scope::Pinned(scope) => {
+                schema["properties"]["scope"]["enum"] = vec![scope.clone()].into()
+            }
+            Scope::Without => {
+                if let Some(properties) = schema["properties"].as_object_mut() {
+                    properties.remove("scope");
+                }
+                if let Some(order) = schema["propertyOrdering"].as_array_mut() {
+                    order.retain(|name| name != "scope");
+                }
+            }
+        }
+        builder.schema(Some(schema))
+    }
+
+    /// Checks a generated message against the style and the preset's rules.
+    pub fn check(&self, message: &CommitMessage) -> Result<(), String> {
+        if self.subject.period == Some(true) {
+            // The house style asks for the period the style would reject.
+            let mut message = message.clone();
+            message.header = message.header.trim_end_matches('.').to_string();
+            self.style.check(&message)?;
+        } else {
+            self.style.check(message)?;
+        }
+        let header = message.header.as_str();
+        if let Some(kind) = message.conventional().map(|h| h.kind)
+            && !self.types.is_empty()
+            && !self.types.iter().any(|t| t == kind)
+        {
+            return Err(tr(
+                Message::TypeNotAllowed,
+                &[&kind, &self.types.join(", ")],
+            ));
+        }
+        if let Some(max) = self.max_header_length
+            && header.chars().count() > max
+        {
+            return Err(tr(Message::SubjectTooLong, &[&max, &header]));
+        }
+        let start = without_variation(header);
+        if !self.prefixes.is_empty()
+            && !self
+                .prefixes
+                .iter()
+                .any(|prefix| start.starts_with(&without_variation(prefix)))
+        {
+            return Err(tr(Message::MissingPrefix, &[&self.name, &header]));
+        }
+        Ok(())
+    }
+}
+
+/// The presets files that apply to the work tree at `root`, most specific first.
+pub fn files(root: Option<&Path>) -> Vec<PathBuf> {
+    root.map(|root| root.join(REPO_FILE))
+        .into_iter()
+        .chain(Preset::user_file())
+        .collect()
+}
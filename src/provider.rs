@@ -0,0 +1,16 @@
+pub mod exec;
+pub mod gemini;
+pub mod mock;
+
+pub use exec::Exec;
+pub use gemini::Gemini;
+pub use mock::Mock;
+
+use crate::prompt::Prompt;
+
+/// A backend that turns a fully built prompt into the raw message text.
+///
+/// Providers are shared between threads when files are summarized concurrently.
+pub trait Provider: Send + Sync {
+    fn generate(&self, prompt: &Prompt) -> Result<String, Box<dyn std::error::Error>>;
+}
@@ -0,0 +1,75 @@
+//! A provider that pipes the prompt to an external command, for models and
+//! internal services without a built-in backend.
+//!
+//! The command runs through the shell with the system instruction, a blank
+//! line and the user turn on stdin, and answers with the message on stdout.
+//! It cannot enforce the schema or read images, so it should answer in plain
+//! text.
+
+use super::Provider;
+use crate::error::Error;
+use crate::prompt::Prompt;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::thread;
+
+pub struct Exec {
+    command: String,
+}
+
+impl Exec {
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            command: command.into(),
+        }
+    }
+
+    fn shell(&self) -> Command {
+        let mut command = if cfg!(windows) {
+            let mut command = Command::new("cmd");
+            command.arg("/C");
+            command
+        } else {
+            let mut command = Command::new("sh");
+            command.arg("-c");
+            command
+        };
+        command.arg(&self.command);
+        command
+    }
+}
+
+impl Provider for Exec {
+    fn generate(&self, prompt: &Prompt) -> Result<String, Box<dyn std::error::Error>> {
+        let mut child = self
+            .shell()
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| Error::Api(format!("cannot run '{}': {}", self.command, e)))?;
+        let input = format!("{}\n\n{}", prompt.system.trim_end(), prompt.user);
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        // Written from another thread so a command that answers before it has
+        // read everything cannot block on a full stdout pipe.
+        let writer = thread::spawn(move || {
+            // The command may exit without reading its input.
+            let _ = stdin.write_all(input.as_bytes());
+        });
+        let output = child.wait_with_output()?;
+        let _ = writer.join();
+        if !output.status.success() {
+            let mut message = format!("'{}' failed ({})", self.command, output.status);
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if !stderr.trim().is_empty() {
+                message = format!("{}: {}", message, stderr.trim());
+            }
+            return Err(Error::Api(message).into());
+        }
+        let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if text.is_empty() {
+            return Err(Error::Api(format!("'{}' printed no message", self.command)).into());
+        }
+        Ok(text)
+    }
+}
@@ -0,0 +1,527 @@
+use super::Provider;
+use crate::config::mask_secret;
+use crate::error::Error;
+use crate::i18n::{Message, tr};
+use crate::prompt::Prompt;
+use crate::rate::{self, RateLimit};
+use crate::{cache, log, timing};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_MODEL: &str = "gemini-flash-lite-latest";
+
+/// Where the API is reached unless a proxy or gateway is configured.
+pub const DEFAULT_BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
+
+/// The thinking budget of the one retry made when a thinking model spent
+/// the whole output limit before writing any answer.
+const RECOVERY_THINKING_BUDGET: i32 = 1024;
+
+/// How many times an answer cut off by the output limit is continued before
+/// giving up.
+const MAX_CONTINUATIONS: usize = 3;
+
+/// Shared by every [`Gemini`] so that long-running processes reuse pooled
+/// connections instead of paying a TLS handshake per request. HTTP errors are
+/// returned as responses so that their bodies can be logged and reported.
+static AGENT: LazyLock<ureq::Agent> = LazyLock::new(|| {
+    ureq::Agent::config_builder()
+        .http_status_as_error(false)
+        .build()
+        .into()
+});
+
+/// Where responses come from besides the live API.
+enum Fixtures {
+    /// Call the API and save every response under the directory.
+    Record(PathBuf),
+    /// Answer from the responses saved under the directory, offline.
+    Replay(PathBuf),
+}
+
+/// Google's Gemini `generateContent` API.
+pub struct Gemini {
+    api_key: String,
+    base_url: String,
+    model: String,
+    thinking_budget: Option<i32>,
+    rate_limit: RateLimit,
+    fixtures: Option<Fixtures>,
+}
+
+impl Gemini {
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            api_key: api_key.into(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            model: DEFAULT_MODEL.to_string(),
+            thinking_budget: None,
+            rate_limit: RateLimit::default(),
+            fixtures: None,
+        }
+    }
+
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    /// Sends requests to `url` instead of [`DEFAULT_BASE_URL`], e.g. to
+    /// LiteLLM, Cloudflare AI Gateway or a proxy that adds the key itself.
+    pub fn base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_url = url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Caps the tokens a thinking model may spend before answering: `0`
+    /// turns thinking off, `-1` lets the model decide. Unset keeps the
+    /// model's default.
+    pub fn thinking_budget(mut self, budget: i32) -> Self {
+        self.thinking_budget = Some(budget);
+        self
+    }
+
+    /// Waits before a request that would exceed `limit`, counting the
+    /// requests of every client in the process.
+    pub fn rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limit = limit;
+        self
+    }
+
+    /// Saves every response under `dir` so that it can be replayed later.
+    pub fn record(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.fixtures = Some(Fixtures::Record(dir.into()));
+        self
+    }
+
+    /// Answers from responses saved by [`Gemini::record`] instead of calling
+    /// the API; a request without a saved response fails.
+    pub fn replay(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.fixtures = Some(Fixtures::Replay(dir.into()));
+        self
+    }
+}
+
+/// The file under a record/replay directory that holds the response to
+/// `prompt` sent to `model` with `thinking_budget`. Without a budget the
+/// name is the one recordings have always had.
+pub fn fixture_name(model: &str, thinking_budget: Option<i32>, prompt: &Prompt) -> String {
+    let images: Vec<String> = prompt
+        .images
+        .iter()
+        .map(|image| cache::key(&[&base64(&image.data)]))
+        .collect();
+    let budget = thinking_budget.map(|budget| format!("thinkingBudget={}", budget));
+    let mut parts = vec![model, prompt.system.as_str(), prompt.user.as_str()];
+    parts.extend(images.iter().map(String::as_str));
+    parts.extend(budget.as_deref());
+    format!("{}.json", cache::key(&parts))
+}
+
+/// Standard base64 with padding, as `inline_data` expects.
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | u32::from(b) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                text.push(char::from(ALPHABET[(n >> (18 - 6 * i) & 63) as usize]));
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
+
+fn save_fixture(
+    path: &Path,
+    model: &str,
+    status: u16,
+    text: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Keep JSON bodies readable in the fixture; anything else stays a string.
+    let response =
+        serde_json::from_str(text).unwrap_or_else(|_| serde_json::Value::String(text.to_string()));
+    let fixture = serde_json::json!({
+        "model": model,
+        "status": status,
+        "response": response,
+    });
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(&fixture)? + "\n")?;
+    Ok(())
+}
+
+fn load_fixture(path: &Path) -> Result<(u16, String), Box<dyn std::error::Error>> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| format!("no recorded response {}: {}", path.display(), e))?;
+    let fixture: serde_json::Value = serde_json::from_str(&text)?;
+    let status = fixture["status"].as_u64().unwrap_or(200) as u16;
+    let body = match &fixture["response"] {
+        serde_json::Value::String(text) => text.clone(),
+        response => response.to_string(),
+    };
+    Ok((status, body))
+}
+
+#[derive(Deserialize, Debug)]
+struct Part {
+    text: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct Content {
+    /// Missing when a thinking model reached the output limit before
+    /// writing anything.
+    #[serde(default)]
+    parts: Vec<Part>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Candidate {
+    content: Option<Content>,
+    finish_reason: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct GeminiResponse {
+    #[serde(default)]
+    candidates: Vec<Candidate>,
+    prompt_feedback: Option<serde_json::Value>,
+}
+
+/// The `error.message` of a failed call, or the whole body if there is none.
+fn api_error(status: ureq::http::StatusCode, text: String) -> String {
+    let message = serde_json::from_str::<serde_json::Value>(&text)
+        .ok()
+        .and_then(|error| error["error"]["message"].as_str().map(str::to_string))
+        .unwrap_or(text);
+    tr(Message::ApiStatus, &[&status, &message])
+}
+
+/// Splits the time until the response headers arrived into the model's own
+/// processing time, which Google reports as `Server-Timing: ...; dur=<ms>`,
+/// and everything else (connection, upload, routing).
+fn record_request_time(elapsed: Duration, headers: &ureq::http::HeaderMap) {
+    let server = headers
+        .get("server-timing")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split("dur=").nth(1))
+        .and_then(|rest| {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .unwrap_or(rest.len());
+            rest[..end].parse::<f64>().ok()
+        })
+        .map(|ms| Duration::from_secs_f64(ms / 1000.0))
+        .filter(|server| *server <= elapsed);
+    match server {
+        Some(server) => {
+            timing::record("model", server);
+            timing::record("network", elapsed - server);
+        }
+        None => timing::record("network + model", elapsed),
+    }
+}
+
+impl Gemini {
+    /// Sends `prompt` and returns the HTTP status and body of the response.
+    fn send(
+        &self,
+        prompt: &Prompt,
+        thinking_budget: Option<i32>,
+    ) -> Result<(u16, String), Box<dyn std::error::Error>> {
+        let url = format!("{}/models/{}:generateContent", self.base_url, self.model);
+
+        let mut payload = serde_json::json!({
+            "contents": [
+                {
+                    "role": "user",
+                    "parts": [
+                        {"text": prompt.user}
+                    ]
+                }
+            ],
+        });
+        if let Some(parts) = payload["contents"][0]["parts"].as_array_mut() {
+            parts.extend(prompt.images.iter().map(|image| {
+                serde_json::json!({
+                    "inline_data": {
+                        "mime_type": image.mime_type,
+                        "data": base64(&image.data),
+                    }
+                })
+            }));
+        }
+        // A separate system instruction is followed more closely than the
+        // same text in the user turn, and stays identical across requests.
+        if !prompt.system.is_empty() {
+            payload["systemInstruction"] = serde_json::json!({
+                "parts": [
+                    {"text": prompt.system}
+                ]
+            });
+        }
+        if let Some(budget) = thinking_budget {
+            payload["generationConfig"]["thinkingConfig"] =
+                serde_json::json!({"thinkingBudget": budget});
+        }
+        if let Some(schema) = &prompt.schema {
+            payload["generationConfig"]["responseMimeType"] = "application/json".into();
+            payload["generationConfig"]["responseSchema"] = schema.clone();
+        }
+
+        log::info(&format!(
+            "POST {} ({} bytes of system instruction, {} bytes of input)",
+            url,
+            prompt.system.len(),
+            prompt.user.len()
+        ));
+        log::dump(
+            &format!(
+                "request POST {}\nX-Goog-Api-Key: {}",
+                url,
+                mask_secret(&self.api_key)
+            ),
+            &serde_json::to_string_pretty(&payload)?,
+        );
+        if !self.rate_limit.is_empty() {
+            let tokens =
+                rate::estimate_tokens(prompt.system.len() + prompt.user.len(), prompt.images.len());
+            let waited = rate::acquire(self.rate_limit, tokens);
+            if !waited.is_zero() {
+                log::info(&format!(
+                    "waited {} ms for the rate limit",
+                    waited.as_millis()
+                ));
+                timing::record("rate limit", waited);
+            }
+        }
+        let started = Instant::now();
+        let mut request = AGENT.post(&url);
+        // A proxy holding the real key is called without one.
+        if !self.api_key.is_empty() {
+            request = request.header("X-Goog-Api-Key", &self.api_key);
+        }
+        let mut response = request.send_json(payload).inspect_err(|e| {
+            timing::record("network", started.elapsed());
+            log::info(&format!("request failed: {}", e));
+        })?;
+        record_request_time(started.elapsed(), response.headers());
+        let status = response.status();
+        let text = timing::measure("download", || response.body_mut().read_to_string())?;
+        log::info(&format!("{} ({} bytes)", status, text.len()));
+        log::dump(&format!("response {}", status), &text);
+        Ok((status.as_u16(), text))
+    }
+
+    fn request(
+        &self,
+        prompt: &Prompt,
+        thinking_budget: Option<i32>,
+    ) -> Result<Reply, Box<dyn std::error::Error>> {
+        let (status, text) = match &self.fixtures {
+            Some(Fixtures::Replay(dir)) => {
+                let path = dir.join(fixture_name(&self.model, thinking_budget, prompt));
+                log::info(&format!("replaying {}", path.display()));
+                load_fixture(&path)?
+            }
+            Some(Fixtures::Record(dir)) => {
+                let (status, text) = self.send(prompt, thinking_budget)?;
+                let path = dir.join(fixture_name(&self.model, thinking_budget, prompt));
+                log::info(&format!("recording {}", path.display()));
+                save_fixture(&path, &self.model, status, &text)?;
+                (status, text)
+            }
+            None => self.send(prompt, thinking_budget)?,
+        };
+        let status = ureq::http::StatusCode::from_u16(status)?;
+        if !status.is_success() {
+            return Err(api_error(status, text).into());
+        }
+        let body: GeminiResponse = timing::measure("parse", || serde_json::from_str(&text))?;
+        let candidate = body.candidates.into_iter().next();
+        let finish_reason = candidate.as_ref().and_then(|c| c.finish_reason.clone());
+        let text = candidate
+            .and_then(|c| c.content)
+            .and_then(|content| content.parts.into_iter().next())
+            .map(|part| part.text);
+        Ok(Reply {
+            text,
+            finish_reason,
+            prompt_feedback: body.prompt_feedback,
+        })
+    }
+
+    /// Answers `prompt`, recovering from the output limit: a thinking model
+    /// that wrote nothing is asked again with less room to think, and an
+    /// answer cut off midway is continued and stitched together.
+    fn answer(&self, prompt: &Prompt) -> Result<String, Box<dyn std::error::Error>> {
+        let mut reply = self.request(prompt, self.thinking_budget)?;
+        let can_think_less = !self
+            .thinking_budget
+            .is_some_and(|budget| (0..=RECOVERY_THINKING_BUDGET).contains(&budget));
+        if reply.is_truncated() && reply.text.is_none() && can_think_less {
+            log::info(&format!(
+                "no answer within the output limit, retrying with a thinking budget of {}",
+                RECOVERY_THINKING_BUDGET
+            ));
+            reply = self.request(prompt, Some(RECOVERY_THINKING_BUDGET))?;
+        }
+        let Some(mut answer) = reply.text.clone() else {
+            return Err(reply.error());
+        };
+        for _ in 0..MAX_CONTINUATIONS {
+            if !reply.is_truncated() {
+                return Ok(answer.trim().to_string());
+            }
+            log::info(&format!(
+                "answer cut off by the output limit after {} bytes, asking for the rest",
+                answer.len()
+            ));
+            reply = self.request(&continuation(prompt, &answer), self.thinking_budget)?;
+            match &reply.text {
+                Some(text) => answer.push_str(text),
+                None => return Err(reply.error()),
+            }
+        }
+        match reply.is_truncated() {
+            true => Err(tr(Message::OutputLimit, &[&MAX_CONTINUATIONS]).into()),
+            false => Ok(answer.trim().to_string()),
+        }
+    }
+}
+
+/// One candidate of a `generateContent` response.
+struct Reply {
+    /// The untrimmed text, so that continued answers can be stitched.
+    text: Option<String>,
+    finish_reason: Option<String>,
+    prompt_feedback: Option<serde_json::Value>,
+}
+
+impl Reply {
+    /// Whether the model stopped because it reached the output limit.
+    fn is_truncated(&self) -> bool {
+        self.finish_reason.as_deref() == Some("MAX_TOKENS")
+    }
+
+    /// Why there is no text, for a reply without any.
+    fn error(&self) -> Box<dyn std::error::Error> {
+        let reason = self
+            .finish_reason
+            .clone()
+            .unwrap_or_else(|| tr(Message::UnknownFinishReason, &[]));
+        let feedback_info = self
+            .prompt_feedback
+            .as_ref()
+            .map(|f| tr(Message::PromptFeedback, &[f]))
+            .unwrap_or_else(|| tr(Message::NoPromptFeedback, &[]));
+        tr(Message::NoText, &[&reason, &feedback_info]).into()
+    }
+}
+
+/// `prompt` with the answer cut off so far, asking for the rest of it. It
+/// goes out without a schema, since the rest of a JSON answer is not valid
+/// JSON on its own.
+pub fn continuation(prompt: &Prompt, answer: &str) -> Prompt {
+    Prompt {
+        system: prompt.system.clone(),
+        user: format!(
+            "{}\n\n## Answer So Far\n\nYour answer was cut off by the output limit. \
+             Continue it exactly where it stops, without repeating any of it or adding \
+             anything before the continuation:\n\n{}",
+            prompt.user, answer
+        ),
+        schema: None,
+        images: prompt.images.clone(),
+    }
+}
+
+/// A model the API key can use, from the `models` endpoint.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelInfo {
+    /// The id to pass to [`Gemini::model`], without the `models/` prefix.
+    pub name: String,
+    #[serde(default)]
+    pub display_name: String,
+    #[serde(default)]
+    pub input_token_limit: u64,
+    #[serde(default)]
+    pub output_token_limit: u64,
+    #[serde(default)]
+    supported_generation_methods: Vec<String>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct ModelList {
+    #[serde(default)]
+    models: Vec<ModelInfo>,
+    next_page_token: Option<String>,
+}
+
+impl Gemini {
+    /// Every model this key can call `generateContent` on, across all pages.
+    pub fn list_models(&self) -> Result<Vec<ModelInfo>, Box<dyn std::error::Error>> {
+        let url = format!("{}/models", self.base_url);
+        let mut models = Vec::new();
+        let mut page_token: Option<String> = None;
+        loop {
+            let mut request = AGENT.get(&url).query("pageSize", "1000");
+            if !self.api_key.is_empty() {
+                request = request.header("X-Goog-Api-Key", &self.api_key);
+            }
+            if let Some(token) = &page_token {
+                request = request.query("pageToken", token);
+            }
+            log::info(&format!("GET {}", url));
+            let mut response = request.call().map_err(|e| Error::Api(e.to_string()))?;
+            let status = response.status();
+            let text = response.body_mut().read_to_string()?;
+            log::dump(&format!("response {}", status), &text);
+            if !status.is_success() {
+                return Err(Error::Api(api_error(status, text)).into());
+            }
+            let page: ModelList = serde_json::from_str(&text)?;
+            models.extend(page.models.into_iter().filter_map(|mut model| {
+                model
+                    .supported_generation_methods
+                    .iter()
+                    .any(|method| method == "generateContent")
+                    .then(|| {
+                        model.name = model
+                            .name
+                            .strip_prefix("models/")
+                            .unwrap_or(&model.name)
+                            .to_string();
+                        model
+                    })
+            }));
+            match page.next_page_token.filter(|token| !token.is_empty()) {
+                Some(token) => page_token = Some(token),
+                None => return Ok(models),
+            }
+        }
+    }
+}
+
+impl Provider for Gemini {
+    fn generate(&self, prompt: &Prompt) -> Result<String, Box<dyn std::error::Error>> {
+        self.answer(prompt)
+            .map_err(|e| Error::Api(e.to_string()).into())
+    }
+}
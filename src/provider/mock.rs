@@ -0,0 +1,151 @@
+//! An offline provider that derives a deterministic message from the diff in
+//! the prompt, for demos, tests and hook development without network access.
+
+use super::Provider;
+use crate::diff::{self, FileDiff};
+use crate::prompt::{
+    BREAKING_CHANGE_CHECK_GUIDELINE, FILE_SUMMARY_GUIDELINE, GITMOJI_GUIDELINE, Prompt,
+    SPLIT_GUIDELINE, SUBJECT_GUIDELINE,
+};
+use crate::style::Style;
+
+pub struct Mock;
+
+fn is_docs(path: &str) -> bool {
+    path.ends_with(".md") || path.ends_with(".txt") || path.starts_with("docs/")
+}
+
+fn is_test(path: &str) -> bool {
+    path.contains("test")
+}
+
+fn kind(files: &[FileDiff]) -> &'static str {
+    if files.iter().all(|f| is_docs(&f.path)) {
+        "docs"
+    } else if files.iter().all(|f| is_test(&f.path)) {
+        "test"
+    } else if files.iter().any(|f| f.text.contains("\nnew file mode")) {
+        "feat"
+    } else {
+        "refactor"
+    }
+}
+
+/// The top-level directory shared by every file, if any.
+fn scope(files: &[FileDiff]) -> Option<&str> {
+    let first = files.first()?.path.split_once('/')?.0;
+    files
+        .iter()
+        .all(|f| f.path.split_once('/').is_some_and(|(dir, _)| dir == first))
+        .then_some(first)
+}
+
+/// `allowed` lists the types the schema permits; empty allows any.
+fn message(files: &[FileDiff], style: Style, allowed: &[&str]) -> String {
+    if files.is_empty() {
+        return match style {
+            Style::Conventional => "chore: update",
+            Style::Plain => "Update",
+            Style::Kernel => "tree: update",
+        }
+        .to_string();
+    }
+    let kind = match kind(files) {
+        kind if allowed.is_empty() || allowed.contains(&kind) => kind,
+        _ => allowed[0],
+    };
+    let verb = if kind == "feat" { "add" } else { "update" };
+    let subject = match files {
+        [file] => file
+            .path
+            .rsplit('/')
+            .next()
+            .unwrap_or(&file.path)
+            .to_string(),
+        _ => format!("{} files", files.len()),
+    };
+    let header = match (style, scope(files)) {
+        (Style::Conventional, Some(scope)) => format!("{}({}): {} {}", kind, scope, verb, subject),
+        (Style::Conventional, None) => format!("{}: {} {}", kind, verb, subject),
+        (Style::Plain, _) => format!("{}{} {}", verb[..1].to_uppercase(), &verb[1..], subject),
+        (Style::Kernel, scope) => format!("{}: {} {}", scope.unwrap_or("tree"), verb, subject),
+    };
+    let body: Vec<String> = files
+        .iter()
+        .map(|f| format!("- {} (+{} -{})", f.path, f.added, f.removed))
+        .collect();
+    format!("{}\n\n{}", header, body.join("\n"))
+}
+
+/// The files listed as `- path (+added -removed): summary` by the chunked
+/// pipeline, which sends summaries instead of the diff.
+fn summarized_files(text: &str) -> Vec<FileDiff> {
+    text.lines()
+        .filter_map(|line| {
+            let (path, rest) = line.strip_prefix("- ")?.split_once(" (+")?;
+            let (added, rest) = rest.split_once(" -")?;
+            let (removed, _) = rest.split_once("):")?;
+            Some(FileDiff {
+                path: path.to_string(),
+                hunks: 0,
+                added: added.parse().ok()?,
+                removed: removed.parse().ok()?,
+                text: String::new(),
+            })
+        })
+        .collect()
+}
+
+impl Provider for Mock {
+    fn generate(&self, prompt: &Prompt) -> Result<String, Box<dyn std::error::Error>> {
+        let style = [Style::Plain, Style::Kernel]
+            .into_iter()
+            .find(|style| prompt.system.contains(style.guideline().trim()))
+            .unwrap_or_default();
+        let allowed: Vec<&str> = prompt
+            .schema
+            .as_ref()
+            .and_then(|schema| schema["properties"]["type"]["enum"].as_array())
+            .map(|types| types.iter().filter_map(|t| t.as_str()).collect())
+            .unwrap_or_default();
+        let mut files = diff::split_files(&prompt.user);
+        if files.is_empty() {
+            files = summarized_files(&prompt.user);
+        }
+        if prompt.system.contains(FILE_SUMMARY_GUIDELINE.trim()) {
+            let summary = message(&files, style, &allowed);
+            return Ok(summary.lines().next().unwrap_or_default().to_string());
+        }
+        if prompt
+            .system
+            .contains(BREAKING_CHANGE_CHECK_GUIDELINE.trim())
+        {
+            return Ok("NONE".to_string());
+        }
+        if prompt.system.contains(SPLIT_GUIDELINE.trim()) {
+            let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+            return Ok(
+                serde_json::json!([{ "message": message(&files, style, &allowed), "files": paths }])
+                    .to_string(),
+            );
+        }
+        if prompt.system.contains(SUBJECT_GUIDELINE.trim()) {
+            let message = message(&files, style, &allowed);
+            let body = message.split_once("\n\n").map_or("", |(_, body)| body);
+            return Ok(body.to_string());
+        }
+        if prompt.system.contains(GITMOJI_GUIDELINE.trim()) {
+            let emoji = if kind(&files) == "feat" {
+                "✨"
+            } else {
+                "♻️"
+            };
+            return Ok(format!(
+                "{} {}",
+                emoji,
+                message(&files, Style::Plain, &allowed)
+            ));
+        }
+        Ok(message(&files, style, &allowed))
+    }
+}
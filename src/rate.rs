@@ -0,0 +1,108 @@
+//! Client-side limits on requests and tokens per minute, so that long
+//! `batch`, `watch` or `serve` runs wait for their quota instead of failing
+//! with `429` halfway through.
+//!
+//! Every request of the process counts against the same window, whichever
+//! thread or [`crate::Gemini`] sends it.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The span the limits apply to.
+pub const WINDOW: Duration = Duration::from_secs(60);
+
+/// Gemini counts an inline image as this many tokens.
+const IMAGE_TOKENS: u64 = 258;
+
+/// The most requests and tokens sent per minute; `None` is unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RateLimit {
+    pub requests_per_minute: Option<u64>,
+    pub tokens_per_minute: Option<u64>,
+}
+
+impl RateLimit {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// The requests sent within the last [`WINDOW`], with their tokens.
+#[derive(Debug, Default)]
+pub struct Window {
+    sent: VecDeque<(Instant, u64)>,
+}
+
+impl Window {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// How long a request of `tokens` has to wait at `now` to stay within
+    /// `limit`. A request larger than the token limit on its own only waits
+    /// for an empty window.
+    pub fn delay(&mut self, limit: RateLimit, tokens: u64, now: Instant) -> Duration {
+        while self
+            .sent
+            .front()
+            .is_some_and(|(at, _)| now.duration_since(*at) >= WINDOW)
+        {
+            self.sent.pop_front();
+        }
+        // The time at which the request fits once the oldest ones expire.
+        let mut until = now;
+        if let Some(max) = limit.requests_per_minute.map(|max| max.max(1) as usize)
+            && self.sent.len() >= max
+        {
+            until = until.max(self.sent[self.sent.len() - max].0 + WINDOW);
+        }
+        if let Some(max) = limit.tokens_per_minute {
+            let mut used: u64 = self.sent.iter().map(|(_, tokens)| tokens).sum();
+            let tokens = tokens.min(max);
+            for (at, expiring) in &self.sent {
+                if used + tokens <= max {
+                    break;
+                }
+                used -= expiring;
+                until = until.max(*at + WINDOW);
+            }
+        }
+        until - now
+    }
+
+    /// Counts a request of `tokens` sent at `at`.
+    pub fn record(&mut self, tokens: u64, at: Instant) {
+        self.sent.push_back((at, tokens));
+    }
+}
+
+static SENT: Mutex<Window> = Mutex::new(Window {
+    sent: VecDeque::new(),
+});
+
+/// A rough token count for a request: about four bytes of text per token.
+pub fn estimate_tokens(text_bytes: usize, images: usize) -> u64 {
+    (text_bytes as u64).div_ceil(4) + images as u64 * IMAGE_TOKENS
+}
+
+/// Blocks until a request of `tokens` fits within `limit`, counts it and
+/// returns how long it waited.
+pub fn acquire(limit: RateLimit, tokens: u64) -> Duration {
+    let started = Instant::now();
+    loop {
+        let delay = {
+            let mut sent = SENT.lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            let delay = sent.delay(limit, tokens, now);
+            if delay.is_zero() {
+                sent.record(tokens, now);
+                return started.elapsed();
+            }
+            delay
+        };
+        // Another thread may take the slot first, so check again after.
+        thread::sleep(delay);
+    }
+}
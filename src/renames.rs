@@ -0,0 +1,112 @@
+//! Changes that mostly move files around. Their diff lists every file under
+//! its new path, so the model tends to enumerate them; the moves grouped by
+//! directory (`src/utils/* → crates/core/src/*`) let it describe the
+//! restructuring instead.
+
+use crate::diff::FileDiff;
+use std::collections::BTreeMap;
+
+/// Fewer moved files are described well enough by the diff itself.
+const MIN_RENAMES: usize = 3;
+
+/// A file git detected as moved, from its `rename from`/`rename to` lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rename {
+    pub from: String,
+    pub to: String,
+    /// The `similarity index` of the old and new contents, in percent.
+    pub similarity: u8,
+}
+
+/// The renames among `files`.
+pub fn renames(files: &[FileDiff]) -> Vec<Rename> {
+    files
+        .iter()
+        .filter_map(|file| {
+            let header = file.text.split("\n@@").next().unwrap_or_default();
+            let field = |name: &str| {
+                header
+                    .lines()
+                    .find_map(|line| line.strip_prefix(name))
+                    .map(str::to_string)
+            };
+            Some(Rename {
+                from: field("rename from ")?,
+                to: field("rename to ")?,
+                similarity: field("similarity index ")
+                    .and_then(|value| value.trim_end_matches('%').parse().ok())
+                    .unwrap_or(100),
+            })
+        })
+        .collect()
+}
+
+/// The directories a rename moves between, once the path components both
+/// sides end with are left out: `src/utils/a.rs → crates/core/src/a.rs`
+/// moves `src/utils` to `crates/core/src`. A file renamed in place has no
+/// common end and is its own group.
+fn directories(rename: &Rename) -> (String, String, bool) {
+    let from: Vec<&str> = rename.from.split('/').collect();
+    let to: Vec<&str> = rename.to.split('/').collect();
+    let common = from
+        .iter()
+        .rev()
+        .zip(to.iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count()
+        .min(from.len() - 1)
+        .min(to.len() - 1);
+    (
+        from[..from.len() - common].join("/"),
+        to[..to.len() - common].join("/"),
+        common > 0,
+    )
+}
+
+/// The renames grouped by the directories they move between, largest group
+/// first, as `src/utils/* → crates/core/src/* (12 files)` lines.
+pub fn mapping(renames: &[Rename]) -> Vec<String> {
+    let mut groups: BTreeMap<(String, String, bool), usize> = BTreeMap::new();
+    for rename in renames {
+        *groups.entry(directories(rename)).or_default() += 1;
+    }
+    let mut groups: Vec<_> = groups.into_iter().collect();
+    groups.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    groups
+        .into_iter()
+        .map(|((from, to, is_directory), count)| {
+            let (from, to) = match is_directory {
+                true => (format!("{}/*", from), format!("{}/*", to)),
+                false => (from, to),
+            };
+            match count {
+                1 => format!("{} → {}", from, to),
+                _ => format!("{} → {} ({} files)", from, to, count),
+            }
+        })
+        .collect()
+}
+
+/// The moves summarized for the prompt when they make up at least half of
+/// the changed files, or `None` for an ordinary change.
+pub fn narrative(files: &[FileDiff]) -> Option<String> {
+    let renames = renames(files);
+    if renames.len() < MIN_RENAMES || renames.len() * 2 < files.len() {
+        return None;
+    }
+    let unchanged = renames.iter().filter(|r| r.similarity == 100).count();
+    Some(format!(
+        "{} of the {} changed files are moved or renamed ({} without changes to their content):\n{}\n\n\
+         This change restructures the code. Describe the restructuring as a whole, as a `refactor` \
+         where the message format has types, and mention any other change it contains; do not \
+         list every moved file.",
+        renames.len(),
+        files.len(),
+        unchanged,
+        mapping(&renames)
+            .iter()
+            .map(|line| format!("- {}", line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    ))
+}
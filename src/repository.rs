@@ -0,0 +1,127 @@
+//! Lightweight facts about the repository — its name, main languages and
+//! top-level layout — so generated scopes and wording match the project.
+
+use crate::git;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Top-level entries listed at most, to keep the section short.
+const MAX_ENTRIES: usize = 40;
+
+/// Languages by file extension; other files do not count.
+const LANGUAGES: &[(&str, &str)] = &[
+    ("rs", "Rust"),
+    ("go", "Go"),
+    ("py", "Python"),
+    ("js", "JavaScript"),
+    ("mjs", "JavaScript"),
+    ("cjs", "JavaScript"),
+    ("jsx", "JavaScript"),
+    ("ts", "TypeScript"),
+    ("tsx", "TypeScript"),
+    ("java", "Java"),
+    ("kt", "Kotlin"),
+    ("swift", "Swift"),
+    ("c", "C"),
+    ("h", "C"),
+    ("cc", "C++"),
+    ("cpp", "C++"),
+    ("hpp", "C++"),
+    ("cs", "C#"),
+    ("rb", "Ruby"),
+    ("php", "PHP"),
+    ("scala", "Scala"),
+    ("ex", "Elixir"),
+    ("exs", "Elixir"),
+    ("hs", "Haskell"),
+    ("lua", "Lua"),
+    ("dart", "Dart"),
+    ("zig", "Zig"),
+    ("sh", "Shell"),
+    ("vue", "Vue"),
+    ("svelte", "Svelte"),
+];
+
+/// The project name from the first manifest that declares one.
+fn project_name(root: &Path) -> Option<String> {
+    let read = |name: &str| fs::read_to_string(root.join(name)).ok();
+    let toml_name = |text: String, table: &str| {
+        let value: toml::Table = toml::from_str(&text).ok()?;
+        value.get(table)?.get("name")?.as_str().map(str::to_string)
+    };
+    read("Cargo.toml")
+        .and_then(|text| toml_name(text, "package"))
+        .or_else(|| {
+            let value: Value = serde_json::from_str(&read("package.json")?).ok()?;
+            value["name"].as_str().map(str::to_string)
+        })
+        .or_else(|| read("pyproject.toml").and_then(|text| toml_name(text, "project")))
+        .or_else(|| {
+            let text = read("go.mod")?;
+            let line = text.lines().find(|l| l.starts_with("module "))?;
+            Some(line["module ".len()..].trim().to_string())
+        })
+}
+
+/// The languages making up most of `files`, with their share of them.
+fn languages(files: &[&str]) -> Vec<(&'static str, usize)> {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for file in files {
+        let extension = file.rsplit_once('.').map_or("", |(_, ext)| ext);
+        if let Some((_, language)) = LANGUAGES.iter().find(|(ext, _)| *ext == extension) {
+            *counts.entry(language).or_default() += 1;
+        }
+    }
+    let total: usize = counts.values().sum();
+    let mut languages: Vec<(&str, usize)> = counts
+        .into_iter()
+        .map(|(language, count)| (language, count * 100 / total))
+        .collect();
+    languages.sort_by_key(|(_, share)| std::cmp::Reverse(*share));
+    languages.truncate(3);
+    languages
+}
+
+/// Describes the repository at `repo` as a few lines of text, or `None`
+/// outside a work tree.
+pub fn describe(repo: Option<&Path>) -> Option<String> {
+    let root = git::toplevel(repo).ok()?;
+    let listing = git::output(Some(&root), &["ls-files", "-z"]).ok()?;
+    let files: Vec<&str> = listing.split('\0').filter(|f| !f.is_empty()).collect();
+
+    let mut lines = Vec::new();
+    let name = project_name(&root).or_else(|| {
+        root.file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+    });
+    if let Some(name) = name {
+        lines.push(format!("Project: {}", name));
+    }
+    let languages: Vec<String> = languages(&files)
+        .iter()
+        .map(|(language, share)| format!("{} ({}%)", language, share))
+        .collect();
+    if !languages.is_empty() {
+        lines.push(format!("Languages: {}", languages.join(", ")));
+    }
+    let mut entries: Vec<String> = files
+        .iter()
+        .map(|file| match file.split_once('/') {
+            Some((dir, _)) => format!("{}/", dir),
+            None => file.to_string(),
+        })
+        .collect();
+    entries.sort();
+    entries.dedup();
+    if entries.len() > MAX_ENTRIES {
+        let more = entries.len() - MAX_ENTRIES;
+        entries.truncate(MAX_ENTRIES);
+        entries.push(format!("... and {} more", more));
+    }
+    if !entries.is_empty() {
+        lines.push(format!("Top-level entries: {}", entries.join(", ")));
+    }
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
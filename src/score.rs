@@ -0,0 +1,96 @@
+//! Scores candidate messages locally, so that the most useful of several
+//! comes first: one that passes the preset's checks, has a header of a
+//! comfortable length, avoids vague wording and names what the diff changes.
+
+use crate::diff;
+use crate::log;
+use crate::message::CommitMessage;
+use crate::preset::Preset;
+use std::collections::BTreeSet;
+
+/// Words that say little about a change on their own.
+const VAGUE: [&str; 14] = [
+    "change", "changes", "code", "files", "minor", "misc", "some", "stuff", "things", "tweak",
+    "tweaks", "various", "wip", "work",
+];
+
+/// Diffs with more changed lines than this deserve a body.
+const BODY_LINES: usize = 20;
+
+/// File names and directories of `diff`, lowercased, without extensions
+/// and too short to be telling.
+fn names(diff: &str) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    for file in diff::split_files(diff) {
+        let mut parts = file.path.rsplit('/');
+        if let Some(name) = parts.next() {
+            names.insert(name.split('.').next().unwrap_or(name).to_lowercase());
+        }
+        if let Some(dir) = parts.next() {
+            names.insert(dir.to_lowercase());
+        }
+    }
+    names.retain(|name| name.chars().count() >= 3);
+    names
+}
+
+/// A higher score is better; only the order of scores matters.
+pub fn score(message: &CommitMessage, preset: &Preset, diff: &str) -> i32 {
+    let mut score = 0;
+    if preset.check(message).is_ok() {
+        score += 50;
+    }
+    score += match message.header.chars().count() {
+        0 => -50,
+        1..=50 => 15,
+        51..=72 => 5,
+        _ => -10,
+    };
+    let summary = message
+        .conventional()
+        .map_or(message.header.as_str(), |header| header.description)
+        .to_lowercase();
+    let words: Vec<&str> = summary
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+    score -= 4 * words.iter().filter(|w| VAGUE.contains(w)).count() as i32;
+    if words.len() < 3 {
+        score -= 5;
+    }
+
+    let names = names(diff);
+    if !names.is_empty() {
+        let text = message.to_string().to_lowercase();
+        let mentioned = names
+            .iter()
+            .filter(|name| text.contains(name.as_str()))
+            .count();
+        score += (20 * mentioned / names.len()) as i32;
+    }
+    let changed: usize = diff::split_files(diff)
+        .iter()
+        .map(|file| file.added + file.removed)
+        .sum();
+    if changed > BODY_LINES && message.body.is_some() {
+        score += 5;
+    }
+    score
+}
+
+/// Sorts `candidates` best first; equal scores keep the model's order.
+pub fn rank(candidates: &mut [CommitMessage], preset: &Preset, diff: &str) {
+    if candidates.len() < 2 {
+        return;
+    }
+    let mut scored: Vec<(i32, CommitMessage)> = candidates
+        .iter()
+        .map(|candidate| (score(candidate, preset, diff), candidate.clone()))
+        .collect();
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    let scores: Vec<String> = scored.iter().map(|(score, _)| score.to_string()).collect();
+    log::info(&format!("candidate scores: {}", scores.join(", ")));
+    for (candidate, (_, message)) in candidates.iter_mut().zip(scored) {
+        *candidate = message;
+    }
+}
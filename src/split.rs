@@ -0,0 +1,45 @@
+//! Proposals to split one staged change into several commits.
+
+use crate::message::CommitMessage;
+use serde::Deserialize;
+
+/// Files that belong in one commit, with the message for that commit.
+#[derive(Debug, Clone)]
+pub struct CommitGroup {
+    pub message: CommitMessage,
+    pub files: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct RawGroup {
+    message: String,
+    files: Vec<String>,
+}
+
+/// Parses the JSON array requested by [`crate::prompt::SPLIT_GUIDELINE`],
+/// tolerating a surrounding ```json fence.
+pub fn parse_groups(text: &str) -> Result<Vec<CommitGroup>, Box<dyn std::error::Error>> {
+    let text = text.trim();
+    let text = text
+        .strip_prefix("```json")
+        .or_else(|| text.strip_prefix("```"))
+        .and_then(|rest| rest.strip_suffix("```"))
+        .unwrap_or(text);
+    let groups: Vec<RawGroup> = serde_json::from_str(text)
+        .map_err(|e| format!("unexpected split proposal ({}): {}", e, text))?;
+    Ok(groups
+        .into_iter()
+        .map(|group| CommitGroup {
+            message: CommitMessage::parse(&group.message),
+            files: group.files,
+        })
+        .collect())
+}
+
+/// Files of `files` that no group claims.
+pub fn unassigned<'a>(groups: &[CommitGroup], files: &'a [String]) -> Vec<&'a String> {
+    files
+        .iter()
+        .filter(|file| !groups.iter().any(|group| group.files.contains(file)))
+        .collect()
+}
@@ -0,0 +1,77 @@
+//! The message formats the tool can write: Conventional Commits (the
+//! default), a plain subject and body, or the Linux kernel's
+//! `subsystem: summary`.
+
+use crate::i18n::{Message, tr};
+use crate::message::CommitMessage;
+use crate::prompt::{COMMIT_MESSAGE_GUIDELINE, KERNEL_GUIDELINE, PLAIN_GUIDELINE};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Style {
+    #[default]
+    Conventional,
+    Plain,
+    Kernel,
+}
+
+impl Style {
+    pub const NAMES: [&'static str; 3] = ["conventional", "plain", "kernel"];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "conventional" => Some(Style::Conventional),
+            "plain" => Some(Style::Plain),
+            "kernel" => Some(Style::Kernel),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Style::Conventional => "conventional",
+            Style::Plain => "plain",
+            Style::Kernel => "kernel",
+        }
+    }
+
+    pub fn guideline(self) -> &'static str {
+        match self {
+            Style::Conventional => COMMIT_MESSAGE_GUIDELINE,
+            Style::Plain => PLAIN_GUIDELINE,
+            Style::Kernel => KERNEL_GUIDELINE,
+        }
+    }
+
+    /// Checks the header of a generated message and explains what is wrong.
+    pub fn check(self, message: &CommitMessage) -> Result<(), String> {
+        let header = message.header.as_str();
+        let length = header.chars().count();
+        match self {
+            Style::Conventional if message.conventional().is_none() => {
+                Err(tr(Message::NotConventional, &[&header]))
+            }
+            Style::Plain if header.is_empty() || length > 72 => {
+                Err(tr(Message::SubjectTooLong, &[&72, &header]))
+            }
+            Style::Plain if header.ends_with('.') => Err(tr(Message::SubjectFullStop, &[&header])),
+            Style::Kernel if length > 75 => Err(tr(Message::SubjectTooLong, &[&75, &header])),
+            Style::Kernel if !is_kernel_header(header) => {
+                Err(tr(Message::NotKernelStyle, &[&header]))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// `subsystem: summary`, where the subsystem may be a path such as `net/ipv4`
+/// or a list such as `mm, slab`.
+fn is_kernel_header(header: &str) -> bool {
+    let Some((subsystem, summary)) = header.split_once(": ") else {
+        return false;
+    };
+    !subsystem.is_empty()
+        && !summary.trim().is_empty()
+        && subsystem
+            .chars()
+            .all(|c| c.is_alphanumeric() || "_-./, ".contains(c))
+}
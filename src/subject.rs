@@ -0,0 +1,192 @@
+//! House rules for the summary in the first line: its mood, whether it
+//! starts with a capital letter and whether it ends with a period.
+//!
+//! The rules are added to the prompt and applied again to the answer, so a
+//! model that ignores them still produces the expected line.
+
+use serde::Deserialize;
+
+/// The grammatical mood of the summary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mood {
+    /// "add", "fix"
+    Imperative,
+    /// "added", "fixed"
+    Past,
+}
+
+impl Mood {
+    pub const NAMES: [&'static str; 2] = ["imperative", "past"];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "imperative" => Some(Mood::Imperative),
+            "past" => Some(Mood::Past),
+            _ => None,
+        }
+    }
+}
+
+/// Verbs commit summaries commonly start with, as imperative, past tense
+/// and third person; other first words are left to the prompt.
+const VERBS: &[(&str, &str, &str)] = &[
+    ("add", "added", "adds"),
+    ("allow", "allowed", "allows"),
+    ("avoid", "avoided", "avoids"),
+    ("build", "built", "builds"),
+    ("bump", "bumped", "bumps"),
+    ("change", "changed", "changes"),
+    ("check", "checked", "checks"),
+    ("clean", "cleaned", "cleans"),
+    ("correct", "corrected", "corrects"),
+    ("create", "created", "creates"),
+    ("delete", "deleted", "deletes"),
+    ("disable", "disabled", "disables"),
+    ("document", "documented", "documents"),
+    ("drop", "dropped", "drops"),
+    ("enable", "enabled", "enables"),
+    ("ensure", "ensured", "ensures"),
+    ("expose", "exposed", "exposes"),
+    ("extract", "extracted", "extracts"),
+    ("fix", "fixed", "fixes"),
+    ("handle", "handled", "handles"),
+    ("implement", "implemented", "implements"),
+    ("improve", "improved", "improves"),
+    ("increase", "increased", "increases"),
+    ("introduce", "introduced", "introduces"),
+    ("keep", "kept", "keeps"),
+    ("limit", "limited", "limits"),
+    ("make", "made", "makes"),
+    ("merge", "merged", "merges"),
+    ("move", "moved", "moves"),
+    ("optimize", "optimized", "optimizes"),
+    ("pass", "passed", "passes"),
+    ("prevent", "prevented", "prevents"),
+    ("reduce", "reduced", "reduces"),
+    ("refactor", "refactored", "refactors"),
+    ("remove", "removed", "removes"),
+    ("rename", "renamed", "renames"),
+    ("replace", "replaced", "replaces"),
+    ("return", "returned", "returns"),
+    ("revert", "reverted", "reverts"),
+    ("run", "ran", "runs"),
+    ("show", "showed", "shows"),
+    ("simplify", "simplified", "simplifies"),
+    ("skip", "skipped", "skips"),
+    ("stop", "stopped", "stops"),
+    ("support", "supported", "supports"),
+    ("tidy", "tidied", "tidies"),
+    ("update", "updated", "updates"),
+    ("upgrade", "upgraded", "upgrades"),
+    ("use", "used", "uses"),
+    ("validate", "validated", "validates"),
+    ("write", "wrote", "writes"),
+];
+
+/// Rules for the summary; `None` leaves that aspect to the guideline.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SubjectStyle {
+    pub mood: Option<Mood>,
+    /// Whether the summary starts with a capital letter.
+    pub capitalize: Option<bool>,
+    /// Whether the summary ends with a period.
+    pub period: Option<bool>,
+}
+
+/// `word` in `mood` if it is a known verb, keeping a leading capital.
+fn conjugate(word: &str, mood: Mood) -> Option<String> {
+    let lower = word.to_lowercase();
+    let &(imperative, past, _) = VERBS
+        .iter()
+        .find(|(imperative, past, third)| [imperative, past, third].contains(&&lower.as_str()))?;
+    let form = match mood {
+        Mood::Imperative => imperative,
+        Mood::Past => past,
+    };
+    Some(match word.chars().next() {
+        Some(first) if first.is_uppercase() => capitalized(form, true),
+        _ => form.to_string(),
+    })
+}
+
+/// `text` with its first letter in upper or lower case. Words such as
+/// `API` or `iOS`, which are not plain capitalized words, are left alone.
+fn capitalized(text: &str, upper: bool) -> String {
+    let mut chars = text.chars();
+    let Some(first) = chars.next() else {
+        return String::new();
+    };
+    let rest = chars.as_str();
+    let word = rest
+        .split(|c: char| !c.is_alphanumeric())
+        .next()
+        .unwrap_or("");
+    if word.chars().any(char::is_uppercase) {
+        return text.to_string();
+    }
+    let first: String = match upper {
+        true => first.to_uppercase().collect(),
+        false => first.to_lowercase().collect(),
+    };
+    format!("{}{}", first, rest)
+}
+
+impl SubjectStyle {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The rules as instructions for the model, or `None` without any.
+    pub fn guideline(&self) -> Option<String> {
+        let mut rules = Vec::new();
+        match self.mood {
+            Some(Mood::Imperative) => rules.push(
+                "The summary MUST use the imperative mood (\"add\", \"fix\"), not the past tense or third person.",
+            ),
+            Some(Mood::Past) => rules.push(
+                "The summary MUST use the past tense (\"added\", \"fixed\"), not the imperative mood.",
+            ),
+            None => {}
+        }
+        match self.capitalize {
+            Some(true) => rules.push("The summary MUST start with a capital letter."),
+            Some(false) => rules.push("The summary MUST start with a lowercase letter."),
+            None => {}
+        }
+        match self.period {
+            Some(true) => rules.push("The summary MUST end with a period."),
+            Some(false) => rules.push("The summary MUST NOT end with a period."),
+            None => {}
+        }
+        (!rules.is_empty()).then(|| {
+            format!(
+                "House style for the summary in the first line, which takes precedence over the rules above:\n{}",
+                rules.join("\n")
+            )
+        })
+    }
+
+    /// Rewrites `summary` to follow the rules as far as it can be done
+    /// without the model.
+    pub fn apply(&self, summary: &str) -> String {
+        let mut summary = summary.trim().to_string();
+        if let Some(mood) = self.mood {
+            let (word, rest) = summary.split_once(' ').unwrap_or((&summary, ""));
+            if let Some(word) = conjugate(word, mood) {
+                summary = format!("{} {}", word, rest).trim_end().to_string();
+            }
+        }
+        if let Some(upper) = self.capitalize {
+            summary = capitalized(&summary, upper);
+        }
+        match self.period {
+            Some(true) if !summary.ends_with(['.', '!', '?']) => summary.push('.'),
+            Some(false) if summary.ends_with('.') && !summary.ends_with("..") => {
+                summary.pop();
+            }
+            _ => {}
+        }
+        summary
+    }
+}
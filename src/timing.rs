@@ -0,0 +1,40 @@
+//! Wall-clock time per phase of a run, reported by `--timings`.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+static PHASES: Mutex<Vec<(&'static str, Duration)>> = Mutex::new(Vec::new());
+
+pub fn record(phase: &'static str, duration: Duration) {
+    let mut phases = PHASES.lock().unwrap_or_else(|e| e.into_inner());
+    match phases.iter_mut().find(|(name, _)| *name == phase) {
+        Some((_, total)) => *total += duration,
+        None => phases.push((phase, duration)),
+    }
+}
+
+/// Runs `f` and adds its duration to `phase`.
+pub fn measure<T>(phase: &'static str, f: impl FnOnce() -> T) -> T {
+    let started = Instant::now();
+    let result = f();
+    record(phase, started.elapsed());
+    result
+}
+
+/// One line per phase in the order they first ran, e.g. `network  412 ms`.
+pub fn report() -> String {
+    let phases = PHASES.lock().unwrap_or_else(|e| e.into_inner());
+    let width = phases.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    phases
+        .iter()
+        .map(|(name, duration)| {
+            format!(
+                "{:width$}  {:>6} ms",
+                name,
+                duration.as_millis(),
+                width = width
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,129 @@
+//! Prebuilt binaries published as GitHub release assets.
+//!
+//! Each release carries one asset per platform, named
+//! `gemini-commit-message-<arch>-<os>` (`.exe` on Windows), and a
+//! `SHA256SUMS` file listing their checksums as `sha256sum` prints them.
+
+use crate::version::Version;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::env;
+use std::io::Read;
+
+/// The asset listing the checksums of the others.
+pub const CHECKSUMS: &str = "SHA256SUMS";
+
+/// Binaries larger than this are not downloaded.
+const MAX_DOWNLOAD_BYTES: u64 = 200 * 1024 * 1024;
+
+/// A published release and the assets attached to it.
+#[derive(Debug, Clone)]
+pub struct Release {
+    pub tag: String,
+    /// Names and download URLs.
+    pub assets: Vec<(String, String)>,
+}
+
+impl Release {
+    pub fn version(&self) -> Option<Version> {
+        Version::parse(&self.tag)
+    }
+
+    pub fn asset_url(&self, name: &str) -> Option<&str> {
+        self.assets
+            .iter()
+            .find(|(asset, _)| asset == name)
+            .map(|(_, url)| url.as_str())
+    }
+}
+
+/// `owner/name` of the repository releases are published in:
+/// `GCM_UPDATE_REPO`, or the one this binary was built from.
+pub fn repository() -> Option<String> {
+    env::var("GCM_UPDATE_REPO")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .or_else(|| {
+            let url = env!("CARGO_PKG_REPOSITORY");
+            url.strip_prefix("https://github.com/")
+                .map(|path| path.trim_end_matches('/').to_string())
+        })
+}
+
+/// The name of the asset built for the running platform.
+pub fn asset_name() -> String {
+    format!(
+        "{}-{}-{}{}",
+        env!("CARGO_PKG_NAME"),
+        env::consts::ARCH,
+        env::consts::OS,
+        env::consts::EXE_SUFFIX
+    )
+}
+
+fn get(url: &str) -> ureq::RequestBuilder<ureq::typestate::WithoutBody> {
+    let request = ureq::get(url).header("User-Agent", env!("CARGO_PKG_NAME"));
+    match env::var("GITHUB_TOKEN").ok().filter(|v| !v.is_empty()) {
+        Some(token) => request.header("Authorization", &format!("Bearer {}", token)),
+        None => request,
+    }
+}
+
+/// The latest release of `repository` (`owner/name`).
+pub fn latest(repository: &str) -> Result<Release, Box<dyn std::error::Error>> {
+    let url = format!(
+        "https://api.github.com/repos/{}/releases/latest",
+        repository
+    );
+    let response: Value = get(&url)
+        .header("Accept", "application/vnd.github+json")
+        .call()?
+        .body_mut()
+        .read_json()?;
+    let tag = response["tag_name"]
+        .as_str()
+        .ok_or("the latest release has no tag")?
+        .to_string();
+    let assets = response["assets"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|asset| {
+            Some((
+                asset["name"].as_str()?.to_string(),
+                asset["browser_download_url"].as_str()?.to_string(),
+            ))
+        })
+        .collect();
+    Ok(Release { tag, assets })
+}
+
+/// Downloads an asset.
+pub fn download(url: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut bytes = Vec::new();
+    get(url)
+        .call()?
+        .into_body()
+        .into_reader()
+        .take(MAX_DOWNLOAD_BYTES)
+        .read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// The checksum `sums` (a `SHA256SUMS` file) lists for `name`, lowercase.
+pub fn expected_checksum(sums: &str, name: &str) -> Option<String> {
+    sums.lines().find_map(|line| {
+        let (hash, file) = line.trim().split_once(char::is_whitespace)?;
+        // `sha256sum -b` marks binary files with `*`.
+        let file = file.trim_start().trim_start_matches('*');
+        (file == name).then(|| hash.to_ascii_lowercase())
+    })
+}
+
+/// Hex SHA-256 of `bytes`.
+pub fn checksum(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
@@ -0,0 +1,93 @@
+//! Semantic version bumps derived from Conventional Commits.
+
+use crate::commits::Commit;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Bump {
+    Patch,
+    Minor,
+    Major,
+}
+
+impl fmt::Display for Bump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Bump::Patch => "patch",
+            Bump::Minor => "minor",
+            Bump::Major => "major",
+        })
+    }
+}
+
+/// The largest bump the commits call for: breaking changes are major,
+/// `feat` is minor and everything else is a patch.
+pub fn bump_for(commits: &[Commit]) -> Option<Bump> {
+    commits
+        .iter()
+        .map(|commit| {
+            if commit.message.is_breaking() {
+                return Bump::Major;
+            }
+            match commit.message.conventional() {
+                Some(header) if header.kind.eq_ignore_ascii_case("feat") => Bump::Minor,
+                _ => Bump::Patch,
+            }
+        })
+        .max()
+}
+
+/// A `MAJOR.MINOR.PATCH` version; a leading `v` is kept for display.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Version {
+    pub prefix: String,
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl Version {
+    /// Parses `1.2.3` or `v1.2.3`; pre-release and build suffixes are dropped.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let (prefix, rest) = match text.strip_prefix('v') {
+            Some(rest) => ("v", rest),
+            None => ("", text),
+        };
+        let core = rest.split(['-', '+']).next()?;
+        let mut parts = core.split('.').map(|part| part.parse::<u64>().ok());
+        let version = Self {
+            prefix: prefix.to_string(),
+            major: parts.next()??,
+            minor: parts.next()??,
+            patch: parts.next()??,
+        };
+        parts.next().is_none().then_some(version)
+    }
+
+    /// Applies `bump`; before 1.0.0 breaking changes only bump the minor version.
+    pub fn bump(&self, bump: Bump) -> Self {
+        let (major, minor, patch) = match bump {
+            Bump::Major if self.major == 0 => (0, self.minor + 1, 0),
+            Bump::Major => (self.major + 1, 0, 0),
+            Bump::Minor => (self.major, self.minor + 1, 0),
+            Bump::Patch => (self.major, self.minor, self.patch + 1),
+        };
+        Self {
+            prefix: self.prefix.clone(),
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}.{}.{}",
+            self.prefix, self.major, self.minor, self.patch
+        )
+    }
+}
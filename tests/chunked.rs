@@ -0,0 +1,106 @@
+//! The chunked pipeline against providers that record how many requests
+//! overlap and how many summaries they write.
+
+use gemini_commit_message::cache::Cache;
+use gemini_commit_message::prompt::FILE_SUMMARY_GUIDELINE;
+use gemini_commit_message::{Generator, Prompt, Provider};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+#[derive(Default)]
+struct Counts {
+    in_flight: AtomicUsize,
+    max_in_flight: AtomicUsize,
+    summaries: AtomicUsize,
+}
+
+struct Slow(Arc<Counts>);
+
+impl Provider for Slow {
+    fn generate(&self, prompt: &Prompt) -> Result<String, Box<dyn std::error::Error>> {
+        if !prompt.system.contains(FILE_SUMMARY_GUIDELINE.trim()) {
+            assert!(prompt.user.contains("- src/b.rs (+1 -0): summary"));
+            return Ok("refactor: touch every file".to_string());
+        }
+        let now = self.0.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.0.max_in_flight.fetch_max(now, Ordering::SeqCst);
+        thread::sleep(Duration::from_millis(50));
+        self.0.in_flight.fetch_sub(1, Ordering::SeqCst);
+        self.0.summaries.fetch_add(1, Ordering::SeqCst);
+        Ok("summary".to_string())
+    }
+}
+
+fn diff(files: usize) -> String {
+    (0..files)
+        .map(|i| {
+            let name = (b'a' + i as u8) as char;
+            format!(
+                "diff --git a/src/{0}.rs b/src/{0}.rs\n--- a/src/{0}.rs\n+++ b/src/{0}.rs\n@@ -1 +1,2 @@\n line\n+added\n",
+                name
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn summarizes_files_concurrently_up_to_the_limit() {
+    let counts = Arc::new(Counts::default());
+    let message = Generator::new(Slow(counts.clone()))
+        .chunked(true)
+        .concurrency(3)
+        .generate_for_diff(&diff(8))
+        .unwrap();
+    assert_eq!(message.header, "refactor: touch every file");
+    assert_eq!(counts.summaries.load(Ordering::SeqCst), 8);
+    let max = counts.max_in_flight.load(Ordering::SeqCst);
+    assert!((2..=3).contains(&max), "{} requests overlapped", max);
+}
+
+/// Counts the summaries it writes.
+struct Counting(Arc<AtomicUsize>);
+
+impl Provider for Counting {
+    fn generate(&self, prompt: &Prompt) -> Result<String, Box<dyn std::error::Error>> {
+        if !prompt.system.contains(FILE_SUMMARY_GUIDELINE.trim()) {
+            return Ok("refactor: touch every file".to_string());
+        }
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Ok("summary".to_string())
+    }
+}
+
+/// A diff of three files whose new blob ids are `blobs`.
+fn diff_with_blobs(blobs: [&str; 3]) -> String {
+    blobs
+        .iter()
+        .zip(["a", "b", "c"])
+        .map(|(blob, name)| {
+            format!(
+                "diff --git a/{0}.rs b/{0}.rs\nindex 1111111..{1} 100644\n--- a/{0}.rs\n+++ b/{0}.rs\n@@ -1 +1,2 @@\n line\n+{1}\n",
+                name, blob
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn summarizes_only_files_whose_blobs_changed() {
+    let dir = std::env::temp_dir().join(format!("gcm-summary-cache-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let summaries = Arc::new(AtomicUsize::new(0));
+    let generator = Generator::new(Counting(summaries.clone()))
+        .chunked(true)
+        .summary_cache(Cache::at(&dir), "model");
+    generator
+        .generate_for_diff(&diff_with_blobs(["aaaaaaa", "bbbbbbb", "ccccccc"]))
+        .unwrap();
+    assert_eq!(summaries.load(Ordering::SeqCst), 3);
+    generator
+        .generate_for_diff(&diff_with_blobs(["aaaaaaa", "bbbbbbb", "ddddddd"]))
+        .unwrap();
+    assert_eq!(summaries.load(Ordering::SeqCst), 4);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,128 @@
+//! The command line as users call it, through the built binary.
+
+use std::process::{Command, Output, Stdio};
+
+fn command(args: &[&str]) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_gemini-commit-message"));
+    command
+        .args(args)
+        .env("LC_ALL", "C")
+        .env("GIT_CONFIG_GLOBAL", "/dev/null")
+        .env("GIT_CONFIG_NOSYSTEM", "1");
+    command
+}
+
+fn run(args: &[&str]) -> Output {
+    command(args).output().unwrap()
+}
+
+/// Runs on a diff from stdin with the mock provider and `max_diff_bytes`
+/// as `GEMINI_MAX_DIFF_BYTES`.
+fn run_with_max_diff_bytes(max_diff_bytes: &str) -> Output {
+    command(&["--stdin", "--no-cache", "-q"])
+        .env("GCM_MOCK", "1")
+        .env("GEMINI_MAX_DIFF_BYTES", max_diff_bytes)
+        .stdin(Stdio::null())
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn a_value_flag_at_the_end_is_an_error() {
+    for flag in ["--candidates", "-n", "-C", "--model"] {
+        let output = run(&[flag]);
+        assert_eq!(output.status.code(), Some(4), "{}", flag);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains(&format!("{} needs a value", flag)),
+            "{}",
+            stderr
+        );
+    }
+}
+
+/// The subcommands `main` dispatches, from its `Some("name") => return` arms.
+fn dispatched() -> Vec<&'static str> {
+    include_str!("../src/main.rs")
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("Some(\""))
+        .filter_map(|rest| rest.split_once("\") => return cli::"))
+        .map(|(name, _)| name)
+        .collect()
+}
+
+#[test]
+fn every_dispatched_subcommand_is_listed() {
+    let source = include_str!("../src/cli.rs");
+    let start = source.find("pub const SUBCOMMANDS").unwrap();
+    let listed = &source[start..start + source[start..].find("\n];").unwrap()];
+    let names = dispatched();
+    assert!(names.len() > 20, "{:?}", names);
+    for name in names {
+        assert!(
+            listed.contains(&format!("\"{}\",", name)),
+            "{} is missing from SUBCOMMANDS",
+            name
+        );
+    }
+}
+
+/// Whether `word` appears in `text` on its own, not as part of a longer
+/// word such as `pr` in `prompt`.
+fn mentions(text: &str, word: &str) -> bool {
+    let is_word = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+    text.match_indices(word).any(|(at, _)| {
+        !text[..at].ends_with(is_word) && !text[at + word.len()..].starts_with(is_word)
+    })
+}
+
+#[test]
+fn completions_offer_every_subcommand() {
+    for shell in ["bash", "zsh", "fish", "powershell"] {
+        let output = run(&["completions", shell]);
+        assert!(output.status.success(), "{}", shell);
+        let script = String::from_utf8_lossy(&output.stdout);
+        for name in dispatched() {
+            assert!(mentions(&script, name), "{} misses {}", shell, name);
+        }
+    }
+}
+
+#[test]
+fn completions_for_an_unknown_shell_fail() {
+    let output = run(&["completions", "zhs"]);
+    assert_eq!(output.status.code(), Some(4));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("bash, zsh, fish, powershell"), "{}", stderr);
+}
+
+#[test]
+fn rejects_invalid_and_overflowing_diff_sizes() {
+    for value in ["abc", "0", "12q", "99999999999999999M"] {
+        let output = run_with_max_diff_bytes(value);
+        assert_eq!(output.status.code(), Some(4), "{}", value);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains(&format!("Invalid maxDiffBytes '{}'", value)),
+            "{}",
+            stderr
+        );
+    }
+}
+
+#[test]
+fn rejects_invalid_candidate_counts() {
+    for (flag, value) in [("-n", "abc"), ("-n", "0"), ("--candidates", "-3")] {
+        let output = run(&[flag, value]);
+        assert_eq!(output.status.code(), Some(4), "{} {}", flag, value);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains(&format!(
+                "Invalid number of candidates '{}' ({})",
+                value, flag
+            )),
+            "{}",
+            stderr
+        );
+    }
+}
@@ -0,0 +1,17 @@
+//! Decoding diffs with legacy encodings file by file.
+
+use gemini_commit_message::encoding::decode;
+
+#[test]
+fn decodes_each_file_on_its_own() {
+    let mut diff = b"diff --git a/a.txt b/a.txt\n+caf\xe9 \x80\n".to_vec();
+    // "日本語" and half-width "ﾃｽﾄ" in Shift_JIS.
+    diff.extend_from_slice(b"diff --git a/b.txt b/b.txt\n+\x93\xfa\x96\x7b\x8c\xea \xc3\xbd\xc4\n");
+    diff.extend_from_slice("diff --git a/c.txt b/c.txt\n+ok ✓\n".as_bytes());
+    assert_eq!(
+        decode(&diff),
+        "diff --git a/a.txt b/a.txt\n+café €\n\
+         diff --git a/b.txt b/b.txt\n+日本語 ﾃｽﾄ\n\
+         diff --git a/c.txt b/c.txt\n+ok ✓\n"
+    );
+}
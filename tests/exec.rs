@@ -0,0 +1,32 @@
+//! The external command provider.
+
+use gemini_commit_message::prompt::Prompt;
+use gemini_commit_message::{Exec, Provider};
+
+fn prompt() -> Prompt {
+    Prompt {
+        system: "Write a commit message.".to_string(),
+        user: "diff --git a/x b/x".to_string(),
+        schema: None,
+        images: Vec::new(),
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn pipes_prompt_and_reads_message() {
+    let text = Exec::new("cat").generate(&prompt()).unwrap();
+    assert_eq!(text, "Write a commit message.\n\ndiff --git a/x b/x");
+    let text = Exec::new("echo 'fix: answer'").generate(&prompt()).unwrap();
+    assert_eq!(text, "fix: answer");
+}
+
+#[cfg(unix)]
+#[test]
+fn failure_and_silence_are_errors() {
+    let error = Exec::new("echo broken >&2; exit 3")
+        .generate(&prompt())
+        .unwrap_err();
+    assert!(error.to_string().contains("broken"));
+    assert!(Exec::new("true").generate(&prompt()).is_err());
+}
@@ -0,0 +1,27 @@
+//! The old lines a staged change touches, which are blamed to find the
+//! commit it fixes up.
+
+use gemini_commit_message::diff::split_files;
+use gemini_commit_message::fixup::{Target, touched_lines};
+
+#[test]
+fn removed_lines_are_touched() {
+    let diff =
+        "diff --git a/x b/x\n--- a/x\n+++ b/x\n@@ -2,4 +2,4 @@ fn main\n b\n-c\n+C\n d\n-e\n+E\n";
+    assert_eq!(touched_lines(&split_files(diff)[0]), vec![3, 5]);
+}
+
+#[test]
+fn insertions_touch_their_neighbours() {
+    let diff = "diff --git a/x b/x\n--- a/x\n+++ b/x\n@@ -3,2 +3,4 @@\n c\n+d\n+e\n f\n@@ -9,2 +11,3 @@\n i\n j\n+k\n";
+    assert_eq!(touched_lines(&split_files(diff)[0]), vec![3, 4, 10]);
+}
+
+#[test]
+fn message_names_the_subject() {
+    let target = Target {
+        commit: "383981b".to_string(),
+        subject: "feat: add export".to_string(),
+    };
+    assert_eq!(target.message(), "fixup! feat: add export");
+}
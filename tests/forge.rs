@@ -0,0 +1,48 @@
+//! The marked section of pull request descriptions and the API URLs.
+
+use gemini_commit_message::forge::{Forge, MARKER_END, MARKER_START, replace_marked};
+
+#[test]
+fn replaces_only_the_marked_section() {
+    let first = replace_marked("", "First draft");
+    assert_eq!(
+        first,
+        format!("{}\nFirst draft\n{}", MARKER_START, MARKER_END)
+    );
+
+    let edited = format!("Closes #12\n\n{}\n\nReviewer notes", first);
+    let second = replace_marked(&edited, "Second draft\n");
+    assert_eq!(
+        second,
+        format!(
+            "Closes #12\n\n{}\nSecond draft\n{}\n\nReviewer notes",
+            MARKER_START, MARKER_END
+        )
+    );
+    assert_eq!(second.matches(MARKER_START).count(), 1);
+}
+
+#[test]
+fn appends_to_a_description_without_markers() {
+    assert_eq!(
+        replace_marked("Written by hand.\n", "Generated"),
+        format!(
+            "Written by hand.\n\n{}\nGenerated\n{}",
+            MARKER_START, MARKER_END
+        )
+    );
+}
+
+#[test]
+fn encodes_the_branch_in_pull_request_queries() {
+    let github = Forge::detect("git@github.com:octo/app.git").unwrap();
+    assert_eq!(
+        github.pull_requests_url("fix/c++-build"),
+        "https://api.github.com/repos/octo/app/pulls?state=open&head=octo:fix%2Fc%2B%2B-build"
+    );
+    let gitlab = Forge::detect("https://gitlab.com/group/sub/app.git").unwrap();
+    assert_eq!(
+        gitlab.pull_requests_url("issue#12&x"),
+        "https://gitlab.com/api/v4/projects/group%2Fsub%2Fapp/merge_requests?state=opened&source_branch=issue%2312%26x"
+    );
+}
@@ -0,0 +1,31 @@
+//! The log of generated messages.
+
+use gemini_commit_message::history::{Entry, History, Status};
+
+#[test]
+fn records_and_reads_entries() {
+    let path = std::env::temp_dir()
+        .join(format!("gcm-history-{}", std::process::id()))
+        .join("history.jsonl");
+    let _ = std::fs::remove_file(&path);
+    let history = History::at(&path);
+    history
+        .record(&[
+            Entry::new("/repo", "diff", "feat: a", Status::Chosen),
+            Entry::new("/repo", "diff", "feat: b", Status::Rejected),
+        ])
+        .unwrap();
+
+    let entries = history.entries();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].message, "feat: a");
+    assert_eq!(entries[1].status, Status::Rejected);
+    assert_eq!(entries[0].diff, entries[1].diff);
+}
+
+#[test]
+fn dates_are_utc() {
+    let mut entry = Entry::new("", "", "", Status::Shown);
+    entry.time = 951_827_696;
+    assert_eq!(entry.date(), "2000-02-29 12:34");
+}
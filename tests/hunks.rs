@@ -0,0 +1,28 @@
+//! Splitting a file's diff into its header and hunks.
+
+use gemini_commit_message::diff::{hunks, split_files};
+
+#[test]
+fn splits_header_and_hunks() {
+    let diff = "diff --git a/x b/x\nindex 1..2 100644\n--- a/x\n+++ b/x\n@@ -1 +1 @@\n-a\n+b\n@@ -9 +9 @@ fn f\n-c\n+d\n";
+    let files = split_files(diff);
+    let (header, parts) = hunks(&files[0]);
+    assert_eq!(
+        header,
+        "diff --git a/x b/x\nindex 1..2 100644\n--- a/x\n+++ b/x\n"
+    );
+    assert_eq!(
+        parts,
+        vec!["@@ -1 +1 @@\n-a\n+b\n", "@@ -9 +9 @@ fn f\n-c\n+d\n"]
+    );
+    assert_eq!(format!("{}{}", header, parts.concat()), diff);
+}
+
+#[test]
+fn binary_files_have_no_hunks() {
+    let diff = "diff --git a/logo.png b/logo.png\nBinary files a/logo.png and b/logo.png differ\n";
+    let files = split_files(diff);
+    let (header, parts) = hunks(&files[0]);
+    assert_eq!(header, diff);
+    assert!(parts.is_empty());
+}
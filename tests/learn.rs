@@ -0,0 +1,41 @@
+//! Corrections recorded from edited messages.
+
+use gemini_commit_message::learn::{Correction, Corrections, is_edit, prompt_text};
+
+#[test]
+fn only_real_edits_count() {
+    assert!(!is_edit(
+        "feat: add export\n",
+        "feat: add export\n\n# Please enter the commit message\nSigned-off-by: A <a@b>\n"
+    ));
+    assert!(is_edit("feat: add export", "feat(csv): add CSV export"));
+    assert!(!is_edit("feat: add export", "# aborted\n"));
+}
+
+#[test]
+fn keeps_the_latest_per_repository() {
+    let path = std::env::temp_dir()
+        .join(format!("gcm-learn-{}", std::process::id()))
+        .join("corrections.json");
+    let _ = std::fs::remove_file(&path);
+    let corrections = Corrections::at(&path);
+    let correction = |repo: &str, generated: &str, committed: &str| Correction {
+        time: 0,
+        repo: repo.to_string(),
+        generated: generated.to_string(),
+        committed: committed.to_string(),
+    };
+    corrections
+        .record(correction("/a", "fix: x", "fix(io): x"))
+        .unwrap();
+    corrections
+        .record(correction("/b", "feat: y", "feat: Y"))
+        .unwrap();
+    corrections
+        .record(correction("/a", "fix: x", "fix(net): x"))
+        .unwrap();
+
+    let recent = corrections.recent("/a", 3);
+    assert_eq!(recent, vec![correction("/a", "fix: x", "fix(net): x")]);
+    assert!(prompt_text(&recent).contains("Committed instead:\nfix(net): x"));
+}
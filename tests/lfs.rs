@@ -0,0 +1,42 @@
+//! Git LFS pointer files described instead of diffed.
+
+use gemini_commit_message::diff::describe_lfs;
+
+const DIFF: &str = "\
+diff --git a/assets/logo.png b/assets/logo.png
+index 1111111..2222222 100644
+--- a/assets/logo.png
++++ b/assets/logo.png
+@@ -1,3 +1,3 @@
+ version https://git-lfs.github.com/spec/v1
+-oid sha256:aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa
+-size 1048576
++oid sha256:bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb
++size 1572864
+diff --git a/model.bin b/model.bin
+new file mode 100644
+index 0000000..3333333
+--- /dev/null
++++ b/model.bin
+@@ -0,0 +1,3 @@
++version https://git-lfs.github.com/spec/v1
++oid sha256:cccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccc
++size 900
+diff --git a/src/main.rs b/src/main.rs
+index 4444444..5555555 100644
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -1 +1 @@
+-fn main() {}
++fn main() { run() }
+";
+
+#[test]
+fn pointers_become_one_line() {
+    let described = describe_lfs(DIFF).unwrap();
+    assert!(described.contains("updated LFS asset assets/logo.png (size 1.0 MB → 1.5 MB)"));
+    assert!(described.contains("added LFS asset model.bin (size 900 B)"));
+    assert!(!described.contains("sha256:"));
+    assert!(described.contains("+fn main() { run() }"));
+    assert!(describe_lfs(&DIFF[DIFF.find("diff --git a/src").unwrap()..]).is_none());
+}
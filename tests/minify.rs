@@ -0,0 +1,37 @@
+//! Shortening diffs for the model.
+
+use gemini_commit_message::diff::minify;
+
+#[test]
+fn drops_context_collapses_runs_and_repeats() {
+    let mut diff = String::from(
+        "diff --git a/a.rs b/a.rs\n--- a/a.rs\n+++ b/a.rs\n@@ -1,3 +1,3 @@ fn main()\n same\n-old\n+new\n same\n",
+    );
+    diff.push_str(
+        "diff --git a/b.rs b/b.rs\n--- a/b.rs\n+++ b/b.rs\n@@ -9,3 +9,3 @@\n x\n-old\n+new\n",
+    );
+    diff.push_str("diff --git a/c.rs b/c.rs\nnew file mode 100644\n--- /dev/null\n+++ b/c.rs\n@@ -0,0 +1,20 @@\n");
+    for i in 0..20 {
+        diff.push_str(&format!("+line {}\n", i));
+    }
+
+    let minified = minify(&diff);
+    assert!(minified.starts_with(
+        "diff --git a/a.rs b/a.rs\n--- a/a.rs\n+++ b/a.rs\n@@ -1,3 +1,3 @@ fn main()\n-old\n+new\n"
+    ));
+    assert!(!minified.contains(" same"));
+    assert!(minified.contains("@@ -9,3 +9,3 @@\n[same change as a.rs @@ -1,3 +1,3 @@]\n"));
+    assert!(minified.contains("+line 7\n+… 9 more added lines\n+line 17\n"));
+    assert!(!minified.contains("+line 8\n"));
+}
+
+#[test]
+fn keeps_lines_starting_with_other_characters() {
+    let diff = "diff --git a/mail.txt b/mail.txt\n--- a/mail.txt\n+++ b/mail.txt\n@@ -1,2 +1,3 @@\néchec\n「引用」\n+réponse\n";
+    let minified = minify(diff);
+    assert!(
+        minified.ends_with("@@ -1,2 +1,3 @@\néchec\n「引用」\n+réponse\n"),
+        "{}",
+        minified
+    );
+}
@@ -0,0 +1,47 @@
+//! Notebook diffs rebuilt from cell sources.
+
+use gemini_commit_message::diff::unified;
+use gemini_commit_message::notebook::{cell_sources, rewrite};
+
+const OLD: &str = r##"{"cells": [
+ {"cell_type": "markdown", "metadata": {}, "source": ["# Sales\n", "Monthly report"]},
+ {"cell_type": "code", "execution_count": 3, "metadata": {},
+  "outputs": [{"output_type": "stream", "text": ["   month  total\n"]}],
+  "source": ["df = load()\n", "df.head()"]}
+], "metadata": {}, "nbformat": 4, "nbformat_minor": 5}"##;
+
+const NEW: &str = r##"{"cells": [
+ {"cell_type": "markdown", "metadata": {}, "source": ["# Sales\n", "Monthly report"]},
+ {"cell_type": "code", "execution_count": 9, "metadata": {},
+  "outputs": [{"output_type": "display_data", "data": {"image/png": "iVBORw0KGgo="}}],
+  "source": ["df = load()\n", "df.plot()"]}
+], "metadata": {}, "nbformat": 4, "nbformat_minor": 5}"##;
+
+#[test]
+fn keeps_only_cell_sources() {
+    assert_eq!(
+        cell_sources(OLD).unwrap(),
+        "# %% [markdown]\n# Sales\nMonthly report\n# %% [code]\ndf = load()\ndf.head()\n"
+    );
+    let diff = "diff --git a/r.ipynb b/r.ipynb\nindex 1111111..2222222 100644\n\
+                --- a/r.ipynb\n+++ b/r.ipynb\n@@ -1,9 +1,9 @@\n-    \"execution_count\": 3,\n";
+    let rewritten = rewrite(diff, |blob| match blob {
+        "1111111" => Some(OLD.to_string()),
+        "2222222" => Some(NEW.to_string()),
+        _ => None,
+    })
+    .unwrap();
+    assert!(rewritten.ends_with("+++ b/r.ipynb\n@@ -3,4 +3,4 @@\n Monthly report\n # %% [code]\n df = load()\n-df.head()\n+df.plot()\n"));
+    assert!(!rewritten.contains("execution_count"));
+    assert!(rewrite(diff, |_| None).is_none());
+}
+
+#[test]
+fn unified_diff_groups_nearby_changes() {
+    let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm\nn\n";
+    let new = "a\nB\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm\nN\n";
+    assert_eq!(
+        unified(old, new, 3),
+        "@@ -1,5 +1,5 @@\n a\n-b\n+B\n c\n d\n e\n@@ -11,4 +11,4 @@\n k\n l\n m\n-n\n+N\n"
+    );
+}
@@ -0,0 +1,94 @@
+//! Custom presets read from TOML and the rules they add.
+
+use gemini_commit_message::CommitMessage;
+use gemini_commit_message::preset::Preset;
+use std::fs;
+use std::path::PathBuf;
+
+fn presets_file(name: &str, text: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("gcm-preset-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("presets.toml");
+    fs::write(&path, text).unwrap();
+    path
+}
+
+#[test]
+fn custom_preset_extends_builtin() {
+    let path = presets_file(
+        "extends",
+        r#"
+[preset.team]
+extends = "angular"
+instructions = "Reference the ticket."
+types = ["feat", "fix"]
+examples = ["feat(api): add pagination"]
+"#,
+    );
+    let preset = Preset::find("team", &[path]).unwrap().unwrap();
+    assert_eq!(preset.max_header_length, Some(100));
+
+    let prompt = preset.prompt_builder().build("diff");
+    assert!(prompt.system.contains("MUST be one of build, ci"));
+    assert!(prompt.system.contains("Reference the ticket."));
+    assert!(prompt.system.contains("feat(api): add pagination"));
+    let schema = prompt.schema.unwrap();
+    assert_eq!(
+        schema["properties"]["type"]["enum"],
+        serde_json::json!(["feat", "fix"])
+    );
+
+    assert!(
+        preset
+            .check(&CommitMessage::parse("fix: handle empty diff"))
+            .is_ok()
+    );
+    assert!(
+        preset
+            .check(&CommitMessage::parse("docs: update readme"))
+            .is_err()
+    );
+}
+
+#[test]
+fn gitmoji_requires_emoji() {
+    let preset = Preset::find("gitmoji", &[]).unwrap().unwrap();
+    assert!(
+        preset
+            .check(&CommitMessage::parse("✨ Add exports"))
+            .is_ok()
+    );
+    // Without the variation selector some models leave out.
+    assert!(
+        preset
+            .check(&CommitMessage::parse("\u{267B} Tidy up"))
+            .is_ok()
+    );
+    assert!(
+        preset
+            .check(&CommitMessage::parse(":bug: Fix crash"))
+            .is_ok()
+    );
+    assert!(preset.check(&CommitMessage::parse("Add exports")).is_err());
+}
+
+#[test]
+fn unknown_base_is_an_error() {
+    let path = presets_file("unknown", "[preset.a]\nextends = \"b\"\n");
+    assert!(Preset::find("a", &[path]).is_err());
+    let path = presets_file("cycle", "[preset.a]\nextends = \"a\"\n");
+    assert!(Preset::find("a", &[path]).is_err());
+    assert!(Preset::find("missing", &[]).unwrap().is_none());
+}
+
+#[test]
+fn template_is_part_of_the_guideline() {
+    let template = "# Explain why\nWhy:\n\nRefs: <ticket>\n";
+    let preset = Preset::find("conventional", &[])
+        .unwrap()
+        .unwrap()
+        .template(template);
+    let prompt = preset.prompt_builder().build("diff");
+    assert!(prompt.system.contains("MUST fill it in"));
+    assert!(prompt.system.contains("Why:\n\nRefs: <ticket>"));
+}
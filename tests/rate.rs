@@ -0,0 +1,71 @@
+//! The shared window: how long a request waits for the per-minute limits.
+
+use gemini_commit_message::rate::{RateLimit, WINDOW, Window, estimate_tokens};
+use std::time::{Duration, Instant};
+
+const SECOND: Duration = Duration::from_secs(1);
+
+#[test]
+fn unlimited_requests_never_wait() {
+    let mut window = Window::new();
+    let now = Instant::now();
+    for _ in 0..100 {
+        assert_eq!(
+            window.delay(RateLimit::default(), 10_000, now),
+            Duration::ZERO
+        );
+        window.record(10_000, now);
+    }
+}
+
+#[test]
+fn waits_for_the_oldest_request_to_expire() {
+    let limit = RateLimit {
+        requests_per_minute: Some(2),
+        tokens_per_minute: None,
+    };
+    let mut window = Window::new();
+    let start = Instant::now();
+    window.record(1, start);
+    window.record(1, start + 10 * SECOND);
+    assert_eq!(window.delay(limit, 1, start + 20 * SECOND), 40 * SECOND);
+    assert_eq!(window.delay(limit, 1, start + WINDOW), Duration::ZERO);
+}
+
+#[test]
+fn waits_until_enough_tokens_expire() {
+    let limit = RateLimit {
+        requests_per_minute: None,
+        tokens_per_minute: Some(1000),
+    };
+    let mut window = Window::new();
+    let start = Instant::now();
+    window.record(400, start);
+    window.record(400, start + 10 * SECOND);
+    assert_eq!(
+        window.delay(limit, 200, start + 20 * SECOND),
+        Duration::ZERO
+    );
+    assert_eq!(window.delay(limit, 300, start + 20 * SECOND), 40 * SECOND);
+    assert_eq!(window.delay(limit, 700, start + 20 * SECOND), 50 * SECOND);
+}
+
+#[test]
+fn an_oversized_request_waits_for_an_empty_window() {
+    let limit = RateLimit {
+        requests_per_minute: None,
+        tokens_per_minute: Some(1000),
+    };
+    let mut window = Window::new();
+    let start = Instant::now();
+    assert_eq!(window.delay(limit, 5000, start), Duration::ZERO);
+    window.record(5000, start);
+    assert_eq!(window.delay(limit, 5000, start + SECOND), 59 * SECOND);
+}
+
+#[test]
+fn estimates_four_bytes_per_token() {
+    assert_eq!(estimate_tokens(0, 0), 0);
+    assert_eq!(estimate_tokens(10, 0), 3);
+    assert_eq!(estimate_tokens(4000, 1), 1258);
+}
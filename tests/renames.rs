@@ -0,0 +1,90 @@
+//! Changes dominated by moved files: the moves are grouped by directory and
+//! the model is asked for the restructuring rather than a list of files.
+
+use gemini_commit_message::PromptBuilder;
+use gemini_commit_message::diff::split_files;
+use gemini_commit_message::renames::{Rename, mapping, narrative, renames};
+
+fn moved(from: &str, to: &str, similarity: u8) -> String {
+    let mut text = format!(
+        "diff --git a/{from} b/{to}\nsimilarity index {similarity}%\nrename from {from}\nrename to {to}\n"
+    );
+    if similarity < 100 {
+        text.push_str(&format!(
+            "index 1111111..2222222 100644\n--- a/{from}\n+++ b/{to}\n@@ -1 +1 @@\n-use crate::utils;\n+use crate::core;\n"
+        ));
+    }
+    text
+}
+
+const EDITED: &str = "diff --git a/Cargo.toml b/Cargo.toml\nindex 3333333..4444444 100644\n--- a/Cargo.toml\n+++ b/Cargo.toml\n@@ -1 +1,2 @@\n [workspace]\n+members = [\"crates/core\"]\n";
+
+fn restructuring() -> String {
+    [
+        moved("src/utils/a.rs", "crates/core/src/a.rs", 100),
+        moved("src/utils/b.rs", "crates/core/src/b.rs", 100),
+        moved("src/utils/nested/c.rs", "crates/core/src/nested/c.rs", 92),
+        moved("src/old_name.rs", "src/new_name.rs", 100),
+        EDITED.to_string(),
+    ]
+    .concat()
+}
+
+#[test]
+fn reads_the_renames_of_a_diff() {
+    let files = split_files(&restructuring());
+    let found = renames(&files);
+    assert_eq!(found.len(), 4);
+    assert_eq!(
+        found[2],
+        Rename {
+            from: "src/utils/nested/c.rs".to_string(),
+            to: "crates/core/src/nested/c.rs".to_string(),
+            similarity: 92,
+        }
+    );
+}
+
+#[test]
+fn groups_moves_by_directory() {
+    let files = split_files(&restructuring());
+    assert_eq!(
+        mapping(&renames(&files)),
+        [
+            "src/utils/* → crates/core/src/* (3 files)",
+            "src/old_name.rs → src/new_name.rs",
+        ]
+    );
+}
+
+#[test]
+fn describes_a_change_dominated_by_moves() {
+    let files = split_files(&restructuring());
+    let narrative = narrative(&files).unwrap();
+    assert!(narrative.starts_with("4 of the 5 changed files are moved or renamed (3 without"));
+    assert!(narrative.contains("`refactor`"));
+
+    let prompt = PromptBuilder::new().build(&restructuring());
+    assert!(prompt.user.starts_with("## Renames\n\n"), "{}", prompt.user);
+}
+
+#[test]
+fn leaves_ordinary_changes_alone() {
+    let few = [moved("src/a.rs", "src/b.rs", 100), EDITED.to_string()].concat();
+    assert_eq!(narrative(&split_files(&few)), None);
+
+    let mostly_edits = [
+        moved("src/a.rs", "lib/a.rs", 100),
+        moved("src/b.rs", "lib/b.rs", 100),
+        moved("src/c.rs", "lib/c.rs", 100),
+        EDITED.repeat(4),
+    ]
+    .concat();
+    assert_eq!(narrative(&split_files(&mostly_edits)), None);
+    assert!(
+        !PromptBuilder::new()
+            .build(&mostly_edits)
+            .user
+            .contains("## Renames")
+    );
+}
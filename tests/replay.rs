@@ -0,0 +1,145 @@
+//! Response parsing against recorded Gemini responses, without credentials.
+//!
+//! Record new fixtures with `GCM_RECORD=<dir>`, then copy the file from that
+//! directory into `tests/fixtures`.
+
+use gemini_commit_message::config::DEFAULT_MODEL;
+use gemini_commit_message::provider::gemini::{Gemini, continuation, fixture_name};
+use gemini_commit_message::{Generator, PromptBuilder, Provider};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A directory holding `tests/fixtures/<fixture>.json` as the recorded
+/// response to the commit message prompt for `diff`.
+fn fixture_dir(fixture: &str, diff: &str) -> PathBuf {
+    let prompt = PromptBuilder::new().build(diff);
+    let dir: PathBuf = std::env::temp_dir().join(format!(
+        "gemini-commit-message-replay-{}-{}",
+        std::process::id(),
+        fixture
+    ));
+    fs::create_dir_all(&dir).unwrap();
+    let source = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(format!("{}.json", fixture));
+    fs::copy(source, dir.join(fixture_name(DEFAULT_MODEL, None, &prompt))).unwrap();
+    dir
+}
+
+/// Replays `tests/fixtures/<fixture>.json` straight from the provider.
+fn replay(fixture: &str, diff: &str) -> Result<String, String> {
+    let prompt = PromptBuilder::new().build(diff);
+    let dir = fixture_dir(fixture, diff);
+    let result = Gemini::new("").replay(&dir).generate(&prompt);
+    fs::remove_dir_all(&dir).unwrap();
+    result.map_err(|e| e.to_string())
+}
+
+#[test]
+fn returns_the_trimmed_text() {
+    let text = replay("text", "describe this diff").unwrap();
+    assert_eq!(
+        text,
+        "feat(cli): add --timings\n\nReport the time spent in each phase."
+    );
+}
+
+#[test]
+fn reports_the_finish_reason_of_a_blocked_answer() {
+    let error = replay("safety", "describe this diff").unwrap_err();
+    assert!(error.contains("finish_reason='SAFETY'"), "{}", error);
+}
+
+#[test]
+fn continues_an_answer_cut_off_by_the_output_limit() {
+    let prompt = PromptBuilder::new().build("describe this diff");
+    let dir = fixture_dir("max_tokens", "describe this diff");
+    let source = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/continuation.json");
+    let rest = continuation(&prompt, "feat(cli): add --timings\n\nReport the time spent");
+    fs::copy(source, dir.join(fixture_name(DEFAULT_MODEL, None, &rest))).unwrap();
+    let text = Gemini::new("").replay(&dir).generate(&prompt);
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(
+        text.unwrap(),
+        "feat(cli): add --timings\n\nReport the time spent in each phase."
+    );
+}
+
+#[test]
+fn thinks_less_when_nothing_was_written_within_the_output_limit() {
+    let prompt = PromptBuilder::new().build("describe this diff");
+    let dir = fixture_dir("max_tokens_thinking", "describe this diff");
+    // Only the retry with the recovery budget of 1024 has this recording.
+    let source = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/text.json");
+    fs::copy(
+        source,
+        dir.join(fixture_name(DEFAULT_MODEL, Some(1024), &prompt)),
+    )
+    .unwrap();
+    let text = Gemini::new("").replay(&dir).generate(&prompt);
+    fs::remove_dir_all(&dir).unwrap();
+    assert!(text.unwrap().starts_with("feat"));
+}
+
+#[test]
+fn asks_for_the_rest_of_a_cut_off_answer() {
+    // The continuation is a request of its own, which has no recording here.
+    let error = replay("max_tokens", "describe this diff").unwrap_err();
+    assert!(error.contains("no recorded response"), "{}", error);
+}
+
+#[test]
+fn reports_the_prompt_feedback_of_a_blocked_prompt() {
+    let error = replay("blocked_prompt", "describe this diff").unwrap_err();
+    assert!(error.contains("\"blockReason\":\"OTHER\""), "{}", error);
+}
+
+#[test]
+fn reports_the_api_error_message() {
+    let error = replay("invalid_key", "describe this diff").unwrap_err();
+    assert!(error.contains("400"), "{}", error);
+    assert!(error.contains("API key not valid"), "{}", error);
+}
+
+#[test]
+fn assembles_a_structured_reply() {
+    let dir = fixture_dir("structured", "describe this diff");
+    // The fixture was recorded for the diff alone, without repository context.
+    let message = Generator::new(Gemini::new("").replay(&dir))
+        .recent_commits(0)
+        .generate_for_diff("describe this diff")
+        .unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(
+        message.to_string(),
+        "feat(cli): add --timings\n\nReport the time spent in each phase.\n\nRefs: #42"
+    );
+}
+
+#[test]
+fn strips_a_preamble_and_fence_from_a_text_reply() {
+    let dir = fixture_dir("wrapped", "describe this diff");
+    // The fixture was recorded for the diff alone, without repository context.
+    let message = Generator::new(Gemini::new("").replay(&dir))
+        .recent_commits(0)
+        .generate_for_diff("describe this diff")
+        .unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(
+        message.to_string(),
+        "fix(parser): accept multiple spaces in strings\n\nSplit on runs of whitespace instead of single spaces."
+    );
+}
+
+#[test]
+fn fails_without_a_recorded_response() {
+    let error = Gemini::new("")
+        .replay(std::env::temp_dir().join("gemini-commit-message-no-fixtures"))
+        .generate(&PromptBuilder::new().build("describe this diff"))
+        .unwrap_err();
+    assert!(
+        error.to_string().contains("no recorded response"),
+        "{}",
+        error
+    );
+}
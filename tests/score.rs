@@ -0,0 +1,31 @@
+//! Ranking candidates by local checks and heuristics.
+
+use gemini_commit_message::CommitMessage;
+use gemini_commit_message::preset::Preset;
+use gemini_commit_message::score::{rank, score};
+
+const DIFF: &str = "diff --git a/src/parser.rs b/src/parser.rs\n--- a/src/parser.rs\n+++ b/src/parser.rs\n@@ -1 +1 @@\n-a\n+b\n";
+
+#[test]
+fn prefers_valid_specific_messages() {
+    let preset = Preset::find("conventional", &[]).unwrap().unwrap();
+    let specific = CommitMessage::parse("fix(parser): handle empty input");
+    let vague = CommitMessage::parse("fix: minor changes");
+    let invalid = CommitMessage::parse("Fixed some stuff in the parser");
+    assert!(score(&specific, &preset, DIFF) > score(&vague, &preset, DIFF));
+    assert!(score(&vague, &preset, DIFF) > score(&invalid, &preset, DIFF));
+
+    let mut candidates = vec![invalid.clone(), vague.clone(), specific.clone()];
+    rank(&mut candidates, &preset, DIFF);
+    assert_eq!(candidates, vec![specific, vague, invalid]);
+}
+
+#[test]
+fn ties_keep_the_model_order() {
+    let preset = Preset::find("conventional", &[]).unwrap().unwrap();
+    let first = CommitMessage::parse("fix(parser): handle empty input");
+    let second = CommitMessage::parse("fix(parser): handle blank input");
+    let mut candidates = vec![first.clone(), second.clone()];
+    rank(&mut candidates, &preset, DIFF);
+    assert_eq!(candidates, vec![first, second]);
+}
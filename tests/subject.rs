@@ -0,0 +1,51 @@
+//! The house style for the summary: mood, capitalization and period.
+
+use gemini_commit_message::CommitMessage;
+use gemini_commit_message::preset::Preset;
+use gemini_commit_message::subject::{Mood, SubjectStyle};
+
+#[test]
+fn rewrites_known_verbs_case_and_period() {
+    let past = SubjectStyle {
+        mood: Some(Mood::Past),
+        capitalize: Some(true),
+        period: Some(true),
+    };
+    assert_eq!(past.apply("add CSV export"), "Added CSV export.");
+    assert_eq!(
+        past.apply("fixes crash on empty diff."),
+        "Fixed crash on empty diff."
+    );
+    // Unknown first words and names are left alone.
+    assert_eq!(past.apply("iOS build flags"), "iOS build flags.");
+
+    let imperative = SubjectStyle {
+        mood: Some(Mood::Imperative),
+        capitalize: Some(false),
+        period: Some(false),
+    };
+    assert_eq!(imperative.apply("Wrote the docs."), "write the docs");
+    assert_eq!(imperative.apply("API docs..."), "API docs...");
+}
+
+#[test]
+fn preset_applies_style_after_the_prefix() {
+    let mut preset = Preset::find("conventional", &[]).unwrap().unwrap();
+    preset.subject = SubjectStyle {
+        mood: Some(Mood::Past),
+        capitalize: None,
+        period: Some(true),
+    };
+    let mut message = CommitMessage::parse("feat(api): add pagination");
+    preset.apply(&mut message);
+    assert_eq!(message.header, "feat(api): added pagination.");
+    let prompt = preset.prompt_builder().build("diff");
+    assert!(prompt.system.contains("MUST use the past tense"));
+
+    let mut gitmoji = Preset::find("gitmoji", &[]).unwrap().unwrap();
+    gitmoji.subject.period = Some(true);
+    let mut message = CommitMessage::parse("✨ Add exports");
+    gitmoji.apply(&mut message);
+    assert_eq!(message.header, "✨ Add exports.");
+    assert!(gitmoji.check(&message).is_ok());
+}
@@ -0,0 +1,21 @@
+//! Configured trailers added to generated messages.
+
+use gemini_commit_message::CommitMessage;
+use gemini_commit_message::message::Footer;
+
+#[test]
+fn trailers_go_before_sign_off_once() {
+    let mut message = CommitMessage::parse(
+        "fix: handle empty diff\n\nRefs: #12\nReviewed-by: Ann <a@x>\nSigned-off-by: Bob <b@x>",
+    );
+    let trailers: Vec<Footer> = ["reviewed-by: Ann <a@x>", "Tracked-by: TEAM-1"]
+        .iter()
+        .map(|line| Footer::parse(line).unwrap())
+        .collect();
+    message.add_trailers(&trailers);
+    message.add_trailers(&trailers);
+    assert_eq!(
+        message.to_string(),
+        "fix: handle empty diff\n\nRefs: #12\nReviewed-by: Ann <a@x>\nTracked-by: TEAM-1\nSigned-off-by: Bob <b@x>"
+    );
+}
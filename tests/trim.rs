@@ -0,0 +1,105 @@
+//! Shortening oversized diffs by the value of each file, and telling what
+//! is shortened.
+
+use gemini_commit_message::PromptBuilder;
+use gemini_commit_message::diff::{HEADERLESS_DIFF, rank, trim, trim_report};
+
+fn file(path: &str, lines: usize) -> String {
+    let mut text = format!(
+        "diff --git a/{0} b/{0}\n--- a/{0}\n+++ b/{0}\n@@ -1,0 +1,{1} @@\n",
+        path, lines
+    );
+    for i in 0..lines {
+        text.push_str(&format!("+line {} of {}\n", i, path));
+    }
+    text
+}
+
+#[test]
+fn ranks_source_before_config_docs_and_assets() {
+    assert_eq!(rank("src/main.rs"), 0);
+    assert_eq!(rank("Cargo.toml"), 1);
+    assert_eq!(rank("docs/guide.html"), 2);
+    assert_eq!(rank("README.md"), 2);
+    assert_eq!(rank("assets/logo.png"), 3);
+    assert_eq!(rank("tests/__snapshots__/view.snap"), 3);
+    assert_eq!(rank("package-lock.json"), 3);
+}
+
+#[test]
+fn keeps_source_and_cuts_from_the_bottom() {
+    let diff = [
+        file("assets/logo.svg", 200),
+        file("README.md", 400),
+        file("src/lib.rs", 50),
+    ]
+    .concat();
+    assert_eq!(trim(&diff, diff.len()), None);
+
+    let trimmed = trim(&diff, 8000).unwrap();
+    assert!(trimmed.starts_with("diff --git a/src/lib.rs"));
+    assert!(trimmed.contains("+line 49 of src/lib.rs"));
+    assert!(trimmed.contains("more lines of README.md cut]"));
+    assert!(!trimmed.contains("line 0 of assets/logo.svg"));
+    assert!(trimmed.ends_with(
+        "[left out to keep the prompt short: README.md (+400 -0), assets/logo.svg (+200 -0)]\n"
+    ));
+    assert!(trimmed.len() < 8200);
+}
+
+#[test]
+fn reports_the_largest_files_and_what_is_left_out() {
+    let diff = [
+        file("assets/logo.svg", 200),
+        file("README.md", 400),
+        file("src/lib.rs", 50),
+    ]
+    .concat();
+    assert_eq!(trim_report(&diff, diff.len()), None);
+
+    let report = trim_report(&diff, 8000).unwrap();
+    assert_eq!(report.bytes, diff.len());
+    assert_eq!(report.max_bytes, 8000);
+    let largest: Vec<&str> = report.largest.iter().map(|(p, _)| p.as_str()).collect();
+    assert_eq!(largest, ["README.md", "assets/logo.svg", "src/lib.rs"]);
+    assert_eq!(report.cut.as_deref(), Some("README.md"));
+    assert_eq!(report.omitted, ["assets/logo.svg"]);
+}
+
+#[test]
+fn the_prompt_builder_applies_its_limit() {
+    let diff = file("src/lib.rs", 1000);
+    let builder = PromptBuilder::new().max_diff_bytes(4096);
+    assert!(builder.trim_report(&diff).is_some());
+    assert!(
+        builder
+            .build(&diff)
+            .user
+            .contains("more lines of src/lib.rs cut]")
+    );
+    assert_eq!(PromptBuilder::new().trim_report(&diff), None);
+}
+
+#[test]
+fn cuts_a_diff_without_file_headers() {
+    let diff = "--- a.txt\n+++ a.txt\n@@ -1 +1 @@\n-old\n+änderung ".repeat(100);
+    // 999 bytes end inside the 21st "ä".
+    let trimmed = trim(&diff, 999).unwrap();
+    assert!(trimmed.starts_with("--- a.txt\n"));
+    let (kept, note) = trimmed.split_once("\n[... ").unwrap();
+    assert_eq!(kept.len(), 998);
+    assert!(diff.starts_with(kept));
+    assert_eq!(
+        note,
+        format!("{} more bytes cut]\n", diff.len() - kept.len())
+    );
+}
+
+#[test]
+fn reports_a_diff_without_file_headers_as_cut() {
+    let diff = "--- a.txt\n+++ a.txt\n@@ -1 +1 @@\n-old\n+new\n".repeat(100);
+    let report = trim_report(&diff, 1000).unwrap();
+    assert_eq!(report.largest, [(HEADERLESS_DIFF.to_string(), diff.len())]);
+    assert_eq!(report.cut.as_deref(), Some(HEADERLESS_DIFF));
+    assert!(report.omitted.is_empty());
+}
@@ -0,0 +1,31 @@
+//! Verifying downloaded release assets.
+
+use gemini_commit_message::update::{checksum, expected_checksum};
+
+#[test]
+fn finds_the_checksum_of_an_asset() {
+    let sums = "\
+0123ABCD  gemini-commit-message-x86_64-linux
+4567ef01 *gemini-commit-message-x86_64-windows.exe
+";
+    assert_eq!(
+        expected_checksum(sums, "gemini-commit-message-x86_64-linux").as_deref(),
+        Some("0123abcd")
+    );
+    assert_eq!(
+        expected_checksum(sums, "gemini-commit-message-x86_64-windows.exe").as_deref(),
+        Some("4567ef01")
+    );
+    assert_eq!(
+        expected_checksum(sums, "gemini-commit-message-aarch64-macos"),
+        None
+    );
+}
+
+#[test]
+fn checksum_is_hex_sha256() {
+    assert_eq!(
+        checksum(b"abc"),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+}